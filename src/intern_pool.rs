@@ -1,6 +1,10 @@
 use crate::token::{TOKEN_TYPES_ENUM, TOKEN_TYPES_STR, TokenType};
 use serde::Serialize;
-use std::{collections::HashMap, path::PathBuf};
+use std::rc::Rc;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 /// The RefCell is only used to store thread-local serialization
 ///     contexts. This serialization only happens in test builds.
@@ -11,25 +15,35 @@ use std::cell::RefCell;
 /// Serialization is automatically implemented, but a custom
 ///     version is specifically defined for test builds.
 /// Therefore, Serialize is not derived for test builds.
+/// Ids are ordered by when they were first inserted.
 #[cfg_attr(not(test), derive(Serialize))]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(crate) struct SymbolId(usize);
 
 /// PathId holds the id of a unique PathBuf.
 /// Serialization is automatically implemented, but a custom
 ///     version is specifically defined for test builds.
 /// Therefore, Serialize is not derived for test builds.
+/// Ids are ordered by when they were first inserted.
 #[cfg_attr(not(test), derive(Serialize))]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(crate) struct PathId(usize);
 
+/// StringId holds the id of a unique string literal.
+/// Serialization is automatically implemented, but a custom
+///     version is specifically defined for test builds.
+/// Therefore, Serialize is not derived for test builds.
+#[cfg_attr(not(test), derive(Serialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct StringId(usize);
+
 /// Since the usize fields of PathId is private, a PathId is predefined
 ///     for tests, as tests often use only one file, and the id is going
 ///     to be 0.
 #[cfg(test)]
 pub(crate) const TEST_PATH_ID: PathId = PathId(0);
 
-/// The InternPool interns symbols and paths.
+/// The InternPool interns symbols, paths and string literals.
 pub(crate) struct InternPool {
     /// The next symbol id value. Increments when a new symbol is inserted.
     symbol_counter: SymbolId,
@@ -47,6 +61,12 @@ pub(crate) struct InternPool {
     path_counter: PathId,
     path_pool: HashMap<PathBuf, PathId>,
     path_reverse: Option<Vec<PathBuf>>,
+
+    /// String literals are needed after parsing (asm bodies, for example),
+    ///     so unlike symbols and paths, they can be looked up at any time.
+    ///     Both fields share the same allocation of each string.
+    string_pool: HashMap<Rc<str>, StringId>,
+    strings: Vec<Rc<str>>,
 }
 
 /// The keyword part of the symbol table is constructed using the constants defined in
@@ -56,11 +76,9 @@ pub(crate) fn is_keyword(id: &SymbolId) -> bool {
     id.0 < TOKEN_TYPES_STR.len()
 }
 
-/// Get the SymbolId for a keyword. Panics if keyword is not a keyword.
-/// This function should have been made const, but due to the limitations
-///     of the current Rust version, Rust can't safely make this const.
-pub(crate) fn get_keyword_symbol_id(keyword: &'static str) -> SymbolId {
-    SymbolId(TOKEN_TYPES_STR.iter().position(|&x| x == keyword).unwrap())
+/// Get the SymbolId for a keyword from its TokenType.
+pub(crate) fn get_token_type_symbol_id(keyword: TokenType) -> SymbolId {
+    SymbolId(TOKEN_TYPES_ENUM.iter().position(|&x| x == keyword).unwrap())
 }
 
 /// Get the TokenType enum value of a keyword. Panics if the id
@@ -73,6 +91,14 @@ pub(crate) fn get_keyword(id: &SymbolId) -> TokenType {
     }
 }
 
+/// Iterates a map keyed by ids in id order, which is insertion order,
+///     so the output doesn't depend on how the map happens to hash.
+pub(crate) fn iter_by_id<K: Ord + Copy, V>(map: &HashMap<K, V>) -> impl Iterator<Item = (K, &V)> {
+    let mut entries: Vec<_> = map.iter().map(|(key, value)| (*key, value)).collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries.into_iter()
+}
+
 impl InternPool {
     /// Create an InternPool with keywords built in.
     pub(crate) fn new() -> InternPool {
@@ -84,6 +110,9 @@ impl InternPool {
             path_counter: PathId(0),
             path_pool: HashMap::new(),
             path_reverse: None,
+
+            string_pool: HashMap::new(),
+            strings: Vec::new(),
         };
         for keyword in TOKEN_TYPES_STR {
             pool.symbol_pool
@@ -93,32 +122,81 @@ impl InternPool {
         pool
     }
 
+    /// Creates an InternPool with the given symbols inserted right after the keywords,
+    ///     so their ids are predictable. Returns the ids in the same order.
+    pub(crate) fn from_symbols(symbols: &[&str]) -> (InternPool, Vec<SymbolId>) {
+        let mut pool = InternPool::new();
+        let ids = symbols
+            .iter()
+            .map(|symbol| pool.insert_symbol(symbol.to_string()))
+            .collect();
+        (pool, ids)
+    }
+
+    /// Like InternPool::from_symbols, but for paths. The first path gets id 0.
+    pub(crate) fn from_paths(paths: &[&str]) -> (InternPool, Vec<PathId>) {
+        let mut pool = InternPool::new();
+        let ids = paths
+            .iter()
+            .map(|path| pool.insert_path(PathBuf::from(path)))
+            .collect();
+        (pool, ids)
+    }
+
     /// Inserts the token into the pool and returns the SymbolId.
     /// If the token exists, the existing SymbolId is returned.
     pub(crate) fn insert_symbol(&mut self, token: String) -> SymbolId {
         std::debug_assert!(self.symbol_reverse.is_none());
-        if self.symbol_pool.contains_key(&token) {
-            self.symbol_pool[&token]
-        } else {
-            let id = self.symbol_counter;
-            self.symbol_pool.insert(token, self.symbol_counter);
-            self.symbol_counter.0 += 1;
+        let counter = &mut self.symbol_counter;
+        *self.symbol_pool.entry(token).or_insert_with(|| {
+            let id = *counter;
+            counter.0 += 1;
             id
-        }
+        })
     }
 
     /// Inserts the path into the pool and returns the PathId.
     /// If the path exists, the existing PathId is returned.
     pub(crate) fn insert_path(&mut self, path: PathBuf) -> PathId {
         std::debug_assert!(self.path_reverse.is_none());
-        if self.path_pool.contains_key(&path) {
-            self.path_pool[&path]
-        } else {
-            let id = self.path_counter;
-            self.path_pool.insert(path, self.path_counter);
-            self.path_counter.0 += 1;
+        let counter = &mut self.path_counter;
+        *self.path_pool.entry(path).or_insert_with(|| {
+            let id = *counter;
+            counter.0 += 1;
             id
+        })
+    }
+
+    /// Inserts the string literal into the pool and returns the StringId.
+    /// If the string exists, the existing StringId is returned.
+    pub(crate) fn insert_string(&mut self, string: String) -> StringId {
+        if let Some(id) = self.string_pool.get(string.as_str()) {
+            return *id;
         }
+        let id = StringId(self.strings.len());
+        let string: Rc<str> = string.into();
+        self.strings.push(string.clone());
+        self.string_pool.insert(string, id);
+        id
+    }
+
+    /// Gets the content of a string literal. Unlike the reverse lookups,
+    ///     this doesn't stop the pool from being inserted into.
+    pub(crate) fn get_string(&self, id: StringId) -> &str {
+        &self.strings[id.0]
+    }
+
+    /// Ranks the paths by their value, so things can be ordered by path
+    ///     rather than by when their paths were interned.
+    pub(crate) fn path_ranks(&self) -> HashMap<PathId, usize> {
+        std::debug_assert!(self.path_reverse.is_none());
+        let mut paths: Vec<_> = self.path_pool.iter().collect();
+        paths.sort_unstable();
+        paths
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (_, id))| (*id, rank))
+            .collect()
     }
 
     /// If the token exists, returns the SymbolId; otherwise, returns None.
@@ -176,6 +254,39 @@ impl InternPool {
             None
         }
     }
+
+    /// Consumes the pool once nothing else will be interned, so ids can be
+    ///     looked up through a shared reference.
+    pub(crate) fn finalize(mut self) -> ResolvedPool {
+        self.reverse();
+        ResolvedPool {
+            symbols: self.symbol_reverse.unwrap(),
+            paths: self.path_reverse.unwrap(),
+            strings: self.strings,
+        }
+    }
+}
+
+/// A read-only InternPool, created by `InternPool::finalize`.
+/// Lookups panic for ids that didn't come from the pool.
+pub(crate) struct ResolvedPool {
+    symbols: Vec<String>,
+    paths: Vec<PathBuf>,
+    strings: Vec<Rc<str>>,
+}
+
+impl ResolvedPool {
+    pub(crate) fn name(&self, id: SymbolId) -> &str {
+        &self.symbols[id.0]
+    }
+
+    pub(crate) fn path(&self, id: PathId) -> &Path {
+        &self.paths[id.0]
+    }
+
+    pub(crate) fn string(&self, id: StringId) -> &str {
+        &self.strings[id.0]
+    }
 }
 
 #[cfg(test)]
@@ -228,3 +339,89 @@ impl Serialize for PathId {
         serializer.serialize_str(path.unwrap().to_str().unwrap())
     }
 }
+
+/// The test-only serialization method for StringIds.
+#[cfg(test)]
+impl Serialize for StringId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        SYMBOL_CONTEXT.with(|c| serializer.serialize_str(c.borrow().get_string(*self)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_pools() {
+        let (mut pool, ids) = InternPool::from_symbols(&["a", "b", "c"]);
+        let first = TOKEN_TYPES_STR.len();
+        assert_eq!(
+            ids,
+            vec![SymbolId(first), SymbolId(first + 1), SymbolId(first + 2)]
+        );
+        assert_eq!(pool.search_symbol("b"), Some(ids[1]));
+        assert_eq!(pool.insert_symbol("d".to_string()), SymbolId(first + 3));
+
+        let (mut pool, ids) = InternPool::from_paths(&["main.code", "lib.code", "main.code"]);
+        assert_eq!(ids, vec![PathId(0), PathId(1), PathId(0)]);
+        assert_eq!(
+            pool.path_reverse_lookup(ids[1]),
+            Some(PathBuf::from("lib.code"))
+        );
+    }
+
+    #[test]
+    fn iteration_by_id() {
+        let names = ["zeta", "alpha", "mu", "beta", "omega", "gamma"];
+        let (_, ids) = InternPool::from_symbols(&names);
+        let map: HashMap<_, _> = ids
+            .iter()
+            .zip(names)
+            .map(|(id, name)| (*id, name))
+            .collect();
+        let ordered: Vec<_> = iter_by_id(&map).map(|(_, name)| *name).collect();
+        assert_eq!(ordered, names);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let (_, paths) = InternPool::from_paths(&["b.code", "a.code"]);
+        assert!(paths[0] < paths[1]);
+    }
+
+    #[test]
+    fn resolved_pool() {
+        let (mut pool, symbols) = InternPool::from_symbols(&["main", "x"]);
+        let path = pool.insert_path(PathBuf::from("main.code"));
+        let string = pool.insert_string("hello".to_string());
+        // Reverse lookups made before finalizing don't lose anything.
+        assert_eq!(pool.symbol_reverse_lookup(symbols[0]).unwrap(), "main");
+        let pool = pool.finalize();
+        assert_eq!(pool.name(symbols[0]), "main");
+        assert_eq!(pool.name(symbols[1]), "x");
+        assert_eq!(pool.name(get_token_type_symbol_id(TokenType::Fn)), "fn");
+        assert_eq!(pool.path(path), Path::new("main.code"));
+        assert_eq!(pool.string(string), "hello");
+    }
+
+    #[test]
+    fn repeated_inserts() {
+        let mut pool = InternPool::new();
+        let first = TOKEN_TYPES_STR.len();
+        let names = ["x", "y", "x", "if", "z", "y"];
+        let ids: Vec<_> = names
+            .iter()
+            .map(|name| pool.insert_symbol(name.to_string()))
+            .collect();
+        let expected = [first, first + 1, first, ids[3].0, first + 2, first + 1];
+        assert_eq!(ids.iter().map(|id| id.0).collect::<Vec<_>>(), expected);
+        assert_eq!(ids[3], get_token_type_symbol_id(TokenType::If));
+        let paths: Vec<_> = ["a", "b", "a"]
+            .iter()
+            .map(|path| pool.insert_path(PathBuf::from(path)))
+            .collect();
+        assert_eq!(paths, vec![PathId(0), PathId(1), PathId(0)]);
+    }
+}
@@ -1,20 +1,47 @@
 use super::*;
+use std::ops::Range;
 
 impl Lexer {
-    pub(super) fn read_identifier(&mut self, pool: &mut InternPool) -> TokenValue {
-        let mut identifier = Vec::new();
+    /// Keywords are looked up before interning, so only identifiers are interned.
+    pub(super) fn read_identifier(&mut self, pool: &mut InternPool) -> Result<TokenValue, Error> {
+        let word = self.read_word()?;
+        if let Some(keyword) = keyword::keyword_lookup(&self.input[word.clone()]) {
+            return Ok(TokenValue::Keyword(keyword));
+        }
+        let id = pool.insert_symbol(self.input[word].iter().collect());
+        Ok(TokenValue::Identifier(id))
+    }
+
+    /// Reads an attribute like `@packed`. Keywords are valid attribute names.
+    pub(super) fn read_attribute(&mut self, pool: &mut InternPool) -> Result<TokenValue, Error> {
+        debug_assert_eq!(self.peek(), Some(&'@'));
+        self.advance();
+        if !self
+            .peek()
+            .is_some_and(|&ch| ch.is_alphabetic() || ch == '_')
+        {
+            return Err(self.error(
+                ErrorType::UnknownCharacter,
+                "Expected an attribute name after `@`",
+            ));
+        }
+        let name = self.read_word()?;
+        let name = self.input[name].iter().collect();
+        Ok(TokenValue::Attribute(pool.insert_symbol(name)))
+    }
+
+    /// Returns where the word is in the input.
+    /// Stops as soon as the word grows past the limit, like string literals.
+    fn read_word(&mut self) -> Result<Range<usize>, Error> {
+        let start = self.index;
         while let Some(&ch) = self.peek()
             && (ch.is_alphanumeric() || ch == '_')
         {
-            identifier.push(ch);
+            if self.index - start == self.options.max_identifier_length {
+                return Err(self.error(ErrorType::IdentifierTooLong, "Identifier is too long"));
+            }
             self.advance();
         }
-        let identifier = identifier.iter().collect();
-        let id = pool.insert_symbol(identifier);
-        if intern_pool::is_keyword(&id) {
-            TokenValue::Keyword(intern_pool::get_keyword(&id))
-        } else {
-            TokenValue::Identifier(id)
-        }
+        Ok(start..self.index)
     }
 }
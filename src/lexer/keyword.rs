@@ -0,0 +1,63 @@
+//! A trie of the keywords spelled with letters, like `if` and `u8`, so a word
+//!     can be told apart from an identifier before it's interned. Most words
+//!     in a file are keywords, and this saves building and hashing a String
+//!     for each of them.
+
+use crate::token::{TOKEN_TYPES_ENUM, TOKEN_TYPES_STR, TokenType};
+use std::sync::LazyLock;
+
+/// Keywords only use lowercase letters and digits.
+const ALPHABET: usize = 36;
+
+struct Node {
+    /// The index of the child for each character, or 0 if there's none,
+    ///     since the root isn't anyone's child.
+    children: [u16; ALPHABET],
+    keyword: Option<TokenType>,
+}
+
+/// The nodes, with the root first.
+static TRIE: LazyLock<Vec<Node>> = LazyLock::new(|| {
+    let new_node = || Node {
+        children: [0; ALPHABET],
+        keyword: None,
+    };
+    let mut nodes = vec![new_node()];
+    for (word, &keyword) in TOKEN_TYPES_STR.iter().zip(TOKEN_TYPES_ENUM.iter()) {
+        if !word.starts_with(char::is_alphabetic) {
+            continue;
+        }
+        let mut node = 0;
+        for ch in word.chars() {
+            let slot = slot(ch).expect("keywords are lowercase letters and digits");
+            if nodes[node].children[slot] == 0 {
+                nodes.push(new_node());
+                nodes[node].children[slot] = (nodes.len() - 1) as u16;
+            }
+            node = nodes[node].children[slot] as usize;
+        }
+        nodes[node].keyword = Some(keyword);
+    }
+    nodes
+});
+
+fn slot(ch: char) -> Option<usize> {
+    match ch {
+        'a'..='z' => Some(ch as usize - 'a' as usize),
+        '0'..='9' => Some(ch as usize - '0' as usize + 26),
+        _ => None,
+    }
+}
+
+/// The keyword the word spells, if it's one.
+pub(super) fn keyword_lookup(word: &[char]) -> Option<TokenType> {
+    let trie = &*TRIE;
+    let mut node = 0;
+    for &ch in word {
+        node = trie[node].children[slot(ch)?] as usize;
+        if node == 0 {
+            return None;
+        }
+    }
+    trie[node].keyword
+}
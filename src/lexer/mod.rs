@@ -6,20 +6,22 @@
 use crate::intern_pool;
 use crate::intern_pool::{InternPool, PathId};
 use crate::span::Span;
-use crate::token::{Literal, Token, TokenValue};
+use crate::token::{FloatSuffix, Literal, NumberBase, Token, TokenValue};
 
 mod identifier;
+mod keyword;
 mod number;
 mod punctuator;
 mod skip;
 mod string;
 mod utils;
 
-/// The Lexer object, one for a file.
+/// The Lexer object, one for a file at a time.
 /// This struct only holds the state of the Lexer, not the result.
 /// So it can be considered as a intermediate construct.
-/// There's no reason to ever construct Lexer, since Lexer::lex
-///     is what you need.
+/// Lexer::lex is usually what you need. Tools lexing many files can
+///     construct one with Lexer::new and Lexer::reset it for each file,
+///     so the buffer the input is decoded into is reused.
 pub(crate) struct Lexer {
     /// Which file we are lexing
     path: PathId,
@@ -41,6 +43,40 @@ pub(crate) struct Lexer {
     start_index: usize,
     start_line: usize,
     start_column: usize,
+
+    options: LexerOptions,
+}
+
+/// Settings that change how the input is lexed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LexerOptions {
+    /// How many columns apart tab stops are. A tab moves the column to the next
+    ///     tab stop, so columns match what editors show. Defaults to 1,
+    ///     which counts a tab as one column like any other character.
+    ///     0 is treated as 1.
+    pub(crate) tab_width: usize,
+    /// The most bytes a string literal can hold once escapes are decoded.
+    ///     Lexing stops as soon as a literal grows past it, so a huge or
+    ///     unclosed literal can't take up all the memory. Defaults to 1 MiB.
+    pub(crate) max_literal_length: usize,
+    /// The most characters an identifier or attribute name can have.
+    ///     Defaults to 1024, far more than any real name needs.
+    pub(crate) max_identifier_length: usize,
+    /// Whether lines and columns are counted. Callers that only need the token
+    ///     values can turn it off to skip the bookkeeping, and spans then only
+    ///     have an index and a size, with the line and column left at 0.
+    pub(crate) track_positions: bool,
+}
+
+impl Default for LexerOptions {
+    fn default() -> Self {
+        Self {
+            tab_width: 1,
+            max_literal_length: 1 << 20,
+            max_identifier_length: 1 << 10,
+            track_positions: true,
+        }
+    }
 }
 
 /// Lexer error types
@@ -48,6 +84,8 @@ pub(crate) struct Lexer {
 pub(crate) enum ErrorType {
     /// A string that's missing a `"`.
     UnclosedString,
+    /// A block comment that's missing a `*/`.
+    UnclosedComment,
     /// Invalid Unicode escape sequence in a string.
     InvalidEscapeSequence,
     /// Invalid number due to many possible reasons.
@@ -71,6 +109,10 @@ pub(crate) enum ErrorType {
     ///     It's generally not good to use Unicode characters to name things,
     ///     as many characters look similar or the same and there are invisible ones.
     UnknownCharacter,
+    /// A string literal is longer than LexerOptions::max_literal_length.
+    LiteralTooLong,
+    /// An identifier is longer than LexerOptions::max_identifier_length.
+    IdentifierTooLong,
 }
 
 /// Lexer error struct
@@ -88,6 +130,27 @@ pub(crate) struct Error {
     msg: &'static str,
 }
 
+/// Lexes tokens one at a time as they're pulled. Created by Lexer::tokens.
+/// It stops after the first error.
+pub(crate) struct TokenStream<'a> {
+    lexer: Lexer,
+    pool: &'a mut InternPool,
+    done: bool,
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let token = self.lexer.next_token(self.pool).transpose();
+        self.done = !matches!(token, Some(Ok(_)));
+        token
+    }
+}
+
 impl Lexer {
     /// Lex the given file content. The InternPool is shared within the whole compilation
     ///     process, so it's passed to the function.
@@ -96,29 +159,122 @@ impl Lexer {
         input: &str,
         pool: &mut InternPool,
     ) -> Result<Vec<Token>, Error> {
+        Self::lex_with_options(path, input, pool, LexerOptions::default())
+    }
+
+    pub(crate) fn lex_with_options(
+        path: PathId,
+        input: &str,
+        pool: &mut InternPool,
+        options: LexerOptions,
+    ) -> Result<Vec<Token>, Error> {
+        Self::tokens_with_options(path, input, pool, options).collect()
+    }
+
+    /// Like Lexer::lex, but tokens are lexed lazily as the stream is iterated.
+    pub(crate) fn tokens<'a>(
+        path: PathId,
+        input: &str,
+        pool: &'a mut InternPool,
+    ) -> TokenStream<'a> {
+        Self::tokens_with_options(path, input, pool, LexerOptions::default())
+    }
+
+    pub(crate) fn tokens_with_options<'a>(
+        path: PathId,
+        input: &str,
+        pool: &'a mut InternPool,
+        options: LexerOptions,
+    ) -> TokenStream<'a> {
+        TokenStream {
+            lexer: Self::new(path, input, options),
+            pool,
+            done: false,
+        }
+    }
+
+    /// Creates a lexer for a file. Its tokens are lexed by Lexer::lex_input.
+    pub(crate) fn new(path: PathId, input: &str, options: LexerOptions) -> Self {
         let mut lexer = Self {
             path,
-            input: input.chars().collect(),
+            input: Vec::new(),
             index: 0,
-            line: 1,
-            column: 1,
+            line: 0,
+            column: 0,
             start_index: 0,
-            start_line: 1,
-            start_column: 1,
+            start_line: 0,
+            start_column: 0,
+            options,
         };
+        lexer.reset(path, input);
+        lexer
+    }
+
+    /// Starts over on a new file, keeping the capacity of the input buffer.
+    pub(crate) fn reset(&mut self, path: PathId, input: &str) {
+        let start = usize::from(self.options.track_positions);
+        self.path = path;
+        self.input.clear();
+        self.input.extend(input.chars());
+        self.index = 0;
+        self.line = start;
+        self.column = start;
+        self.start_index = 0;
+        self.start_line = start;
+        self.start_column = start;
+    }
+
+    /// Lexes the rest of the input, stopping at the first error.
+    pub(crate) fn lex_input(&mut self, pool: &mut InternPool) -> Result<Vec<Token>, Error> {
         let mut tokens = Vec::new();
-        while let Some(token) = lexer.next_token(pool)? {
+        while let Some(token) = self.next_token(pool)? {
             tokens.push(token);
         }
         Ok(tokens)
     }
 }
 
+/// Formats tokens for debugging, one per line, as `line:column kind value`.
+/// Names are reverse looked up, so nothing can be inserted into the pool afterwards.
+pub(crate) fn format_tokens(tokens: &[Token], pool: &mut InternPool) -> String {
+    let mut output = String::new();
+    for token in tokens {
+        let (kind, value) = match &token.value {
+            TokenValue::Identifier(id) => ("Identifier", pool.symbol_reverse_lookup(*id).unwrap()),
+            TokenValue::Keyword(kw) => ("Keyword", kw.as_str().to_string()),
+            TokenValue::Attribute(id) => (
+                "Attribute",
+                format!("@{}", pool.symbol_reverse_lookup(*id).unwrap()),
+            ),
+            TokenValue::Literal(literal) => (
+                "Literal",
+                match literal {
+                    Literal::UInt(uint, NumberBase::Binary) => format!("{uint:#b}"),
+                    Literal::UInt(uint, NumberBase::Decimal) => uint.to_string(),
+                    Literal::UInt(uint, NumberBase::Hexadecimal) => format!("{uint:#x}"),
+                    Literal::Int(int) => int.to_string(),
+                    Literal::Float(float, None) => format!("{float:?}"),
+                    Literal::Float(float, Some(FloatSuffix::F32)) => format!("{float:?}f32"),
+                    Literal::Float(float, Some(FloatSuffix::F64)) => format!("{float:?}f64"),
+                    Literal::String(id) => format!("{:?}", pool.get_string(*id)),
+                },
+            ),
+        };
+        let position = if token.span.size == 0 {
+            "-".to_string()
+        } else {
+            format!("{}:{}", token.span.line, token.span.column)
+        };
+        output.push_str(&format!("{position} {kind} {value}\n"));
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::intern_pool::TEST_PATH_ID;
-    use crate::token::TokenType;
+    use crate::token::{TOKEN_TYPES_ENUM, TOKEN_TYPES_STR, TokenType};
     use std::path::PathBuf;
 
     fn span(line: usize, column: usize, index: usize, size: usize) -> Span {
@@ -147,6 +303,33 @@ mod tests {
     fn test_whitespace_and_comments() {
         assert_lexes("  // this is a comment\n  ", vec![]);
         assert_lexes("// another comment", vec![]);
+        assert_lexes("/* block\n comment */ /**/", vec![]);
+    }
+
+    #[test]
+    fn test_comments_between_tokens() {
+        let values = |input| {
+            let mut pool = InternPool::new();
+            let path_id = pool.insert_path(PathBuf::new());
+            let tokens = Lexer::lex(path_id, input, &mut pool).unwrap();
+            tokens
+                .into_iter()
+                .map(|token| token.value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values("foo /* c */ ( /* c */ a, // c\n b /**/)"),
+            values("foo(a, b)")
+        );
+        // A comment splits a multi-character operator like whitespace does.
+        assert_eq!(values("x/*c*/+/*c*/=/*/c*/-1//c"), values("x + = -1"));
+        assert_eq!(values("a /* c */ .5 /* nested /* */ 1"), values("a .5 1"));
+
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let err = Lexer::lex(path_id, "a\n  /* c * /", &mut pool).unwrap_err();
+        assert_eq!(err.typ, ErrorType::UnclosedComment);
+        assert_eq!((err.span.line, err.span.column), (2, 3));
     }
 
     #[test]
@@ -154,7 +337,7 @@ mod tests {
         assert_lexes(
             "123",
             vec![Token {
-                value: TokenValue::Literal(Literal::UInt(123)),
+                value: TokenValue::Literal(Literal::UInt(123, NumberBase::Decimal)),
                 span: span(1, 1, 0, 3),
             }],
         );
@@ -172,14 +355,14 @@ mod tests {
         assert_lexes(
             "0x1A",
             vec![Token {
-                value: TokenValue::Literal(Literal::UInt(26)),
+                value: TokenValue::Literal(Literal::UInt(26, NumberBase::Hexadecimal)),
                 span: span(1, 1, 0, 4),
             }],
         );
         assert_lexes(
             "0Xff",
             vec![Token {
-                value: TokenValue::Literal(Literal::UInt(255)),
+                value: TokenValue::Literal(Literal::UInt(255, NumberBase::Hexadecimal)),
                 span: span(1, 1, 0, 4),
             }],
         );
@@ -190,30 +373,165 @@ mod tests {
         assert_lexes(
             "0b1010",
             vec![Token {
-                value: TokenValue::Literal(Literal::UInt(10)),
+                value: TokenValue::Literal(Literal::UInt(10, NumberBase::Binary)),
                 span: span(1, 1, 0, 6),
             }],
         );
     }
 
+    #[test]
+    fn test_number_bases() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, "0xFF 0b1010 42", &mut pool).unwrap();
+        let literals: Vec<_> = tokens.into_iter().map(|token| token.value).collect();
+        assert_eq!(
+            literals,
+            vec![
+                TokenValue::Literal(Literal::UInt(255, NumberBase::Hexadecimal)),
+                TokenValue::Literal(Literal::UInt(10, NumberBase::Binary)),
+                TokenValue::Literal(Literal::UInt(42, NumberBase::Decimal)),
+            ]
+        );
+    }
+
     #[test]
     fn test_float_numbers() {
         assert_lexes(
             "123.456",
             vec![Token {
-                value: TokenValue::Literal(Literal::Float(123.456)),
+                value: TokenValue::Literal(Literal::Float(123.456, None)),
                 span: span(1, 1, 0, 7),
             }],
         );
         assert_lexes(
             "-0.5",
             vec![Token {
-                value: TokenValue::Literal(Literal::Float(-0.5)),
+                value: TokenValue::Literal(Literal::Float(-0.5, None)),
                 span: span(1, 1, 0, 4),
             }],
         );
     }
 
+    #[test]
+    fn test_float_suffixes() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, "1.5f32 -2.25f64 1.15", &mut pool).unwrap();
+        let literals: Vec<_> = tokens.into_iter().map(|token| token.value).collect();
+        assert_eq!(
+            literals,
+            vec![
+                TokenValue::Literal(Literal::Float(1.5, Some(FloatSuffix::F32))),
+                TokenValue::Literal(Literal::Float(-2.25, Some(FloatSuffix::F64))),
+                TokenValue::Literal(Literal::Float(1.15, None)),
+            ]
+        );
+        let err = Lexer::lex(path_id, "1.5f16", &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Invalid float suffix");
+    }
+
+    #[test]
+    fn test_float_edges() {
+        assert_lexes(
+            ".5",
+            vec![Token {
+                value: TokenValue::Literal(Literal::Float(0.5, None)),
+                span: span(1, 1, 0, 2),
+            }],
+        );
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let err = Lexer::lex(path_id, "5.", &mut pool).unwrap_err();
+        assert_eq!(err.msg, "No digits found after decimal point");
+        // After an operand, `.` is still a field access.
+        let tokens = Lexer::lex(path_id, "t.0", &mut pool).unwrap();
+        assert_eq!(tokens[1].value, TokenValue::Keyword(TokenType::Dot));
+        let tokens = Lexer::lex(path_id, "1..2", &mut pool).unwrap();
+        assert_eq!(tokens[1].value, TokenValue::Keyword(TokenType::DotDot));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, "@packed struct", &mut pool).unwrap();
+        let packed = pool.search_symbol("packed").unwrap();
+        assert_eq!(tokens[0].value, TokenValue::Attribute(packed));
+        assert_eq!(tokens[0].span, span(1, 1, 0, 7));
+        let err = Lexer::lex(path_id, "@ packed", &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected an attribute name after `@`");
+    }
+
+    #[test]
+    fn test_token_stream() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        // The unclosed string at the end is never reached.
+        let input = format!("let x = {} \"", "x + ".repeat(100_000));
+        let tokens: Vec<_> = Lexer::tokens(path_id, &input, &mut pool)
+            .take(3)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[2].value, TokenValue::Keyword(TokenType::Assign));
+
+        let mut stream = Lexer::tokens(path_id, "x \"", &mut pool);
+        assert!(matches!(stream.next(), Some(Ok(_))));
+        assert!(matches!(stream.next(), Some(Err(_))));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_tab_width() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let input = "\tx\n\t\ty z\n  \tw";
+        let columns = |options| {
+            let tokens = Lexer::lex_with_options(path_id, input, &mut InternPool::new(), options);
+            let tokens = tokens.unwrap();
+            tokens
+                .iter()
+                .map(|token| token.span.column)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(columns(LexerOptions::default()), vec![2, 3, 5, 4]);
+        let zero = LexerOptions {
+            tab_width: 0,
+            ..Default::default()
+        };
+        assert_eq!(columns(zero), vec![2, 3, 5, 4]);
+        let options = LexerOptions {
+            tab_width: 4,
+            ..Default::default()
+        };
+        assert_eq!(columns(options), vec![5, 9, 11, 5]);
+        let tokens = Lexer::lex_with_options(path_id, input, &mut pool, options);
+        // The index still counts characters.
+        assert_eq!(tokens.unwrap()[1].span.index, 5);
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, "let\n  count", &mut pool).unwrap();
+        assert_eq!(
+            format_tokens(&tokens, &mut pool),
+            "1:1 Keyword let\n2:3 Identifier count\n"
+        );
+
+        let mut pool = InternPool::new();
+        let tokens = Lexer::lex(path_id, r#"@packed 0xff -2 1.5 "a\n""#, &mut pool).unwrap();
+        let mut path_only = tokens[1].clone();
+        path_only.span = Span::path_only(path_id);
+        assert_eq!(
+            format_tokens(&tokens, &mut pool),
+            "1:1 Attribute @packed\n1:9 Literal 0xff\n1:14 Literal -2\n1:17 Literal 1.5\n1:21 Literal \"a\\n\"\n"
+        );
+        assert_eq!(format_tokens(&[path_only], &mut pool), "- Literal 0xff\n");
+    }
+
     #[test]
     fn test_number_errors() {
         let mut pool = InternPool::new();
@@ -222,24 +540,130 @@ mod tests {
         assert!(Lexer::lex(path_id, "0b2", &mut pool).is_err());
     }
 
+    #[test]
+    fn test_digit_separators() {
+        let lex_number = |input: &str| {
+            let mut pool = InternPool::new();
+            let path_id = pool.insert_path(PathBuf::new());
+            Lexer::lex(path_id, input, &mut pool)
+                .map(|tokens| tokens.into_iter().next().unwrap().value)
+        };
+        let cases = [
+            (
+                "1_000_000",
+                NumberBase::Decimal,
+                1_000_000,
+                ["1__0", "1_", "12_3_"],
+            ),
+            (
+                "0xFF_FF",
+                NumberBase::Hexadecimal,
+                0xFFFF,
+                ["0x__FF", "0xF__F", "0xFF_"],
+            ),
+            (
+                "0b1010_1010",
+                NumberBase::Binary,
+                0b1010_1010,
+                ["0b_1", "0b1__0", "0b1010_"],
+            ),
+        ];
+        for (valid, base, value, invalid) in cases {
+            assert_eq!(
+                lex_number(valid).unwrap(),
+                TokenValue::Literal(Literal::UInt(value, base))
+            );
+            for input in invalid {
+                let err = lex_number(input).unwrap_err();
+                assert!(matches!(err.typ, ErrorType::InvalidNumber), "{input}");
+                assert_eq!(err.msg, "Misplaced digit separator");
+            }
+        }
+        // A leading `_` makes an identifier instead.
+        assert!(matches!(lex_number("_1"), Ok(TokenValue::Identifier(_))));
+        assert_eq!(
+            lex_number("1_0.2_5").unwrap(),
+            TokenValue::Literal(Literal::Float(10.25, None))
+        );
+        assert!(lex_number("1._5").is_err());
+    }
+
+    /// Lexes a single string literal and returns its content and span.
+    fn lex_string(input: &str) -> (String, Span) {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, input, &mut pool).unwrap();
+        assert_eq!(tokens.len(), 1);
+        let TokenValue::Literal(Literal::String(id)) = tokens[0].value else {
+            panic!("Expected a string literal");
+        };
+        (pool.get_string(id).to_string(), tokens[0].span)
+    }
+
     #[test]
     fn test_string_literals() {
-        assert_lexes(
-            r#""hello""#,
-            vec![Token {
-                value: TokenValue::Literal(Literal::String("hello".to_string())),
-                span: span(1, 1, 0, 7),
-            }],
+        assert_eq!(
+            lex_string(r#""hello""#),
+            ("hello".to_string(), span(1, 1, 0, 7))
         );
-        assert_lexes(
-            r#""escaped \" \n \t \\""#,
-            vec![Token {
-                value: TokenValue::Literal(Literal::String("escaped \" \n \t \\".to_string())),
-                span: span(1, 1, 0, 21),
-            }],
+        assert_eq!(
+            lex_string(r#""escaped \" \n \t \\""#),
+            ("escaped \" \n \t \\".to_string(), span(1, 1, 0, 21))
         );
     }
 
+    #[test]
+    fn test_c_escapes() {
+        let (content, _) = lex_string(r#""\0\a\b\f\v\'""#);
+        assert_eq!(content.as_bytes(), [0x00, 0x07, 0x08, 0x0C, 0x0B, b'\'']);
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let err = Lexer::lex(path_id, r#""\q""#, &mut pool).unwrap_err();
+        assert_eq!(err.typ, ErrorType::InvalidEscapeSequence);
+    }
+
+    #[test]
+    fn test_string_span_is_raw_length() {
+        let (content, span) = lex_string(r#""a\nb""#);
+        assert_eq!(content, "a\nb");
+        assert_eq!(content.chars().count(), 3);
+        assert_eq!(span.size, 6);
+    }
+
+    #[test]
+    fn test_unicode_escapes() {
+        assert_eq!(
+            lex_string(r#""\u{41}""#),
+            ("A".to_string(), span(1, 1, 0, 8))
+        );
+        assert_eq!(
+            lex_string(r#""ab\u{1F600}c""#),
+            ("ab\u{1F600}c".to_string(), span(1, 1, 0, 14))
+        );
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        assert!(Lexer::lex(path_id, r#""\u{}""#, &mut pool).is_err());
+        assert!(Lexer::lex(path_id, r#""\u{110000}""#, &mut pool).is_err());
+    }
+
+    #[test]
+    fn test_string_interning() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path_id, r#""same" "other" "same""#, &mut pool).unwrap();
+        let ids: Vec<_> = tokens
+            .iter()
+            .map(|token| match token.value {
+                TokenValue::Literal(Literal::String(id)) => id,
+                _ => panic!("Expected a string literal"),
+            })
+            .collect();
+        assert_eq!(ids[0], ids[2]);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(pool.get_string(ids[0]), "same");
+        assert_eq!(pool.get_string(ids[1]), "other");
+    }
+
     #[test]
     fn test_unclosed_string() {
         let mut pool = InternPool::new();
@@ -266,7 +690,7 @@ mod tests {
                 span: span(1, 7, 6, 1),
             },
             Token {
-                value: TokenValue::Literal(Literal::UInt(5)),
+                value: TokenValue::Literal(Literal::UInt(5, NumberBase::Decimal)),
                 span: span(1, 9, 8, 1),
             },
             Token {
@@ -302,6 +726,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_every_keyword() {
+        for (keyword, typ) in TOKEN_TYPES_STR.iter().zip(TOKEN_TYPES_ENUM) {
+            assert_lexes(
+                keyword,
+                vec![Token {
+                    value: TokenValue::Keyword(typ),
+                    span: span(1, 1, 0, keyword.chars().count()),
+                }],
+            );
+        }
+    }
+
+    #[test]
+    fn test_modulo_and_mod_keyword() {
+        assert_lexes(
+            "% %= mod",
+            vec![
+                Token {
+                    value: TokenValue::Keyword(TokenType::Modulo),
+                    span: span(1, 1, 0, 1),
+                },
+                Token {
+                    value: TokenValue::Keyword(TokenType::ModuloEq),
+                    span: span(1, 3, 2, 2),
+                },
+                Token {
+                    value: TokenValue::Keyword(TokenType::Mod),
+                    span: span(1, 6, 5, 3),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_span_path() {
+        let mut pool = InternPool::new();
+        pool.insert_path(PathBuf::from("main.code"));
+        let path_id = pool.insert_path(PathBuf::from("lib.code"));
+        let tokens = Lexer::lex(path_id, "let x", &mut pool).unwrap();
+        assert_eq!(tokens.len(), 2);
+        for token in tokens {
+            assert_eq!(token.span.path, path_id);
+            assert_eq!(
+                pool.path_reverse_lookup(token.span.path),
+                Some(PathBuf::from("lib.code"))
+            );
+        }
+    }
+
     #[test]
     fn test_multiline_lexing() {
         let mut pool = InternPool::new();
@@ -321,7 +795,7 @@ mod tests {
                 span: span(2, 3, 8, 1),
             },
             Token {
-                value: TokenValue::Literal(Literal::UInt(10)),
+                value: TokenValue::Literal(Literal::UInt(10, NumberBase::Decimal)),
                 span: span(2, 5, 10, 2),
             },
             Token {
@@ -331,4 +805,137 @@ mod tests {
         ];
         assert_eq!(tokens, expected);
     }
+
+    #[test]
+    fn test_max_literal_length() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let options = LexerOptions {
+            max_literal_length: 8,
+            ..Default::default()
+        };
+        let mut lex = |input| Lexer::lex_with_options(path_id, input, &mut pool, options);
+        assert!(lex(r#"x = "12345678";"#).is_ok());
+        // Escapes count as what they decode to.
+        assert!(lex(r#""1234567\n""#).is_ok());
+        let err = lex(r#"x = "123456789";"#).unwrap_err();
+        assert_eq!(err.typ, ErrorType::LiteralTooLong);
+        assert_eq!(err.span.column, 5);
+        // The limit is hit before the missing quote is noticed.
+        let err = lex("\"123456789\n;").unwrap_err();
+        assert_eq!(err.typ, ErrorType::LiteralTooLong);
+    }
+
+    #[test]
+    fn test_hex_escapes() {
+        assert_eq!(lex_string(r#""\x41\x7F""#).0.as_bytes(), [0x41, 0x7F]);
+        assert_eq!(lex_string(r#""\u{E9}""#).0.as_bytes(), [0xC3, 0xA9]);
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let err = Lexer::lex(path_id, r#""\xE9""#, &mut pool).unwrap_err();
+        assert_eq!(err.typ, ErrorType::InvalidEscapeSequence);
+        assert_eq!(
+            err.msg,
+            "Hex escape sequence isn't ASCII, use `\\u{...}` instead"
+        );
+        assert!(Lexer::lex(path_id, r#""\x80""#, &mut pool).is_err());
+    }
+
+    #[test]
+    fn test_untracked_positions() {
+        let input = "module test;\nprv fn f() -> i32 {\n\treturn 0x1F + \"a\\nb\";\n}\n";
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let tracked = Lexer::lex(path_id, input, &mut pool).unwrap();
+        let options = LexerOptions {
+            track_positions: false,
+            ..Default::default()
+        };
+        let fast = Lexer::lex_with_options(path_id, input, &mut pool, options).unwrap();
+        assert_eq!(tracked.len(), fast.len());
+        for (tracked, fast) in tracked.iter().zip(&fast) {
+            assert_eq!(tracked.value, fast.value);
+            assert_eq!((fast.span.line, fast.span.column), (0, 0));
+            assert_eq!(tracked.span.index, fast.span.index);
+            assert_eq!(tracked.span.size, fast.span.size);
+        }
+        assert_eq!(tracked.last().unwrap().span.line, 4);
+    }
+
+    #[test]
+    fn test_reused_lexer() {
+        let files = [
+            "module long;\nprv fn f() -> i32 {\n    return 0x1F + 2;\n}\n",
+            "x = \"a\\nb\";",
+            "",
+            "let y: f32 = 1.5f32;",
+        ];
+        let mut pool = InternPool::new();
+        let paths: Vec<_> = (0..files.len())
+            .map(|i| pool.insert_path(PathBuf::from(format!("{i}.code"))))
+            .collect();
+        let mut lexer = Lexer::new(paths[0], files[0], LexerOptions::default());
+        let capacity = lexer.input.capacity();
+        for (&path, file) in paths.iter().zip(files) {
+            lexer.reset(path, file);
+            let reused = lexer.lex_input(&mut pool).unwrap();
+            let fresh = Lexer::lex(path, file, &mut pool).unwrap();
+            assert_eq!(reused, fresh);
+        }
+        // The first file is the longest, so the buffer never had to grow.
+        assert_eq!(lexer.input.capacity(), capacity);
+
+        lexer.reset(paths[0], "\"unclosed");
+        assert!(lexer.lex_input(&mut pool).is_err());
+        lexer.reset(paths[1], files[1]);
+        assert_eq!(
+            lexer.lex_input(&mut pool).unwrap(),
+            Lexer::lex(paths[1], files[1], &mut pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_max_identifier_length() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let options = LexerOptions {
+            max_identifier_length: 8,
+            ..Default::default()
+        };
+        let mut lex = |input| Lexer::lex_with_options(path_id, input, &mut pool, options);
+        assert!(lex("abcdefgh;").is_ok());
+        let err = lex("x = abcdefghi;").unwrap_err();
+        assert_eq!(err.typ, ErrorType::IdentifierTooLong);
+        assert_eq!(err.span.column, 5);
+        let err = lex("@abcdefghi").unwrap_err();
+        assert_eq!(err.typ, ErrorType::IdentifierTooLong);
+        let long = "a".repeat(1 << 20);
+        let err = Lexer::lex(path_id, &long, &mut pool).unwrap_err();
+        assert_eq!(err.typ, ErrorType::IdentifierTooLong);
+    }
+
+    #[test]
+    fn test_keyword_lookup() {
+        let mut pool = InternPool::new();
+        let path_id = pool.insert_path(PathBuf::new());
+        let input = "if iff i f fn fnn and andy or u8 u81 i64 isize void voi _if If";
+        let tokens = Lexer::lex(path_id, input, &mut pool).unwrap();
+        for (token, word) in tokens.iter().zip(input.split(' ')) {
+            let expected = match pool.search_symbol(word) {
+                Some(id) if intern_pool::is_keyword(&id) => {
+                    TokenValue::Keyword(intern_pool::get_keyword(&id))
+                }
+                Some(id) => TokenValue::Identifier(id),
+                None => panic!("`{word}` wasn't interned"),
+            };
+            assert_eq!(token.value, expected, "`{word}`");
+        }
+        assert_eq!(keyword::keyword_lookup(&['i', 'f']), Some(TokenType::If));
+        assert_eq!(
+            keyword::keyword_lookup(&['o', 'r']),
+            Some(TokenType::LogicalOr)
+        );
+        assert_eq!(keyword::keyword_lookup(&['i']), None);
+        assert_eq!(keyword::keyword_lookup(&[]), None);
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use crate::token::{FloatSuffix, NumberBase};
 
 impl Lexer {
     /// Reads a number token (dispatches to decimal, float, hex, or binary).
@@ -18,15 +19,56 @@ impl Lexer {
 
     fn read_decimal_or_float_number(&mut self) -> Result<TokenValue, Error> {
         let negative = self.consume_negative_sign();
-        let number = self.collect_digits()?;
-        if self.peek() != Some(&'.') {
+        // A float may leave out its integer part, as in `.5`.
+        let number = if self.peek() == Some(&'.') {
+            0
+        } else {
+            self.collect_digits()?
+        };
+        // `1..` is an integer followed by `..`, not a float.
+        if self.peek() != Some(&'.') || self.peek2() == Some(&'.') {
             return self.make_integer(number, negative);
         }
         self.advance(); // skip '.'
         let fraction = self.collect_fraction()?;
-        let float_value = (number as f64) + fraction;
+        // Parsing the digits as a whole gives the closest float, unlike adding up the parts.
+        let float_value: f64 = format!("{number}.{fraction}").parse().unwrap();
         let final_value = if negative { -float_value } else { float_value };
-        Ok(TokenValue::Literal(Literal::Float(final_value)))
+        let suffix = self.read_float_suffix()?;
+        Ok(TokenValue::Literal(Literal::Float(final_value, suffix)))
+    }
+
+    /// Reads `f32` or `f64` right after a float.
+    fn read_float_suffix(&mut self) -> Result<Option<FloatSuffix>, Error> {
+        if self.peek() != Some(&'f') {
+            return Ok(None);
+        }
+        let suffix: String = self.input[self.index..]
+            .iter()
+            .take_while(|ch| ch.is_alphanumeric() || **ch == '_')
+            .collect();
+        let suffix = match suffix.as_str() {
+            "f32" => FloatSuffix::F32,
+            "f64" => FloatSuffix::F64,
+            _ => return Err(self.error(ErrorType::InvalidNumber, "Invalid float suffix")),
+        };
+        for _ in 0..3 {
+            self.advance();
+        }
+        Ok(Some(suffix))
+    }
+
+    /// Whether a `.` at the current position starts a float like `.5`.
+    /// It doesn't if it follows an operand, since `t.0` is a field access.
+    pub(super) fn starts_fraction(&self) -> bool {
+        let follows_operand = match self.index.checked_sub(1) {
+            Some(prev) => {
+                let ch = self.input[prev];
+                ch.is_alphanumeric() || ch == '_' || ch == ')' || ch == ']'
+            }
+            None => false,
+        };
+        self.peek2().is_some_and(char::is_ascii_digit) && !follows_operand
     }
 
     fn consume_negative_sign(&mut self) -> bool {
@@ -38,48 +80,59 @@ impl Lexer {
         }
     }
 
-    fn collect_digits(&mut self) -> Result<u64, Error> {
-        let mut number = 0u64;
-        let mut found = false;
+    /// Collects the digits of a number in the given radix, without the separators.
+    /// Digits may be grouped with `_`, as in `0xFF_FF`, but a separator can't
+    ///     come first, last, or right after another one.
+    fn collect_digit_run(&mut self, radix: u32) -> Result<String, Error> {
+        let mut digits = String::new();
+        let mut separated = false;
         while let Some(&ch) = self.peek() {
-            if !ch.is_ascii_digit() {
+            if ch == '_' {
+                if digits.is_empty() || separated {
+                    return Err(self.error(ErrorType::InvalidNumber, "Misplaced digit separator"));
+                }
+                separated = true;
+            } else if ch.is_digit(radix) {
+                digits.push(ch);
+                separated = false;
+            } else {
                 break;
             }
-            found = true;
+            self.advance();
+        }
+        if separated {
+            return Err(self.error(ErrorType::InvalidNumber, "Misplaced digit separator"));
+        }
+        Ok(digits)
+    }
+
+    fn collect_digits(&mut self) -> Result<u64, Error> {
+        let digits = self.collect_digit_run(10)?;
+        if digits.is_empty() {
+            return Err(self.error(ErrorType::InvalidNumber, "No digits found in number"));
+        }
+        let mut number = 0u64;
+        for ch in digits.chars() {
             number = number
                 .checked_mul(10)
                 .and_then(|n| n.checked_add(ch.to_digit(10).unwrap() as u64))
                 .ok_or_else(|| {
                     self.error(ErrorType::InvalidNumber, "Integer overflow in number")
                 })?;
-            self.advance();
-        }
-        if !found {
-            return Err(self.error(ErrorType::InvalidNumber, "No digits found in number"));
         }
         Ok(number)
     }
 
-    fn collect_fraction(&mut self) -> Result<f64, Error> {
-        let mut fraction = 0f64;
-        let mut divisor = 10f64;
-        let mut found = false;
-        while let Some(&ch) = self.peek() {
-            if !ch.is_ascii_digit() {
-                break;
-            }
-            found = true;
-            fraction += (ch.to_digit(10).unwrap() as f64) / divisor;
-            divisor *= 10f64;
-            self.advance();
-        }
-        if !found {
+    /// Collects the digits after the decimal point.
+    fn collect_fraction(&mut self) -> Result<String, Error> {
+        let digits = self.collect_digit_run(10)?;
+        if digits.is_empty() {
             return Err(self.error(
                 ErrorType::InvalidNumber,
                 "No digits found after decimal point",
             ));
         }
-        Ok(fraction)
+        Ok(digits)
     }
 
     fn make_integer(&self, number: u64, negative: bool) -> Result<TokenValue, Error> {
@@ -93,22 +146,17 @@ impl Lexer {
                 Ok(TokenValue::Literal(Literal::Int(-(number as i64))))
             }
         } else {
-            Ok(TokenValue::Literal(Literal::UInt(number)))
+            Ok(TokenValue::Literal(Literal::UInt(
+                number,
+                NumberBase::Decimal,
+            )))
         }
     }
 
     fn read_hexadecimal_number(&mut self) -> Result<TokenValue, Error> {
         self.advance(); // skip '0'
         self.advance(); // skip 'x' or 'X'
-        let mut hex_str = String::new();
-        while let Some(&ch) = self.peek() {
-            if ch.is_ascii_hexdigit() {
-                hex_str.push(ch);
-                self.advance();
-            } else {
-                break;
-            }
-        }
+        let hex_str = self.collect_digit_run(16)?;
         if hex_str.is_empty() {
             return Err(self.error(
                 ErrorType::InvalidNumber,
@@ -116,7 +164,10 @@ impl Lexer {
             ));
         }
         if let Ok(value) = u64::from_str_radix(&hex_str, 16) {
-            Ok(TokenValue::Literal(Literal::UInt(value)))
+            Ok(TokenValue::Literal(Literal::UInt(
+                value,
+                NumberBase::Hexadecimal,
+            )))
         } else {
             Err(self.error(ErrorType::InvalidNumber, "Invalid hexadecimal number"))
         }
@@ -125,20 +176,15 @@ impl Lexer {
     fn read_binary_number(&mut self) -> Result<TokenValue, Error> {
         self.advance(); // skip '0'
         self.advance(); // skip 'b' or 'B'
-        let mut bin_str = String::new();
-        while let Some(&ch) = self.peek() {
-            if ch == '0' || ch == '1' {
-                bin_str.push(ch);
-                self.advance();
-            } else {
-                break;
-            }
-        }
+        let bin_str = self.collect_digit_run(2)?;
         if bin_str.is_empty() {
             return Err(self.error(ErrorType::InvalidNumber, "No digits found in binary number"));
         }
         if let Ok(value) = u64::from_str_radix(&bin_str, 2) {
-            Ok(TokenValue::Literal(Literal::UInt(value)))
+            Ok(TokenValue::Literal(Literal::UInt(
+                value,
+                NumberBase::Binary,
+            )))
         } else {
             Err(self.error(ErrorType::InvalidNumber, "Invalid binary number"))
         }
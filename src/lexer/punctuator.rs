@@ -23,7 +23,9 @@ impl Lexer {
         }
         if let Some(kw) = keyword {
             self.index += kw_i + 1;
-            self.column += kw_i + 1;
+            if self.options.track_positions {
+                self.column += kw_i + 1;
+            }
             Ok(TokenValue::Keyword(kw))
         } else {
             Err(self.error(ErrorType::UnknownCharacter, "Unknown punctuator"))
@@ -1,8 +1,11 @@
 use super::*;
 
 impl Lexer {
-    pub(super) fn skip_whitespace_and_comments(&mut self) {
-        while self.skip_whitespace() || self.skip_comment() {}
+    /// Comments can go between any two tokens, as they're skipped
+    ///     before every token along with whitespace.
+    pub(super) fn skip_whitespace_and_comments(&mut self) -> Result<(), Error> {
+        while self.skip_whitespace() || self.skip_comment() || self.skip_block_comment()? {}
+        Ok(())
     }
 
     fn skip_whitespace(&mut self) -> bool {
@@ -30,4 +33,23 @@ impl Lexer {
             false
         }
     }
+
+    /// Block comments `/* */` don't nest, the first `*/` ends the comment.
+    fn skip_block_comment(&mut self) -> Result<bool, Error> {
+        if self.peek() != Some(&'/') || self.peek2() != Some(&'*') {
+            return Ok(false);
+        }
+        self.start_token();
+        self.advance();
+        self.advance();
+        while self.peek().is_some() {
+            if self.peek() == Some(&'*') && self.peek2() == Some(&'/') {
+                self.advance();
+                self.advance();
+                return Ok(true);
+            }
+            self.advance();
+        }
+        Err(self.error(ErrorType::UnclosedComment, "Unclosed block comment"))
+    }
 }
@@ -2,11 +2,14 @@ use super::*;
 
 impl Lexer {
     /// Reads a string literal token.
-    pub(super) fn read_string(&mut self) -> Result<TokenValue, Error> {
+    pub(super) fn read_string(&mut self, pool: &mut InternPool) -> Result<TokenValue, Error> {
         debug_assert_eq!(self.peek(), Some(&'"'));
         self.advance(); // skip opening quote
         let mut string_content = String::new();
         while let Some(&ch) = self.peek() {
+            if string_content.len() > self.options.max_literal_length {
+                return Err(self.error(ErrorType::LiteralTooLong, "String literal is too long"));
+            }
             if ch == '\\' {
                 self.advance();
                 string_content.push(self.read_escape_sequence()?);
@@ -14,7 +17,8 @@ impl Lexer {
             }
             if ch == '"' {
                 self.advance();
-                return Ok(TokenValue::Literal(Literal::String(string_content)));
+                let id = pool.insert_string(string_content);
+                return Ok(TokenValue::Literal(Literal::String(id)));
             }
             if ch == '\n' {
                 break;
@@ -45,12 +49,21 @@ impl Lexer {
             'r' => Ok('\r'),
             '\\' => Ok('\\'),
             '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '0' => Ok('\0'),
+            'a' => Ok('\u{07}'),
+            'b' => Ok('\u{08}'),
+            'f' => Ok('\u{0C}'),
+            'v' => Ok('\u{0B}'),
             'x' => self.read_hexidecimal_escape_sequence(),
             'u' => self.read_unicode_escape_sequence(),
             _ => Err(self.error(ErrorType::InvalidEscapeSequence, "Invalid escape sequence")),
         }
     }
 
+    /// `\xHH` is a single ASCII byte, so it's at most `\x7F`.
+    /// Strings are UTF-8, and a byte above that would only be valid as part
+    ///     of a multi-byte character, which `\u{...}` should be used for.
     fn read_hexidecimal_escape_sequence(&mut self) -> Result<char, Error> {
         let h1 = self.peek();
         let h2 = self.peek2();
@@ -59,6 +72,12 @@ impl Lexer {
             if let Ok(byte) = u8::from_str_radix(&hex_str, 16) {
                 self.advance();
                 self.advance();
+                if !byte.is_ascii() {
+                    return Err(self.error(
+                        ErrorType::InvalidEscapeSequence,
+                        "Hex escape sequence isn't ASCII, use `\\u{...}` instead",
+                    ));
+                }
                 Ok(byte as char)
             } else {
                 Err(self.error(
@@ -74,11 +93,13 @@ impl Lexer {
         }
     }
 
+    /// `\u{...}` is a Unicode scalar value, stored encoded as UTF-8.
     fn read_unicode_escape_sequence(&mut self) -> Result<char, Error> {
         if self.peek() != Some(&'{') {
             return Err(self.error(ErrorType::InvalidEscapeSequence, "Expected '{' after \\u"));
         }
         self.advance();
+        let digits_start = self.index;
         while let Some(&ch) = self.peek() {
             if ch == '}' {
                 break;
@@ -97,9 +118,7 @@ impl Lexer {
                 "Unclosed Unicode escape sequence",
             ));
         }
-        let hex_str: String = self.input[self.start_index + 2..self.index]
-            .iter()
-            .collect();
+        let hex_str: String = self.input[digits_start..self.index].iter().collect();
         self.advance();
         if let Ok(code_point) = u32::from_str_radix(&hex_str, 16) {
             if let Some(ch) = std::char::from_u32(code_point) {
@@ -1,7 +1,7 @@
 use super::*;
 
 impl Lexer {
-    fn start_token(&mut self) {
+    pub(super) fn start_token(&mut self) {
         self.start_index = self.index;
         self.start_line = self.line;
         self.start_column = self.column;
@@ -26,7 +26,7 @@ impl Lexer {
     }
 
     pub(super) fn next_token(&mut self, pool: &mut InternPool) -> Result<Option<Token>, Error> {
-        self.skip_whitespace_and_comments();
+        self.skip_whitespace_and_comments()?;
         self.start_token();
         if self.peek().is_none() {
             return Ok(None);
@@ -42,10 +42,13 @@ impl Lexer {
     fn next_token_value(&mut self, pool: &mut InternPool) -> Result<TokenValue, Error> {
         let ch = *self.peek().unwrap();
         if ch.is_alphabetic() || ch == '_' {
-            return Ok(self.read_identifier(pool));
+            return self.read_identifier(pool);
         }
         if ch == '"' {
-            return self.read_string();
+            return self.read_string(pool);
+        }
+        if ch == '@' {
+            return self.read_attribute(pool);
         }
         if ch.is_ascii_punctuation() {
             // Check for negative number: '-' followed by digit
@@ -55,6 +58,9 @@ impl Lexer {
             {
                 return self.read_number();
             }
+            if ch == '.' && self.starts_fraction() {
+                return self.read_number();
+            }
             return self.read_punctuator(pool);
         }
         if ch.is_ascii_digit() {
@@ -74,9 +80,15 @@ impl Lexer {
     pub(super) fn advance(&mut self) {
         if let Some(&ch) = self.peek() {
             self.index += 1;
+            if !self.options.track_positions {
+                return;
+            }
             if ch == '\n' {
                 self.line += 1;
                 self.column = 1;
+            } else if ch == '\t' {
+                let tab_width = self.options.tab_width.max(1);
+                self.column = (self.column - 1) / tab_width * tab_width + tab_width + 1;
             } else {
                 self.column += 1;
             }
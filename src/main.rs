@@ -1,3 +1,8 @@
+#![cfg_attr(
+    not(test),
+    allow(dead_code, reason = "the compiler isn't called from `main` yet")
+)]
+
 mod intern_pool;
 mod lexer;
 mod rw_arc;
@@ -7,6 +12,7 @@ mod span;
 mod syntactic_parser;
 mod syntax_ast;
 mod token;
+mod visitor;
 
 fn main() {
     println!("Hello, world!");
@@ -13,6 +13,9 @@ where
 
 impl<T: PartialEq + Serialize> PartialEq for RwArc<T> {
     fn eq(&self, other: &Self) -> bool {
+        if Arc::ptr_eq(&self.0, &other.0) {
+            return true;
+        }
         let self_data = self.0.read().unwrap();
         let other_data = other.0.read().unwrap();
         *self_data == *other_data
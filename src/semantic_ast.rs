@@ -1,9 +1,9 @@
-use crate::intern_pool::SymbolId;
+use crate::intern_pool::{StringId, SymbolId};
 use crate::rw_arc::RwArc;
 use crate::span::Span;
-use crate::syntax_ast::Scope;
+use crate::syntax_ast::{AssignmentType, BinaryOp, Scope, UnaryOp};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct Ast {
@@ -21,34 +21,97 @@ pub(crate) struct Module {
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct File {
     pub(crate) name: SymbolId,
-    pub(crate) module: SymbolId,
+    /// The module the file is in, after the modules it's nested in.
+    pub(crate) module: Vec<SymbolId>,
     pub(crate) imports: HashMap<SymbolId, RwArc<Module>>,
+    /// Functions and globals imported by name, used when a bare
+    ///     name isn't defined in the file.
+    pub(crate) item_imports: HashMap<SymbolId, Identifier>,
+    /// Types of the other files of the module that aren't private, used when
+    ///     a bare type name isn't defined in the file. Names defined by
+    ///     several of them are left out, since they're ambiguous.
+    pub(crate) sibling_types: HashMap<SymbolId, RwArc<TypeDef>>,
+    /// Types imported by name, used after the sibling types.
+    pub(crate) type_imports: HashMap<SymbolId, RwArc<TypeDef>>,
     pub(crate) globals: HashMap<SymbolId, Scope<RwArc<Declaration>>>,
     pub(crate) functions: HashMap<SymbolId, Scope<RwArc<Function>>>,
     pub(crate) types: HashMap<SymbolId, Scope<RwArc<TypeDef>>>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Clone, Copy)]
 pub(crate) struct TypeId(pub(crate) usize);
 
-#[derive(Debug, PartialEq, Serialize)]
+/// A capability `@derive` can generate for a type. Every field must
+///     already have it.
+#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+pub(crate) enum Derive {
+    /// `==` and `!=`, comparing field by field.
+    Eq,
+    /// Printing the value, field by field.
+    Print,
+}
+
+#[derive(Debug, Serialize)]
 pub(crate) struct TypeDef {
     pub(crate) id: TypeId,
     pub(crate) name: SymbolId,
     pub(crate) body: TypeDefBody,
     pub(crate) size: usize,
+    pub(crate) align: usize,
+    /// Set by `@packed`. Packed structs and unions have no padding.
+    pub(crate) packed: bool,
+    /// What `@derive` asked to be generated for the type.
+    pub(crate) derives: Vec<Derive>,
+    /// The constant values of struct fields that struct literals may leave out.
+    pub(crate) defaults: HashMap<SymbolId, Expression>,
     pub(crate) span: Span,
 }
 
+/// Type definitions are nominal: two definitions are the same type only
+///     if they are the same definition. Comparing ids also keeps
+///     self-referencing types from recursing forever.
+impl PartialEq for TypeDef {
+    fn eq(&self, other: &Self) -> bool {
+        self.id.0 == other.id.0
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) enum TypeDefBody {
     Struct(HashMap<SymbolId, Type>),
-    Enum(HashMap<SymbolId, u64>),
+    Enum(Enum),
+    TaggedEnum(TaggedEnum),
     Union(HashMap<SymbolId, Type>),
     Alias(Type),
 }
 
+impl TypeDefBody {
+    /// The variants of an enum, whether it's tagged or not.
+    pub(crate) fn as_enum(&self) -> Option<&Enum> {
+        match self {
+            TypeDefBody::Enum(enum_) => Some(enum_),
+            TypeDefBody::TaggedEnum(tagged) => Some(&tagged.tags),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Enum {
+    /// The integer type the values are stored as. u64 unless written otherwise.
+    pub(crate) backing: Type,
+    pub(crate) variants: HashMap<SymbolId, i128>,
+}
+
+/// Stored as the tag, a value of the backing type, followed by the payload
+///     of the variant it names. Every variant has a payload, maybe empty.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct TaggedEnum {
+    pub(crate) tags: Enum,
+    pub(crate) payloads: HashMap<SymbolId, Vec<Type>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
 pub(crate) enum Type {
     U8,
     U16,
@@ -63,11 +126,16 @@ pub(crate) enum Type {
     F32,
     F64,
     Bool,
+    /// The type of expressions that don't produce a value,
+    ///     such as calls to functions without a return type.
+    Unit,
 
     Custom(RwArc<TypeDef>),
 
     Function(FunctionType),
 
+    Tuple(Vec<Type>),
+
     Pointer {
         inner: Box<Type>,
         mutable: bool,
@@ -83,7 +151,121 @@ pub(crate) enum Type {
     },
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+/// Aliases are transparent, so an alias equals the type it stands for.
+/// They're only followed when a custom type isn't equal as it is.
+impl PartialEq for Type {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Type::Custom(a), Type::Custom(b)) if a == b => true,
+            (Type::Custom(_), _) | (_, Type::Custom(_)) => {
+                let (a, b) = (self.alias_target(), other.alias_target());
+                (a.is_some() || b.is_some())
+                    && a.as_ref().unwrap_or(self) == b.as_ref().unwrap_or(other)
+            }
+            (Type::Function(a), Type::Function(b)) => a == b,
+            (Type::Tuple(a), Type::Tuple(b)) => a == b,
+            (
+                Type::Pointer {
+                    inner: a,
+                    mutable: a_mutable,
+                },
+                Type::Pointer {
+                    inner: b,
+                    mutable: b_mutable,
+                },
+            )
+            | (
+                Type::Slice {
+                    inner: a,
+                    mutable: a_mutable,
+                },
+                Type::Slice {
+                    inner: b,
+                    mutable: b_mutable,
+                },
+            ) => a_mutable == b_mutable && a == b,
+            (
+                Type::Array {
+                    inner: a,
+                    size: a_size,
+                    mutable: a_mutable,
+                },
+                Type::Array {
+                    inner: b,
+                    size: b_size,
+                    mutable: b_mutable,
+                },
+            ) => a_size == b_size && a_mutable == b_mutable && a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Type {
+    /// The type an alias stands for, following aliases of aliases.
+    /// Other types, and aliases that form a cycle, are returned as they are.
+    pub(crate) fn resolve_alias(&self) -> Type {
+        self.alias_target().unwrap_or_else(|| self.clone())
+    }
+
+    /// None if the type isn't an alias, or if following it leads back to
+    ///     an alias already seen. The layout pass reports such cycles.
+    fn alias_target(&self) -> Option<Type> {
+        let mut target: Option<Type> = None;
+        let mut seen = HashSet::new();
+        loop {
+            let next = match target.as_ref().unwrap_or(self) {
+                Type::Custom(def) => {
+                    let def = def.read().unwrap();
+                    match &def.body {
+                        TypeDefBody::Alias(inner) if seen.insert(def.id) => Some(inner.clone()),
+                        TypeDefBody::Alias(_) => return None,
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match next {
+                Some(inner) => target = Some(inner),
+                None => return target,
+            }
+        }
+    }
+
+    /// The range of values an integer type can hold. None for non-integer types.
+    pub(crate) fn integer_bounds(&self) -> Option<(i128, i128)> {
+        Some(match self {
+            Type::Custom(_) => return self.alias_target()?.integer_bounds(),
+            Type::U8 => (0, u8::MAX as i128),
+            Type::U16 => (0, u16::MAX as i128),
+            Type::U32 => (0, u32::MAX as i128),
+            Type::U64 | Type::Usize => (0, u64::MAX as i128),
+            Type::I8 => (i8::MIN as i128, i8::MAX as i128),
+            Type::I16 => (i16::MIN as i128, i16::MAX as i128),
+            Type::I32 => (i32::MIN as i128, i32::MAX as i128),
+            Type::I64 | Type::Isize => (i64::MIN as i128, i64::MAX as i128),
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        self.integer_bounds().is_some()
+    }
+
+    pub(crate) fn is_float(&self) -> bool {
+        match self {
+            Type::F32 | Type::F64 => true,
+            Type::Custom(_) => self.alias_target().is_some_and(|typ| typ.is_float()),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
 pub(crate) struct FunctionType {
     pub(crate) args: Vec<Type>,
     pub(crate) ret: Option<Box<Type>>,
@@ -94,10 +276,36 @@ pub(crate) struct Function {
     pub(crate) name: SymbolId,
     pub(crate) arguments: Vec<RwArc<FunctionArg>>,
     pub(crate) return_type: Option<Type>,
-    pub(crate) body: Vec<Statement>,
+    pub(crate) body: FunctionBody,
     pub(crate) span: Span,
 }
 
+impl Function {
+    /// The type of the function when it's used as a value.
+    pub(crate) fn signature(&self) -> FunctionType {
+        FunctionType {
+            args: self
+                .arguments
+                .iter()
+                .map(|arg| arg.read().unwrap().typ.clone())
+                .collect(),
+            ret: self.return_type.clone().map(Box::new),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) enum FunctionBody {
+    Block(Vec<Statement>),
+    Asm(Asm),
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Asm {
+    pub(crate) code: String,
+    pub(crate) operands: Vec<Expression>,
+}
+
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct FunctionArg {
     pub(crate) name: SymbolId,
@@ -112,6 +320,9 @@ pub(crate) struct Declaration {
     pub(crate) typ: Type,
     pub(crate) value: Expression,
     pub(crate) span: Span,
+    /// Only globals are poisoned, when their value fails to resolve.
+    /// Their error is reported once, and not again by what inlines them.
+    pub(crate) poisoned: bool,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -121,12 +332,33 @@ pub(crate) struct Expression {
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, PartialEq, Serialize, Clone)]
 pub(crate) enum Identifier {
     Declaraction(RwArc<Declaration>),
     Function(RwArc<Function>),
     Argument(RwArc<FunctionArg>),
     EnumVariant(RwArc<TypeDef>, SymbolId),
+    /// An element of the payload of the tagged enum being matched, bound
+    ///     by a pattern like `Circle(r)`. Only used as the binding's value.
+    Payload(usize),
+    /// A struct or union field. Only used as the right side of a field access.
+    Field(SymbolId),
+    /// A tuple element. Only used as the right side of a field access.
+    TupleElement(usize),
+    /// The length of a slice, `.len`. Only used as the right side of a field
+    ///     access. An array's length is constant, so it's folded instead.
+    Length,
+    /// A builtin function. Only used as the callee of a call,
+    ///     since intrinsics aren't values, it has the unit type.
+    Intrinsic(Intrinsic),
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub(crate) enum Intrinsic {
+    /// `format("{}", x)` returns the formatted string.
+    Format,
+    /// `print("{}", x)` writes the formatted string.
+    Print,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -136,6 +368,12 @@ pub(crate) enum ExpressionValue {
     Call(Call),
     Literal(Literal),
     Identifier(Identifier),
+    /// Converts the value to the type of the cast expression.
+    Cast(Box<Expression>),
+    /// Runs the body until a `break`, whose value becomes the loop's.
+    Loop(Vec<Statement>),
+    /// Valued by the arm taken. Every arm has the type of the match.
+    Match(Box<MatchExpression>),
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -145,45 +383,12 @@ pub(crate) struct Binary {
     pub(crate) op: BinaryOp,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
-pub(crate) enum BinaryOp {
-    Plus,
-    Minus,
-    Mul,
-    Div,
-    Mod,
-    LeftShift,
-    RightShift,
-    BitAnd,
-    BitOr,
-    BitXor,
-    Gt,
-    Ge,
-    Lt,
-    Le,
-    Eq,
-    NotEq,
-    LogicalAnd,
-    LogicalOr,
-    Indexing,
-    FieldAccess,
-}
-
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct Unary {
     pub(crate) operand: Box<Expression>,
     pub(crate) op: UnaryOp,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
-pub(crate) enum UnaryOp {
-    LogicalNot,
-    BitNot,
-    Dereference,
-    AddressOf,
-    Negate,
-}
-
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct Call {
     pub(crate) function: Box<Expression>,
@@ -192,13 +397,31 @@ pub(crate) struct Call {
 
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) enum Literal {
-    String(String),
+    String(StringId),
     UInt(u64),
     Int(i64),
     Float(f64),
     Bool(bool),
+    /// Typed as an array, or as an immutable slice like a string literal.
     Array(Vec<Expression>),
-    Struct(HashMap<SymbolId, Expression>),
+    /// An array of `count` copies of the value.
+    ArrayRepeat {
+        value: Box<Expression>,
+        count: u64,
+    },
+    Struct(StructLiteral),
+    Tuple(Vec<Expression>),
+    /// A variant of a tagged enum with its payload, like `Shape::Circle(1.0)`.
+    Variant {
+        variant: SymbolId,
+        payload: Vec<Expression>,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct StructLiteral {
+    pub(crate) fields: HashMap<SymbolId, Expression>,
+    pub(crate) base: Option<Box<Expression>>,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -209,21 +432,6 @@ pub(crate) struct Assignment {
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
-pub(crate) enum AssignmentType {
-    Assign,
-    Plus,
-    Minus,
-    Mul,
-    Div,
-    Mod,
-    LeftShift,
-    RightShift,
-    BitAnd,
-    BitOr,
-    BitXor,
-}
-
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct ConditionalBranch {
     pub(crate) condition: Expression,
@@ -244,9 +452,36 @@ pub(crate) struct Match {
     pub(crate) default: Option<Vec<Statement>>,
 }
 
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct MatchExpression {
+    pub(crate) value: Expression,
+    pub(crate) cases: Vec<MatchArm>,
+    pub(crate) default: Option<ValueBlock>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct MatchArm {
+    pub(crate) condition: Expression,
+    pub(crate) body: ValueBlock,
+}
+
+/// Runs the statements in their own scope, then evaluates the value.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct ValueBlock {
+    pub(crate) statements: Vec<Statement>,
+    pub(crate) value: Expression,
+}
+
+/// The value is None for `break;`, which gives a loop expression the unit value.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Break {
+    pub(crate) value: Option<Expression>,
+    pub(crate) span: Span,
+}
+
 #[derive(Debug, PartialEq, Serialize)]
 pub(crate) struct Loop {
-    pub(crate) init: Option<RwArc<Declaration>>,
+    pub(crate) init: Vec<RwArc<Declaration>>,
     pub(crate) condition: Option<Expression>,
     pub(crate) update: Vec<Statement>,
     pub(crate) body: Vec<Statement>,
@@ -259,8 +494,10 @@ pub(crate) enum Statement {
     Expression(Expression),
     Loop(Loop),
     Continue(Span),
-    Break(Span),
+    Break(Break),
     Conditional(Conditional),
     Match(Match),
-    Return(Expression),
+    /// A bare `return` has no value.
+    Return(Option<Expression>),
+    Block(Vec<Statement>),
 }
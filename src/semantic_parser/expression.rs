@@ -0,0 +1,1024 @@
+use super::statement::place_mutability;
+use super::*;
+use crate::semantic_ast::{Binary, Call, StructLiteral, Unary};
+use crate::token::FloatSuffix;
+use syntax_ast::{BinaryOp, UnaryOp};
+
+impl Resolver<'_> {
+    /// Resolves an expression that must be of the expected type.
+    pub(super) fn resolve_typed_expression(
+        &mut self,
+        exp: &syntax_ast::Expression,
+        expected: &Type,
+    ) -> Result<Expression, Error> {
+        let exp = self.resolve_expression(exp, Some(expected))?;
+        if exp.typ != *expected {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Mismatched types",
+                span: exp.span,
+            });
+        }
+        Ok(exp)
+    }
+
+    /// Resolves an expression. The expected type is only a hint for literals,
+    ///     the result may be of any type.
+    pub(super) fn resolve_expression(
+        &mut self,
+        exp: &syntax_ast::Expression,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        match &exp.value {
+            syntax_ast::ExpressionValue::Literal(literal) => {
+                self.resolve_literal(literal, exp.span, expected)
+            }
+            syntax_ast::ExpressionValue::Identifier(name) => {
+                self.resolve_identifier(name, exp.span)
+            }
+            syntax_ast::ExpressionValue::Call(call) => self.resolve_call(call, exp.span),
+            syntax_ast::ExpressionValue::Binary(binary) => {
+                self.resolve_binary(binary, exp.span, expected)
+            }
+            syntax_ast::ExpressionValue::Unary(unary) => {
+                self.resolve_unary(unary, exp.span, expected)
+            }
+            syntax_ast::ExpressionValue::Cast(cast) => self.resolve_cast(cast, exp.span),
+            syntax_ast::ExpressionValue::Loop(body) => {
+                self.resolve_loop_expression(body, exp.span, expected)
+            }
+            syntax_ast::ExpressionValue::Match(syn_match) => {
+                self.resolve_match_expression(syn_match, exp.span, expected)
+            }
+        }
+    }
+
+    /// Numbers can be cast to other numbers, enums and bools to integers,
+    ///     and pointers to other pointers and to and from integers.
+    fn resolve_cast(&mut self, cast: &syntax_ast::Cast, span: Span) -> Result<Expression, Error> {
+        let value = self.resolve_expression(&cast.value, None)?;
+        let typ = resolve_type_annot(self.file, self.pool, &cast.typ)?;
+        let is_enum = |typ: &Type| match typ {
+            Type::Custom(def) => matches!(def.read().unwrap().body, TypeDefBody::Enum(_)),
+            _ => false,
+        };
+        let valid = match (&value.typ.resolve_alias(), &typ.resolve_alias()) {
+            (from, to) if from.is_numeric() && to.is_numeric() => true,
+            (Type::Bool, to) => to.is_integer(),
+            (from, to) if is_enum(from) => to.is_integer(),
+            (Type::Pointer { .. }, Type::Pointer { .. }) => true,
+            (Type::Pointer { .. }, to) => to.is_integer(),
+            (from, Type::Pointer { .. }) => from.is_integer(),
+            _ => false,
+        };
+        if !valid {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Invalid cast",
+                span,
+            });
+        }
+        Ok(Expression {
+            value: ExpressionValue::Cast(Box::new(value)),
+            typ,
+            span,
+        })
+    }
+
+    fn resolve_literal(
+        &mut self,
+        literal: &syntax_ast::Literal,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let (value, typ) = match literal {
+            syntax_ast::Literal::UInt(uint, _) => (
+                Literal::UInt(*uint),
+                integer_literal_type(*uint as i128, span, expected)?,
+            ),
+            syntax_ast::Literal::Int(int) => (
+                Literal::Int(*int),
+                integer_literal_type(*int as i128, span, expected)?,
+            ),
+            syntax_ast::Literal::Float(float, suffix) => {
+                let typ = match (suffix, expected) {
+                    (Some(FloatSuffix::F32), _) => Type::F32,
+                    (None, Some(typ)) if *typ == Type::F32 => Type::F32,
+                    _ => Type::F64,
+                };
+                if typ == Type::F32 && !fits_f32(*float) {
+                    return Err(Error {
+                        typ: ErrorType::Type,
+                        msg: "Literal not representable as f32",
+                        span,
+                    });
+                }
+                // Like folded constants, f32 values are kept rounded to f32.
+                let value = if typ == Type::F32 {
+                    *float as f32 as f64
+                } else {
+                    *float
+                };
+                (Literal::Float(value), typ)
+            }
+            syntax_ast::Literal::Bool(bool) => (Literal::Bool(*bool), Type::Bool),
+            syntax_ast::Literal::String(string) => {
+                let typ = Type::Slice {
+                    inner: Box::new(Type::U8),
+                    mutable: false,
+                };
+                (Literal::String(*string), typ)
+            }
+            syntax_ast::Literal::Array(elements) => {
+                return self.resolve_array_literal(elements, span, expected);
+            }
+            syntax_ast::Literal::ArrayRepeat { value, count } => {
+                return self.resolve_array_repeat(value, count, span, expected);
+            }
+            syntax_ast::Literal::Struct(literal) => {
+                return self.resolve_struct_literal(literal, span);
+            }
+            syntax_ast::Literal::Tuple(elements) => {
+                let expected = match expected {
+                    Some(Type::Tuple(types)) if types.len() == elements.len() => Some(types),
+                    _ => None,
+                };
+                let mut values = Vec::with_capacity(elements.len());
+                for (i, element) in elements.iter().enumerate() {
+                    values.push(match expected {
+                        Some(types) => self.resolve_typed_expression(element, &types[i])?,
+                        None => self.resolve_expression(element, None)?,
+                    });
+                }
+                let typ = Type::Tuple(values.iter().map(|value| value.typ.clone()).collect());
+                (Literal::Tuple(values), typ)
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Literal(value),
+            typ,
+            span,
+        })
+    }
+
+    /// Array literals take the expected array type if there is one,
+    ///     otherwise the type of their first element, which every other
+    ///     element must have too. Like string literals, they can also be
+    ///     immutable slices, whose element type then comes from the slice.
+    fn resolve_array_literal(
+        &mut self,
+        elements: &[syntax_ast::Expression],
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let mut values = Vec::with_capacity(elements.len());
+        // Aliases are followed, but the literal keeps the type it's expected to have.
+        let typ = match expected.map(|typ| (typ, typ.resolve_alias())) {
+            Some((typ, Type::Array { inner, size, .. })) => {
+                if size != elements.len() as u64 {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Wrong number of array elements",
+                        span,
+                    });
+                }
+                for element in elements.iter() {
+                    values.push(self.resolve_typed_expression(element, &inner)?);
+                }
+                typ.clone()
+            }
+            Some((
+                typ,
+                Type::Slice {
+                    inner,
+                    mutable: false,
+                },
+            )) => {
+                for element in elements.iter() {
+                    values.push(self.resolve_typed_expression(element, &inner)?);
+                }
+                typ.clone()
+            }
+            _ => {
+                let Some((first, rest)) = elements.split_first() else {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Can't infer the type of an empty array literal",
+                        span,
+                    });
+                };
+                let first = self.resolve_expression(first, None)?;
+                let inner = first.typ.clone();
+                values.push(first);
+                for element in rest.iter() {
+                    let value = self.resolve_expression(element, Some(&inner))?;
+                    if value.typ != inner {
+                        return Err(Error {
+                            typ: ErrorType::Type,
+                            msg: "Array elements have different types",
+                            span: value.span,
+                        });
+                    }
+                    values.push(value);
+                }
+                Type::Array {
+                    inner: Box::new(inner),
+                    size: elements.len() as u64,
+                    mutable: false,
+                }
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Literal(Literal::Array(values)),
+            typ,
+            span,
+        })
+    }
+
+    /// The count is resolved like an array size, so it must be a constant.
+    fn resolve_array_repeat(
+        &mut self,
+        value: &syntax_ast::Expression,
+        count: &syntax_ast::Expression,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let count = self.resolve_size(count)?;
+        // Aliases are followed like for array literals.
+        let (value, typ) = match expected.map(|typ| (typ, typ.resolve_alias())) {
+            Some((typ, Type::Array { inner, size, .. })) => {
+                if size != count {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Wrong number of array elements",
+                        span,
+                    });
+                }
+                (self.resolve_typed_expression(value, &inner)?, typ.clone())
+            }
+            _ => {
+                let value = self.resolve_expression(value, None)?;
+                let typ = Type::Array {
+                    inner: Box::new(value.typ.clone()),
+                    size: count,
+                    mutable: false,
+                };
+                (value, typ)
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Literal(Literal::ArrayRepeat {
+                value: Box::new(value),
+                count,
+            }),
+            typ,
+            span,
+        })
+    }
+
+    /// Struct literals are typed by the name before them, and must give
+    ///     every field without a default unless there's a `..base` of the
+    ///     same struct type. Defaults only fill in fields without a base.
+    fn resolve_struct_literal(
+        &mut self,
+        literal: &syntax_ast::StructLiteral,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let struct_type = resolve_type_annot(self.file, self.pool, &literal.typ)?;
+        let Type::Custom(typ) = &struct_type else {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Struct literal of a non-struct type",
+                span: literal.typ.span,
+            });
+        };
+        let field_types = match &typ.read().unwrap().body {
+            TypeDefBody::Struct(field_types) => field_types.clone(),
+            _ => {
+                return Err(Error {
+                    typ: ErrorType::Type,
+                    msg: "Struct literal of a non-struct type",
+                    span,
+                });
+            }
+        };
+        let base = match &literal.base {
+            Some(base) => {
+                let base = self.resolve_expression(base, Some(&struct_type))?;
+                if base.typ != struct_type {
+                    let msg = match &base.typ {
+                        Type::Custom(base_typ)
+                            if matches!(base_typ.read().unwrap().body, TypeDefBody::Struct(_)) =>
+                        {
+                            "Mismatched types"
+                        }
+                        _ => "Spreading a non-struct value",
+                    };
+                    return Err(Error {
+                        typ: ErrorType::Type,
+                        msg,
+                        span: base.span,
+                    });
+                }
+                Some(Box::new(base))
+            }
+            None => None,
+        };
+        let mut fields = HashMap::with_capacity(literal.fields.len());
+        for (name, field) in literal.fields.iter() {
+            let Some(field_type) = field_types.get(name) else {
+                return Err(Error {
+                    typ: ErrorType::Name,
+                    msg: "No such field",
+                    span: field.span,
+                });
+            };
+            fields.insert(*name, self.resolve_typed_expression(field, field_type)?);
+        }
+        if base.is_none() {
+            for (name, default) in typ.read().unwrap().defaults.iter() {
+                if !fields.contains_key(name) {
+                    fields.insert(*name, copy_constant(default).unwrap());
+                }
+            }
+        }
+        // Without a base, nothing else fills in the fields that aren't given.
+        if base.is_none() && fields.len() != field_types.len() {
+            return Err(Error {
+                typ: ErrorType::Expression,
+                msg: "Missing field in struct literal",
+                span,
+            });
+        }
+        Ok(Expression {
+            value: ExpressionValue::Literal(Literal::Struct(StructLiteral { fields, base })),
+            typ: struct_type,
+            span,
+        })
+    }
+
+    fn resolve_identifier(
+        &mut self,
+        name: &syntax_ast::Name,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let identifier = match self.lookup(name) {
+            Some(identifier) => identifier,
+            None => match self.enum_variant(name, span)? {
+                Some(identifier) => identifier,
+                None => {
+                    return Err(Error {
+                        typ: ErrorType::Name,
+                        msg: "Can't resolve name",
+                        span,
+                    });
+                }
+            },
+        };
+        if self.inline_constants
+            && let Identifier::Declaraction(declaration) = &identifier
+        {
+            let declaration = declaration.read().unwrap();
+            if declaration.poisoned {
+                return Err(Error {
+                    typ: ErrorType::Poisoned,
+                    msg: "Inlining a global that failed to resolve",
+                    span,
+                });
+            }
+            if !declaration.mutable
+                && let Some(constant) = copy_constant(&declaration.value)
+            {
+                return Ok(Expression { span, ..constant });
+            }
+        }
+        let typ = match &identifier {
+            Identifier::Declaraction(declaration) => declaration.read().unwrap().typ.clone(),
+            Identifier::Argument(arg) => arg.read().unwrap().typ.clone(),
+            Identifier::Function(function) => Type::Function(function.read().unwrap().signature()),
+            Identifier::EnumVariant(typ, variant) => {
+                if let TypeDefBody::TaggedEnum(tagged) = &typ.read().unwrap().body
+                    && !tagged.payloads[variant].is_empty()
+                {
+                    return Err(Error {
+                        typ: ErrorType::Name,
+                        msg: "Variant needs a payload",
+                        span,
+                    });
+                }
+                Type::Custom(typ.clone())
+            }
+            Identifier::Field(_)
+            | Identifier::TupleElement(_)
+            | Identifier::Length
+            | Identifier::Payload(_)
+            | Identifier::Intrinsic(_) => {
+                unreachable!("fields, tuple elements, payloads and intrinsics are never looked up")
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Identifier(identifier),
+            typ,
+            span,
+        })
+    }
+
+    /// Looks a name up in the local scopes, innermost first, then in the file.
+    /// Qualified names are looked up in the imported file they point to.
+    /// Items in other files must be visible from this one.
+    pub(super) fn lookup(&self, name: &syntax_ast::Name) -> Option<Identifier> {
+        let (item, path) = name.split_last().unwrap();
+        if !path.is_empty() {
+            let found = with_imported_file(self.file, path, |file| lookup_file(file, *item))?;
+            return is_visible(found.visibility, path, &self.file.module).then_some(found.value);
+        }
+        for scope in self.scopes.iter().rev() {
+            if let Some(identifier) = scope.get(item) {
+                return Some(identifier.clone());
+            }
+        }
+        lookup_file(self.file, *item)
+            .map(|found| found.value)
+            .or_else(|| self.file.item_imports.get(item).cloned())
+    }
+
+    /// `Enum::Variant`, where the enum may itself be qualified like a type.
+    /// None if the name doesn't start with an enum.
+    pub(super) fn enum_variant(
+        &self,
+        name: &syntax_ast::Name,
+        span: Span,
+    ) -> Result<Option<Identifier>, Error> {
+        let Some((variant, type_name)) = name.split_last() else {
+            return Ok(None);
+        };
+        let Some((type_name, path)) = type_name.split_last() else {
+            return Ok(None);
+        };
+        let typ = if path.is_empty() {
+            local_type(self.file, *type_name)
+        } else {
+            with_imported_file(self.file, path, |file| {
+                file.types
+                    .get(type_name)
+                    .filter(|typ| is_visible(typ.visibility, path, &self.file.module))
+                    .map(|typ| typ.value.clone())
+            })
+        };
+        let Some(typ) = typ else {
+            return Ok(None);
+        };
+        let guard = typ.read().unwrap();
+        let Some(enum_) = guard.body.as_enum() else {
+            return Ok(None);
+        };
+        if !enum_.variants.contains_key(variant) {
+            return Err(Error {
+                typ: ErrorType::Name,
+                msg: "Enum has no such variant",
+                span,
+            });
+        }
+        Ok(Some(Identifier::EnumVariant(typ.clone(), *variant)))
+    }
+
+    fn resolve_call(&mut self, call: &syntax_ast::Call, span: Span) -> Result<Expression, Error> {
+        if let Some(intrinsic) = self.intrinsic(&call.function) {
+            return self.resolve_intrinsic_call(intrinsic, call, span);
+        }
+        if let Some((typ, variant)) = self.payload_variant(&call.function)? {
+            return self.resolve_variant(typ, variant, call, span);
+        }
+        let function = self.resolve_expression(&call.function, None)?;
+        let Type::Function(signature) = &function.typ else {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg: "Calling a non-function value",
+                span: function.span,
+            });
+        };
+        let args = self.resolve_args(&call.args, &signature.args, span)?;
+        let typ = match &signature.ret {
+            Some(ret) => (**ret).clone(),
+            None => Type::Unit,
+        };
+        Ok(Expression {
+            value: ExpressionValue::Call(Call {
+                function: Box::new(function),
+                args,
+            }),
+            typ,
+            span,
+        })
+    }
+
+    /// Resolves the arguments of a call, which must be as many as the types.
+    fn resolve_args(
+        &mut self,
+        args: &[syntax_ast::Expression],
+        types: &[Type],
+        span: Span,
+    ) -> Result<Vec<Expression>, Error> {
+        if args.len() < types.len() {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg: "Too few arguments",
+                span,
+            });
+        }
+        if let Some(extra) = args.get(types.len()) {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg: "Too many arguments",
+                span: extra.span,
+            });
+        }
+        args.iter()
+            .zip(types.iter())
+            .map(|(arg, typ)| self.resolve_typed_expression(arg, typ))
+            .collect()
+    }
+
+    /// The tagged enum and variant a call constructs, if the callee names
+    ///     a variant with a payload. Local names take precedence.
+    fn payload_variant(
+        &self,
+        function: &syntax_ast::Expression,
+    ) -> Result<Option<(RwArc<TypeDef>, SymbolId)>, Error> {
+        let syntax_ast::ExpressionValue::Identifier(name) = &function.value else {
+            return Ok(None);
+        };
+        if self.lookup(name).is_some() {
+            return Ok(None);
+        }
+        let Some(Identifier::EnumVariant(typ, variant)) = self.enum_variant(name, function.span)?
+        else {
+            return Ok(None);
+        };
+        let has_payload = match &typ.read().unwrap().body {
+            TypeDefBody::TaggedEnum(tagged) => !tagged.payloads[&variant].is_empty(),
+            _ => false,
+        };
+        Ok(has_payload.then_some((typ, variant)))
+    }
+
+    /// Constructs a variant of a tagged enum, whose payload is passed like arguments.
+    fn resolve_variant(
+        &mut self,
+        typ: RwArc<TypeDef>,
+        variant: SymbolId,
+        call: &syntax_ast::Call,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let types = match &typ.read().unwrap().body {
+            TypeDefBody::TaggedEnum(tagged) => tagged.payloads[&variant].clone(),
+            _ => unreachable!("only tagged enum variants have payloads"),
+        };
+        let payload = self.resolve_args(&call.args, &types, span)?;
+        Ok(Expression {
+            value: ExpressionValue::Literal(Literal::Variant { variant, payload }),
+            typ: Type::Custom(typ),
+            span,
+        })
+    }
+
+    fn resolve_binary(
+        &mut self,
+        binary: &syntax_ast::Binary,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let (left, right, typ) = match binary.op {
+            BinaryOp::FieldAccess => return self.resolve_field_access(binary, span),
+            BinaryOp::Indexing => return self.resolve_indexing(binary, span),
+            BinaryOp::LogicalAnd | BinaryOp::LogicalOr => {
+                let left = self.resolve_typed_expression(&binary.left, &Type::Bool)?;
+                let right = self.resolve_typed_expression(&binary.right, &Type::Bool)?;
+                (left, right, Type::Bool)
+            }
+            BinaryOp::Gt | BinaryOp::Ge | BinaryOp::Lt | BinaryOp::Le => {
+                let (left, right) = self.resolve_operands(binary, None)?;
+                if !left.typ.is_numeric() {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Ordering a non-numeric value",
+                        span: left.span,
+                    });
+                }
+                (left, right, Type::Bool)
+            }
+            BinaryOp::Eq | BinaryOp::NotEq => {
+                let (left, right) = self.resolve_operands(binary, None)?;
+                if !has_equality(&left.typ) {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Values of this type can't be compared for equality",
+                        span: left.span,
+                    });
+                }
+                (left, right, Type::Bool)
+            }
+            BinaryOp::LeftShift | BinaryOp::RightShift => {
+                let left = self.resolve_expression(&binary.left, expected)?;
+                let right = self.resolve_expression(&binary.right, None)?;
+                if let Some(operand) = [&left, &right]
+                    .into_iter()
+                    .find(|exp| !exp.typ.is_integer())
+                {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Shifting a non-integer value",
+                        span: operand.span,
+                    });
+                }
+                let typ = left.typ.clone();
+                (left, right, typ)
+            }
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => {
+                let (left, right) = self.resolve_operands(binary, expected)?;
+                if !left.typ.is_integer() {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Bitwise operation on a non-integer value",
+                        span: left.span,
+                    });
+                }
+                let typ = left.typ.clone();
+                (left, right, typ)
+            }
+            BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                let (left, right) = self.resolve_arithmetic_operands(binary, expected)?;
+                let is_pointer = |exp: &Expression| matches!(exp.typ, Type::Pointer { .. });
+                let offset =
+                    is_pointer(&left) && matches!(binary.op, BinaryOp::Plus | BinaryOp::Minus);
+                if !left.typ.is_numeric() && !offset {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Arithmetic on a non-numeric value",
+                        span: left.span,
+                    });
+                }
+                let typ = if offset && is_pointer(&right) {
+                    Type::Isize
+                } else {
+                    left.typ.clone()
+                };
+                (left, right, typ)
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Binary(Binary {
+                left: Box::new(left),
+                right: Box::new(right),
+                op: binary.op,
+            }),
+            typ,
+            span,
+        })
+    }
+
+    /// Like `resolve_operands`, but a pointer can be offset by an integer
+    ///     with `+` and `-`, and two pointers of the same type subtracted.
+    ///     An offset pointer has the pointer's type, and the distance between
+    ///     two is an isize. The pointer must be on the left.
+    fn resolve_arithmetic_operands(
+        &mut self,
+        binary: &syntax_ast::Binary,
+        expected: Option<&Type>,
+    ) -> Result<(Expression, Expression), Error> {
+        if !matches!(binary.op, BinaryOp::Plus | BinaryOp::Minus)
+            || matches!(binary.left.value, syntax_ast::ExpressionValue::Literal(_))
+        {
+            return self.resolve_operands(binary, expected);
+        }
+        let left = self.resolve_expression(&binary.left, expected)?;
+        if !matches!(left.typ, Type::Pointer { .. }) {
+            let right = self.resolve_typed_expression(&binary.right, &left.typ)?;
+            return Ok((left, right));
+        }
+        let right = self.resolve_expression(&binary.right, Some(&Type::Isize))?;
+        let valid = right.typ.is_integer() || binary.op == BinaryOp::Minus && right.typ == left.typ;
+        if !valid {
+            return Err(Error {
+                typ: ErrorType::Expression,
+                msg: "Invalid pointer arithmetic",
+                span: right.span,
+            });
+        }
+        Ok((left, right))
+    }
+
+    /// Resolves both operands of a binary operation, which must be of the same type.
+    /// If only the left one is a literal, the right one is resolved first
+    ///     so that the literal can take its type.
+    fn resolve_operands(
+        &mut self,
+        binary: &syntax_ast::Binary,
+        expected: Option<&Type>,
+    ) -> Result<(Expression, Expression), Error> {
+        let is_literal = |exp: &syntax_ast::Expression| {
+            matches!(exp.value, syntax_ast::ExpressionValue::Literal(_))
+        };
+        if expected.is_none() && is_literal(&binary.left) && !is_literal(&binary.right) {
+            let right = self.resolve_expression(&binary.right, None)?;
+            let left = self.resolve_typed_expression(&binary.left, &right.typ)?;
+            return Ok((left, right));
+        }
+        let left = self.resolve_expression(&binary.left, expected)?;
+        let right = self.resolve_typed_expression(&binary.right, &left.typ)?;
+        Ok((left, right))
+    }
+
+    fn resolve_field_access(
+        &mut self,
+        binary: &syntax_ast::Binary,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let left = auto_deref(self.resolve_expression(&binary.left, None)?);
+        let (identifier, typ) = match &binary.right.value {
+            syntax_ast::ExpressionValue::Identifier(name)
+                if name.len() == 1
+                    && Some(name[0]) == self.pool.search_symbol("len")
+                    && !matches!(left.typ.resolve_alias(), Type::Custom(_)) =>
+            {
+                return resolve_length(left, binary.right.span, span);
+            }
+            syntax_ast::ExpressionValue::Identifier(name) if name.len() == 1 => {
+                let Some(typ) = field_type(&left.typ, name[0]) else {
+                    return Err(Error {
+                        typ: ErrorType::Name,
+                        msg: "No such field",
+                        span: binary.right.span,
+                    });
+                };
+                (Identifier::Field(name[0]), typ)
+            }
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(index, _)) => {
+                let element = match left.typ.resolve_alias() {
+                    Type::Tuple(types) => types.get(*index as usize).cloned(),
+                    _ => None,
+                };
+                let Some(typ) = element else {
+                    return Err(Error {
+                        typ: ErrorType::Name,
+                        msg: "No such tuple element",
+                        span: binary.right.span,
+                    });
+                };
+                (Identifier::TupleElement(*index as usize), typ)
+            }
+            _ => {
+                return Err(Error {
+                    typ: ErrorType::Expression,
+                    msg: "Expected a field name",
+                    span: binary.right.span,
+                });
+            }
+        };
+        let right = Expression {
+            value: ExpressionValue::Identifier(identifier),
+            typ: typ.clone(),
+            span: binary.right.span,
+        };
+        Ok(Expression {
+            value: ExpressionValue::Binary(Binary {
+                left: Box::new(left),
+                right: Box::new(right),
+                op: BinaryOp::FieldAccess,
+            }),
+            typ,
+            span,
+        })
+    }
+
+    fn resolve_indexing(
+        &mut self,
+        binary: &syntax_ast::Binary,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let left = self.resolve_expression(&binary.left, None)?;
+        let typ = match left.typ.resolve_alias() {
+            Type::Array { inner, .. } | Type::Slice { inner, .. } => *inner,
+            _ => {
+                return Err(Error {
+                    typ: ErrorType::Expression,
+                    msg: "Indexing a non-array value",
+                    span: left.span,
+                });
+            }
+        };
+        let right = self.resolve_expression(&binary.right, Some(&Type::Usize))?;
+        if !right.typ.is_integer() {
+            return Err(Error {
+                typ: ErrorType::Expression,
+                msg: "Indexing with a non-integer value",
+                span: right.span,
+            });
+        }
+        Ok(Expression {
+            value: ExpressionValue::Binary(Binary {
+                left: Box::new(left),
+                right: Box::new(right),
+                op: BinaryOp::Indexing,
+            }),
+            typ,
+            span,
+        })
+    }
+
+    fn resolve_unary(
+        &mut self,
+        unary: &syntax_ast::Unary,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let (operand, typ) = match unary.op {
+            UnaryOp::LogicalNot => {
+                let operand = self.resolve_typed_expression(&unary.operand, &Type::Bool)?;
+                (operand, Type::Bool)
+            }
+            UnaryOp::Negate => {
+                let operand = self.resolve_expression(&unary.operand, expected)?;
+                let signed = operand.typ.integer_bounds().is_some_and(|(min, _)| min < 0);
+                if !signed && !operand.typ.is_float() {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Negating an unsigned or non-numeric value",
+                        span: operand.span,
+                    });
+                }
+                let typ = operand.typ.clone();
+                (operand, typ)
+            }
+            UnaryOp::BitNot => {
+                let operand = self.resolve_expression(&unary.operand, expected)?;
+                if !operand.typ.is_integer() {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Bitwise operation on a non-integer value",
+                        span: operand.span,
+                    });
+                }
+                let typ = operand.typ.clone();
+                (operand, typ)
+            }
+            UnaryOp::Dereference => {
+                let operand = self.resolve_expression(&unary.operand, None)?;
+                let Type::Pointer { inner, .. } = operand.typ.resolve_alias() else {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Dereferencing a non-pointer value",
+                        span: operand.span,
+                    });
+                };
+                let typ = *inner;
+                (operand, typ)
+            }
+            UnaryOp::AddressOf => {
+                let (inner, mutable) = match expected.map(Type::resolve_alias) {
+                    Some(Type::Pointer { inner, mutable }) => (Some(*inner), mutable),
+                    _ => (None, false),
+                };
+                let operand = self.resolve_expression(&unary.operand, inner.as_ref())?;
+                // A `*var` pointer could be used to change what it points to.
+                if mutable && place_mutability(&operand) != Some(true) {
+                    return Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Taking a mutable pointer to an immutable value",
+                        span,
+                    });
+                }
+                let typ = Type::Pointer {
+                    inner: Box::new(operand.typ.clone()),
+                    mutable,
+                };
+                (operand, typ)
+            }
+        };
+        Ok(Expression {
+            value: ExpressionValue::Unary(Unary {
+                operand: Box::new(operand),
+                op: unary.op,
+            }),
+            typ,
+            span,
+        })
+    }
+}
+
+/// Integer literals take the expected type if it's an integer type, i32 otherwise.
+fn integer_literal_type(value: i128, span: Span, expected: Option<&Type>) -> Result<Type, Error> {
+    let typ = match expected {
+        Some(typ) if typ.is_integer() => typ.clone(),
+        _ => Type::I32,
+    };
+    let (min, max) = typ.integer_bounds().unwrap();
+    if value < min || value > max {
+        return Err(Error {
+            typ: ErrorType::Expression,
+            msg: "Integer literal out of range",
+            span,
+        });
+    }
+    Ok(typ)
+}
+
+/// Looks a name up among the globals and functions of a file.
+/// The visibility is returned along with it for lookups from other files.
+pub(super) fn lookup_file(file: &File, name: SymbolId) -> Option<Scope<Identifier>> {
+    if let Some(global) = file.globals.get(&name) {
+        return Some(Scope {
+            visibility: global.visibility,
+            value: Identifier::Declaraction(global.value.clone()),
+        });
+    }
+    file.functions.get(&name).map(|function| Scope {
+        visibility: function.visibility,
+        value: Identifier::Function(function.value.clone()),
+    })
+}
+
+/// `.len` is the length of a slice, or the constant length of an array.
+/// It isn't resolved for structs and unions, which may have a `len` field.
+fn resolve_length(left: Expression, right_span: Span, span: Span) -> Result<Expression, Error> {
+    let length = match left.typ.resolve_alias() {
+        Type::Array { size, .. } => ExpressionValue::Literal(Literal::UInt(size)),
+        Type::Slice { .. } => {
+            let right = Expression {
+                value: ExpressionValue::Identifier(Identifier::Length),
+                typ: Type::Usize,
+                span: right_span,
+            };
+            ExpressionValue::Binary(Binary {
+                left: Box::new(left),
+                right: Box::new(right),
+                op: BinaryOp::FieldAccess,
+            })
+        }
+        _ => {
+            return Err(Error {
+                typ: ErrorType::Name,
+                msg: "Only slices and arrays have a length",
+                span: right_span,
+            });
+        }
+    };
+    Ok(Expression {
+        value: length,
+        typ: Type::Usize,
+        span,
+    })
+}
+
+/// Only values compared as a whole have `==` and `!=`: numbers, bools,
+///     pointers, functions and enums without payloads. Aggregates would
+///     have to be compared field by field, which is only done for types
+///     that `@derive(Eq)`.
+pub(super) fn has_equality(typ: &Type) -> bool {
+    match typ.resolve_alias() {
+        Type::Bool | Type::Pointer { .. } | Type::Function(_) => true,
+        Type::Custom(def) => {
+            let def = def.read().unwrap();
+            matches!(def.body, TypeDefBody::Enum(_)) || def.derives.contains(&Derive::Eq)
+        }
+        typ => typ.is_numeric(),
+    }
+}
+
+/// `p.x` through a pointer `p` is `(*p).x`. Only one level is dereferenced,
+///     so a pointer to a pointer needs an explicit `*`.
+fn auto_deref(exp: Expression) -> Expression {
+    let Type::Pointer { inner, .. } = exp.typ.resolve_alias() else {
+        return exp;
+    };
+    if matches!(inner.resolve_alias(), Type::Pointer { .. }) {
+        return exp;
+    }
+    let span = exp.span;
+    Expression {
+        value: ExpressionValue::Unary(Unary {
+            operand: Box::new(exp),
+            op: UnaryOp::Dereference,
+        }),
+        typ: *inner,
+        span,
+    }
+}
+
+/// The type of a struct or union field. None if there's no such field.
+fn field_type(typ: &Type, field: SymbolId) -> Option<Type> {
+    let Type::Custom(typ) = typ.resolve_alias() else {
+        return None;
+    };
+    match &typ.read().unwrap().body {
+        TypeDefBody::Struct(fields) | TypeDefBody::Union(fields) => fields.get(&field).cloned(),
+        _ => None,
+    }
+}
+
+/// Whether an f32 holds the same number as the literal, i.e. the literal
+///     has no more digits than an f32 can keep and isn't out of range.
+fn fits_f32(value: f64) -> bool {
+    let narrowed = value as f32;
+    narrowed.is_finite() && narrowed.to_string().parse::<f64>() == Ok(value)
+}
@@ -0,0 +1,263 @@
+use super::*;
+use crate::intern_pool::StringId;
+use crate::semantic_ast::{Binary, Statement, Unary};
+use crate::visitor::{self, Visitor};
+use syntax_ast::{BinaryOp, UnaryOp};
+
+/// A literal operand of a foldable expression.
+enum Constant {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+}
+
+/// Folds the binary and unary expressions whose operands are literals into literals.
+/// Operands are visited, and therefore folded, before the expression itself,
+///     so folding one level at a time folds whole constant expressions.
+/// Expressions that can't be folded are left unchanged.
+/// Folding stops at the first error, like dividing by zero.
+struct ConstantFolder<'a> {
+    /// Where the contents of string literals are.
+    pool: &'a InternPool,
+    error: Option<Error>,
+}
+
+impl Visitor for ConstantFolder<'_> {
+    fn visit_expression(&mut self, exp: &mut Expression) {
+        if self.error.is_some() {
+            return;
+        }
+        visitor::walk_expression(self, exp);
+        if self.error.is_some() {
+            return;
+        }
+        let folded = match &exp.value {
+            ExpressionValue::Binary(binary) => match binary.left.value {
+                ExpressionValue::Literal(Literal::String(string)) => {
+                    self.fold_string(string, binary)
+                }
+                _ => fold_binary(binary, &exp.typ, exp.span),
+            },
+            ExpressionValue::Unary(unary) => fold_unary(unary, &exp.typ, exp.span),
+            _ => Ok(None),
+        };
+        match folded {
+            Ok(Some(literal)) => exp.value = ExpressionValue::Literal(literal),
+            Ok(None) => {}
+            Err(err) => self.error = Some(err),
+        }
+    }
+}
+
+impl<'a> ConstantFolder<'a> {
+    fn new(pool: &'a InternPool) -> Self {
+        Self { pool, error: None }
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        self.error.map_or(Ok(()), Err)
+    }
+
+    /// Strings are the bytes they're encoded as, like any `[]let u8`:
+    ///     `.len` is the length in bytes, not in chars, and indexing
+    ///     gives a byte. A constant index has to be in bounds.
+    fn fold_string(&self, string: StringId, binary: &Binary) -> Result<Option<Literal>, Error> {
+        let bytes = self.pool.get_string(string).as_bytes();
+        match binary.op {
+            BinaryOp::FieldAccess
+                if matches!(
+                    binary.right.value,
+                    ExpressionValue::Identifier(Identifier::Length)
+                ) =>
+            {
+                Ok(Some(Literal::UInt(bytes.len() as u64)))
+            }
+            BinaryOp::Indexing => {
+                let Some(Constant::Int(index)) = constant(&binary.right) else {
+                    return Ok(None);
+                };
+                match usize::try_from(index)
+                    .ok()
+                    .and_then(|index| bytes.get(index))
+                {
+                    Some(&byte) => Ok(Some(Literal::UInt(byte as u64))),
+                    None => Err(Error {
+                        typ: ErrorType::Expression,
+                        msg: "Index out of bounds",
+                        span: binary.right.span,
+                    }),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Folds a resolved expression, which is returned with its constant parts folded.
+pub(super) fn fold_constants(mut exp: Expression, pool: &InternPool) -> Result<Expression, Error> {
+    let mut folder = ConstantFolder::new(pool);
+    folder.visit_expression(&mut exp);
+    folder.finish()?;
+    Ok(exp)
+}
+
+/// Folds every expression of a resolved block.
+pub(super) fn fold_block(block: &mut [Statement], pool: &InternPool) -> Result<(), Error> {
+    let mut folder = ConstantFolder::new(pool);
+    visitor::walk_block(&mut folder, block);
+    folder.finish()
+}
+
+fn constant(exp: &Expression) -> Option<Constant> {
+    match exp.value {
+        ExpressionValue::Literal(Literal::UInt(uint)) => Some(Constant::Int(uint as i128)),
+        ExpressionValue::Literal(Literal::Int(int)) => Some(Constant::Int(int as i128)),
+        ExpressionValue::Literal(Literal::Float(float)) => Some(Constant::Float(float)),
+        ExpressionValue::Literal(Literal::Bool(bool)) => Some(Constant::Bool(bool)),
+        _ => None,
+    }
+}
+
+fn fold_binary(binary: &Binary, typ: &Type, span: Span) -> Result<Option<Literal>, Error> {
+    // Dividing by a constant zero is always wrong, whether or not the left side is constant.
+    if matches!(binary.op, BinaryOp::Div | BinaryOp::Mod)
+        && matches!(constant(&binary.right), Some(Constant::Int(0)))
+    {
+        return Err(Error {
+            typ: ErrorType::Expression,
+            msg: "Division by zero",
+            span,
+        });
+    }
+    // So is shifting by a negative amount, or by the width of the shifted type or more.
+    if matches!(binary.op, BinaryOp::LeftShift | BinaryOp::RightShift)
+        && let Some(Constant::Int(amount)) = constant(&binary.right)
+        && !(0..bit_width(typ)).contains(&amount)
+    {
+        return Err(Error {
+            typ: ErrorType::Expression,
+            msg: "Shift amount out of range",
+            span: binary.right.span,
+        });
+    }
+    let (Some(left), Some(right)) = (constant(&binary.left), constant(&binary.right)) else {
+        return Ok(None);
+    };
+    Ok(match (left, right) {
+        (Constant::Int(left), Constant::Int(right)) => {
+            if let Some(result) = compare(binary.op, left, right) {
+                return Ok(Some(Literal::Bool(result)));
+            }
+            let value = match binary.op {
+                BinaryOp::Plus => left.checked_add(right),
+                BinaryOp::Minus => left.checked_sub(right),
+                BinaryOp::Mul => left.checked_mul(right),
+                BinaryOp::Div => left.checked_div(right),
+                BinaryOp::Mod => left.checked_rem(right),
+                BinaryOp::BitAnd => Some(left & right),
+                BinaryOp::BitOr => Some(left | right),
+                BinaryOp::BitXor => Some(left ^ right),
+                // Operands are at most 64 bits wide, so these can't lose bits.
+                // Bits shifted out of the type are dropped, not an overflow.
+                BinaryOp::LeftShift => Some(truncate(left << right, typ)),
+                BinaryOp::RightShift => Some(left >> right),
+                _ => return Ok(None),
+            };
+            Some(integer_literal(value, typ, span)?)
+        }
+        (Constant::Float(left), Constant::Float(right)) => {
+            if let Some(result) = compare(binary.op, left, right) {
+                return Ok(Some(Literal::Bool(result)));
+            }
+            let value = match binary.op {
+                BinaryOp::Plus => left + right,
+                BinaryOp::Minus => left - right,
+                BinaryOp::Mul => left * right,
+                BinaryOp::Div => left / right,
+                BinaryOp::Mod => left % right,
+                _ => return Ok(None),
+            };
+            Some(float_literal(value, typ))
+        }
+        (Constant::Bool(left), Constant::Bool(right)) => match binary.op {
+            BinaryOp::LogicalAnd => Some(Literal::Bool(left && right)),
+            BinaryOp::LogicalOr => Some(Literal::Bool(left || right)),
+            op => compare(op, left, right).map(Literal::Bool),
+        },
+        _ => None,
+    })
+}
+
+fn fold_unary(unary: &Unary, typ: &Type, span: Span) -> Result<Option<Literal>, Error> {
+    let Some(operand) = constant(&unary.operand) else {
+        return Ok(None);
+    };
+    Ok(match (unary.op, operand) {
+        (UnaryOp::LogicalNot, Constant::Bool(bool)) => Some(Literal::Bool(!bool)),
+        (UnaryOp::Negate, Constant::Int(int)) => {
+            Some(integer_literal(int.checked_neg(), typ, span)?)
+        }
+        (UnaryOp::Negate, Constant::Float(float)) => Some(float_literal(-float, typ)),
+        (UnaryOp::BitNot, Constant::Int(int)) => {
+            let (min, max) = typ.integer_bounds().unwrap();
+            let value = if min == 0 { max - int } else { !int };
+            Some(integer_literal(Some(value), typ, span)?)
+        }
+        _ => None,
+    })
+}
+
+/// The number of bits of an integer type.
+fn bit_width(typ: &Type) -> i128 {
+    let (min, max) = typ.integer_bounds().unwrap();
+    (max - min + 1).trailing_zeros() as i128
+}
+
+/// Keeps the low bits of a value that fit in an integer type, in two's complement,
+///     so shifting into the sign bit gives a negative number.
+fn truncate(value: i128, typ: &Type) -> i128 {
+    let bits = bit_width(typ);
+    let value = value & ((1 << bits) - 1);
+    let (_, max) = typ.integer_bounds().unwrap();
+    if value > max {
+        value - (1 << bits)
+    } else {
+        value
+    }
+}
+
+/// The result of a comparison, or None if the operator doesn't compare.
+fn compare<T: PartialOrd>(op: BinaryOp, left: T, right: T) -> Option<bool> {
+    Some(match op {
+        BinaryOp::Eq => left == right,
+        BinaryOp::NotEq => left != right,
+        BinaryOp::Gt => left > right,
+        BinaryOp::Ge => left >= right,
+        BinaryOp::Lt => left < right,
+        BinaryOp::Le => left <= right,
+        _ => return None,
+    })
+}
+
+/// Like the lexer, only negative integers are Int literals.
+/// None means the computation itself overflowed.
+fn integer_literal(value: Option<i128>, typ: &Type, span: Span) -> Result<Literal, Error> {
+    let (min, max) = typ.integer_bounds().unwrap();
+    match value {
+        Some(value) if value < 0 && value >= min => Ok(Literal::Int(value as i64)),
+        Some(value) if value >= 0 && value <= max => Ok(Literal::UInt(value as u64)),
+        _ => Err(Error {
+            typ: ErrorType::Expression,
+            msg: "Constant overflow",
+            span,
+        }),
+    }
+}
+
+/// f32 results are rounded so the folded value is what the program would compute.
+fn float_literal(value: f64, typ: &Type) -> Literal {
+    match typ {
+        Type::F32 => Literal::Float(value as f32 as f64),
+        _ => Literal::Float(value),
+    }
+}
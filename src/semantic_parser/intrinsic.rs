@@ -0,0 +1,127 @@
+use super::*;
+use crate::semantic_ast::{Call, Intrinsic};
+
+impl Resolver<'_> {
+    /// A callee is an intrinsic if it's named `print` or `format`, optionally
+    ///     qualified like `std::print`, and the name doesn't refer to anything else.
+    pub(super) fn intrinsic(&self, callee: &syntax_ast::Expression) -> Option<Intrinsic> {
+        let syntax_ast::ExpressionValue::Identifier(name) = &callee.value else {
+            return None;
+        };
+        let last = *name.last().unwrap();
+        let intrinsic = if Some(last) == self.pool.search_symbol("print") {
+            Intrinsic::Print
+        } else if Some(last) == self.pool.search_symbol("format") {
+            Intrinsic::Format
+        } else {
+            return None;
+        };
+        match self.lookup(name) {
+            Some(_) => None,
+            None => Some(intrinsic),
+        }
+    }
+
+    /// Both `print` and `format` take a string literal followed by
+    ///     the values of its placeholders, which may be of any type.
+    pub(super) fn resolve_intrinsic_call(
+        &mut self,
+        intrinsic: Intrinsic,
+        call: &syntax_ast::Call,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let Some((format, values)) = call.args.split_first() else {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg: "Expected a format string",
+                span,
+            });
+        };
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::String(string)) =
+            format.value
+        else {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg: "The format string must be a string literal",
+                span: format.span,
+            });
+        };
+        if let Err(msg) = check_format_string(self.pool.get_string(string), values.len()) {
+            return Err(Error {
+                typ: ErrorType::Call,
+                msg,
+                span: format.span,
+            });
+        }
+        let mut args = Vec::with_capacity(call.args.len());
+        args.push(self.resolve_expression(format, None)?);
+        for value in values.iter() {
+            args.push(self.resolve_expression(value, None)?);
+        }
+        let typ = match intrinsic {
+            Intrinsic::Format => Type::Slice {
+                inner: Box::new(Type::U8),
+                mutable: false,
+            },
+            Intrinsic::Print => Type::Unit,
+        };
+        let function = Expression {
+            value: ExpressionValue::Identifier(Identifier::Intrinsic(intrinsic)),
+            typ: Type::Unit,
+            span: call.function.span,
+        };
+        Ok(Expression {
+            value: ExpressionValue::Call(Call {
+                function: Box::new(function),
+                args,
+            }),
+            typ,
+            span,
+        })
+    }
+}
+
+/// Checks a format string against the number of values it's given.
+/// `{}` takes the next value, `{n}` takes the nth one, and `{{` and `}}`
+///     are literal braces. Every value must be used at least once.
+fn check_format_string(format: &str, value_count: usize) -> Result<(), &'static str> {
+    let mut used = vec![false; value_count];
+    let mut next = 0;
+    let mut chars = format.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let mut position = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) if ch.is_ascii_digit() => position.push(ch),
+                        _ => return Err("Invalid format placeholder"),
+                    }
+                }
+                let index = if position.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    position.parse().map_err(|_| "Invalid format placeholder")?
+                };
+                match used.get_mut(index) {
+                    Some(used) => *used = true,
+                    None => return Err("Too few format arguments"),
+                }
+            }
+            '}' => return Err("Unmatched `}` in format string"),
+            _ => {}
+        }
+    }
+    if used.contains(&false) {
+        return Err("Too many format arguments");
+    }
+    Ok(())
+}
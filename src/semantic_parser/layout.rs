@@ -0,0 +1,125 @@
+use super::*;
+
+/// Computes the size and alignment of every type definition in the file.
+/// The colors are shared by all files, since a type can contain types
+///     defined in other files.
+pub(super) fn layout_file_types(
+    sem_file: &File,
+    colors: &mut HashMap<TypeId, TypeColor>,
+) -> Result<(), Error> {
+    for (_, typ) in iter_by_id(&sem_file.types) {
+        layout_type_def(&typ.value, colors)?;
+    }
+    Ok(())
+}
+
+/// The size and alignment of a type, in bytes.
+fn layout(typ: &Type, colors: &mut HashMap<TypeId, TypeColor>) -> Result<(usize, usize), Error> {
+    Ok(match typ {
+        Type::Unit => (0, 1),
+        Type::U8 | Type::I8 | Type::Bool => (1, 1),
+        Type::U16 | Type::I16 => (2, 2),
+        Type::U32 | Type::I32 | Type::F32 => (4, 4),
+        Type::U64 | Type::I64 | Type::Usize | Type::Isize | Type::F64 => (8, 8),
+        Type::Pointer { .. } | Type::Function(_) => (8, 8),
+        // A pointer and a length.
+        Type::Slice { .. } => (16, 8),
+        Type::Array { inner, size, .. } => {
+            let (inner_size, align) = layout(inner, colors)?;
+            (inner_size.saturating_mul(*size as usize), align)
+        }
+        Type::Tuple(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| layout(element, colors))
+                .collect::<Result<Vec<_>, _>>()?;
+            aggregate_layout(&elements, false)
+        }
+        Type::Custom(def) => layout_type_def(def, colors)?,
+    })
+}
+
+/// Lays out a type definition once and stores the result in it.
+/// Reaching a definition that's still being laid out means it contains itself.
+fn layout_type_def(
+    def: &RwArc<TypeDef>,
+    colors: &mut HashMap<TypeId, TypeColor>,
+) -> Result<(usize, usize), Error> {
+    let guard = def.read().unwrap();
+    match colors.get(&guard.id).unwrap_or(&TypeColor::Unvisited) {
+        TypeColor::Visited => return Ok((guard.size, guard.align)),
+        TypeColor::Visiting => {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Type contains itself",
+                span: guard.span,
+            });
+        }
+        TypeColor::Unvisited => {}
+    }
+    colors.insert(guard.id, TypeColor::Visiting);
+    let (size, align) = match &guard.body {
+        // Fields aren't kept in source order, so they're laid out from
+        //     the most aligned to the least, which also minimizes padding.
+        TypeDefBody::Struct(fields) => {
+            let mut fields = fields
+                .values()
+                .map(|field| layout(field, colors))
+                .collect::<Result<Vec<_>, _>>()?;
+            fields.sort_by_key(|field| std::cmp::Reverse(field.1));
+            aggregate_layout(&fields, guard.packed)
+        }
+        TypeDefBody::Union(fields) => {
+            let mut size = 0;
+            let mut align = 1;
+            for field in fields.values() {
+                let (field_size, field_align) = layout(field, colors)?;
+                size = size.max(field_size);
+                align = align.max(field_align);
+            }
+            if guard.packed {
+                (size, 1)
+            } else {
+                (size.next_multiple_of(align), align)
+            }
+        }
+        TypeDefBody::Enum(enum_) => layout(&enum_.backing, colors)?,
+        // The payloads overlap like a union's, after the tag.
+        TypeDefBody::TaggedEnum(tagged) => {
+            let mut payload = (0, 1);
+            for elements in tagged.payloads.values() {
+                let elements = elements
+                    .iter()
+                    .map(|element| layout(element, colors))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (size, align) = aggregate_layout(&elements, false);
+                payload = (payload.0.max(size), payload.1.max(align));
+            }
+            let tag = layout(&tagged.tags.backing, colors)?;
+            aggregate_layout(&[tag, payload], false)
+        }
+        TypeDefBody::Alias(inner) => layout(inner, colors)?,
+    };
+    let id = guard.id;
+    drop(guard);
+    let mut guard = def.write().unwrap();
+    guard.size = size;
+    guard.align = align;
+    colors.insert(id, TypeColor::Visited);
+    Ok((size, align))
+}
+
+/// Lays out fields in the given order. Unless packed, each field starts at a
+///     multiple of its alignment, and the size is a multiple of the largest one.
+fn aggregate_layout(fields: &[(usize, usize)], packed: bool) -> (usize, usize) {
+    if packed {
+        return (fields.iter().map(|field| field.0).sum(), 1);
+    }
+    let mut size = 0usize;
+    let mut align = 1;
+    for &(field_size, field_align) in fields {
+        size = size.next_multiple_of(field_align) + field_size;
+        align = align.max(field_align);
+    }
+    (size.next_multiple_of(align), align)
+}
@@ -1,30 +1,176 @@
-use crate::intern_pool::SymbolId;
+use crate::intern_pool::{InternPool, SymbolId, iter_by_id};
 use crate::rw_arc::RwArc;
 use crate::semantic_ast::{
-    Ast, Declaration, Expression, ExpressionValue, File, Function, FunctionArg, FunctionType,
-    Literal, Module, Type, TypeDef, TypeDefBody, TypeId,
+    Asm, Ast, Declaration, Derive, Enum, Expression, ExpressionValue, File, Function, FunctionArg,
+    FunctionBody, FunctionType, Identifier, Literal, Module, TaggedEnum, Type, TypeDef,
+    TypeDefBody, TypeId,
 };
 use crate::span::Span;
 use crate::token::TokenType;
 use crate::{intern_pool, syntax_ast};
-use std::collections::HashMap;
-use syntax_ast::Scope;
+use fold::{fold_block, fold_constants};
+use std::collections::{HashMap, HashSet};
+use syntax_ast::{Scope, Visibility};
 
+mod expression;
+mod fold;
+mod intrinsic;
+mod layout;
+mod statement;
+
+#[derive(Debug)]
 pub(crate) enum ErrorType {
     Import,
     Type,
+    Name,
+    Expression,
+    Call,
+    Statement,
+    /// A match over a bool or an enum without a default is missing cases,
+    ///     `true`, `false` or variants, in the order they're declared.
+    NonExhaustive {
+        missing: Box<[SymbolId]>,
+    },
+    /// Inlining a poisoned global. Its own error is reported instead,
+    ///     so this one never is.
+    Poisoned,
 }
 
+#[derive(Debug)]
 pub(crate) struct Error {
     pub(crate) typ: ErrorType,
     pub(crate) msg: &'static str,
     pub(crate) span: Span,
 }
 
+#[derive(Debug)]
+pub(crate) enum WarningType {
+    Shadowing,
+}
+
+/// Like an error, but doesn't stop the semantic pass.
+#[derive(Debug)]
+pub(crate) struct Warning {
+    pub(crate) typ: WarningType,
+    pub(crate) msg: &'static str,
+    pub(crate) span: Span,
+}
+
+/// Extra information about the other diagnostics, like that some were left out.
+#[derive(Debug)]
+pub(crate) struct Note {
+    pub(crate) msg: &'static str,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+/// Everything reported by the semantic pass.
+/// An error in a global value or a function body doesn't stop the pass,
+///     so the others are still checked. Errors in earlier phases do,
+///     so there's at most one of them.
+#[derive(Debug, Default)]
+pub(crate) struct Diagnostics {
+    pub(crate) errors: Vec<Error>,
+    pub(crate) warnings: Vec<Warning>,
+    pub(crate) notes: Vec<Note>,
+}
+
+impl Diagnostics {
+    /// Lists every diagnostic with its level, errors first and notes last.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Level, Span, &'static str)> + '_ {
+        let errors = self.errors.iter().map(|e| (Level::Error, e.span, e.msg));
+        let warnings = self
+            .warnings
+            .iter()
+            .map(|w| (Level::Warning, w.span, w.msg));
+        let notes = self.notes.iter().map(|n| (Level::Note, n.span, n.msg));
+        errors.chain(warnings).chain(notes)
+    }
+
+    /// Records an error, unless it comes from a poisoned global.
+    fn report(&mut self, err: Error) {
+        if !matches!(err.typ, ErrorType::Poisoned) {
+            self.errors.push(err);
+        }
+    }
+
+    /// Keeps the first `max_errors` errors. They're sorted by then, so which
+    ///     ones are kept doesn't depend on the order bodies were checked in.
+    /// The first one left out adds a note instead.
+    fn truncate(&mut self, max_errors: usize) {
+        if self.errors.len() > max_errors {
+            self.notes.push(Note {
+                msg: "Too many errors, the rest aren't reported",
+                span: self.errors[max_errors].span,
+            });
+            self.errors.truncate(max_errors);
+        }
+    }
+
+    /// Orders errors and warnings by where they are in the source: by path,
+    ///     then line and column.
+    /// Bodies are checked in whatever order the files and functions are stored,
+    ///     so this makes the output deterministic. Path-only spans have no
+    ///     line, so they come first in their file.
+    fn sort(&mut self, pool: &InternPool) {
+        let ranks = pool.path_ranks();
+        let location = |span: &Span| (ranks.get(&span.path), span.line, span.column);
+        self.errors.sort_by_key(|err| location(&err.span));
+        self.warnings.sort_by_key(|warning| location(&warning.span));
+    }
+}
+
+pub(crate) struct SemanticOptions {
+    /// How many errors are reported. The rest are still checked for, but left out.
+    /// The first error is always reported, even if this is zero.
+    pub(crate) max_errors: usize,
+}
+
+impl Default for SemanticOptions {
+    fn default() -> Self {
+        Self { max_errors: 100 }
+    }
+}
+
 pub(crate) struct SemanticParser {
     type_id: TypeId,
 }
 
+/// The state needed to resolve function bodies and global values.
+/// One is created for each function or global.
+struct Resolver<'a> {
+    /// The file the code is in.
+    file: &'a File,
+    /// Used to read string literals and to recognize intrinsics.
+    pool: &'a InternPool,
+    /// Local variables and arguments. The innermost scope is the last one.
+    scopes: Vec<HashMap<SymbolId, Identifier>>,
+    /// What `return` must return. Unit if the function has no return type.
+    return_type: Type,
+    /// Whether immutable declarations with constant values are replaced by their values.
+    /// Set where values must be known at compile time, like array sizes.
+    inline_constants: bool,
+    /// Warnings found so far, collected by whoever created the resolver.
+    warnings: Vec<Warning>,
+    /// The enclosing loops, innermost last. None for loop statements,
+    ///     which can't break with a value.
+    loops: Vec<Option<LoopValue>>,
+}
+
+/// The value of a `loop` expression, agreed on by all of its `break`s.
+struct LoopValue {
+    /// The type the loop is expected to have, used as a hint for `break` values.
+    hint: Option<Type>,
+    /// The type of the first `break`, if there is one yet.
+    typ: Option<Type>,
+}
+
 enum TypeColor {
     Unvisited,
     Visiting,
@@ -32,10 +178,103 @@ enum TypeColor {
 }
 
 impl SemanticParser {
+    /// Resolves the whole syntax AST, stopping at the first error.
+    pub(crate) fn parse(ast: &syntax_ast::Ast, pool: &InternPool) -> Result<Ast, Error> {
+        let (sem_ast, mut diagnostics) = Self::analyze(ast, pool);
+        sem_ast.ok_or_else(|| diagnostics.errors.remove(0))
+    }
+
+    /// Resolves the whole syntax AST, returning the warnings found along the way
+    ///     even if it succeeds. The AST is None if there are errors.
+    pub(crate) fn analyze(ast: &syntax_ast::Ast, pool: &InternPool) -> (Option<Ast>, Diagnostics) {
+        Self::analyze_with_options(ast, pool, SemanticOptions::default())
+    }
+
+    pub(crate) fn analyze_with_options(
+        ast: &syntax_ast::Ast,
+        pool: &InternPool,
+        options: SemanticOptions,
+    ) -> (Option<Ast>, Diagnostics) {
+        let mut diagnostics = Diagnostics::default();
+        let sem_ast = match Self::resolve(ast, pool, &mut diagnostics) {
+            Ok(sem_ast) if diagnostics.errors.is_empty() => Some(sem_ast),
+            Ok(_) => None,
+            Err(err) => {
+                diagnostics.report(err);
+                None
+            }
+        };
+        diagnostics.sort(pool);
+        diagnostics.truncate(options.max_errors.max(1));
+        (sem_ast, diagnostics)
+    }
+
+    /// Resolves the whole syntax AST. This is done in phases so that
+    ///     everything a phase refers to is resolved by an earlier one:
+    /// 1. Names: skeletons are built for every definition.
+    /// 2. Imports: file imports are bound to modules and items.
+    /// 3. Constants: immutable integer globals with constant values are
+    ///    evaluated, so array sizes can refer to them.
+    /// 4. Types: type definition bodies are resolved.
+    /// 5. Layouts: type sizes and alignments are computed, then derives checked.
+    /// 6. Signatures: global types and function signatures are resolved.
+    /// 7. Defaults: the other constant globals are evaluated, now that their
+    ///    types are known, then struct field defaults, which may refer to them.
+    ///    Globals that fail to resolve are poisoned before the defaults
+    ///    referring to them are, so only the globals' errors are reported.
+    /// 8. Bodies: global values and function bodies are resolved.
+    ///    Their errors are collected in `diagnostics` instead of returned.
+    fn resolve(
+        ast: &syntax_ast::Ast,
+        pool: &InternPool,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Ast, Error> {
+        let mut parser = SemanticParser { type_id: TypeId(0) };
+        let sem_ast = parser.collect_names(ast);
+        for (name, syn_module) in iter_by_id(&ast.modules) {
+            resolve_imports(syn_module, &sem_ast.modules[&name], &sem_ast)?;
+        }
+        resolve_constants(ast, &sem_ast, pool, Type::is_integer)?;
+        for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+            resolve_file_types(syn_file, sem_file, pool)
+        })?;
+        let mut colors = HashMap::new();
+        for_each_file(ast, &sem_ast, &mut |_, sem_file| {
+            layout::layout_file_types(sem_file, &mut colors)
+        })?;
+        for_each_file(ast, &sem_ast, &mut |_, sem_file| {
+            check_file_derives(sem_file)
+        })?;
+        for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+            resolve_file_signatures(syn_file, sem_file, pool)
+        })?;
+        resolve_constants(ast, &sem_ast, pool, can_be_constant)?;
+        // Globals may use the defaults that don't refer to them.
+        for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+            resolve_file_defaults(syn_file, sem_file, pool, false)
+        })?;
+        let mut found = true;
+        while found {
+            found = false;
+            for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+                found |= poison_file_globals(syn_file, sem_file, pool, diagnostics);
+                Ok(())
+            })?;
+        }
+        for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+            resolve_file_defaults(syn_file, sem_file, pool, true)
+        })?;
+        for_each_file(ast, &sem_ast, &mut |syn_file, sem_file| {
+            resolve_file_bodies(syn_file, sem_file, pool, diagnostics);
+            Ok(())
+        })?;
+        Ok(sem_ast)
+    }
+
     fn collect_names(&mut self, ast: &syntax_ast::Ast) -> Ast {
         let mut modules = HashMap::new();
         for (module_name, module) in ast.modules.iter() {
-            modules.insert(*module_name, self.collect_module_names(module));
+            modules.insert(*module_name, self.collect_module_names(module, &[]));
         }
         Ast {
             entry: ast.entry,
@@ -43,14 +282,23 @@ impl SemanticParser {
         }
     }
 
-    fn collect_module_names(&mut self, module: &syntax_ast::Module) -> RwArc<Module> {
+    /// The parents are the modules the module is nested in, outermost first.
+    fn collect_module_names(
+        &mut self,
+        module: &syntax_ast::Module,
+        parents: &[SymbolId],
+    ) -> RwArc<Module> {
+        let modules = [parents, &[module.name]].concat();
         let mut submodules = HashMap::new();
         for (submodule_name, submodule) in module.submodules.iter() {
-            submodules.insert(*submodule_name, self.collect_module_names(submodule));
+            submodules.insert(
+                *submodule_name,
+                self.collect_module_names(submodule, &modules),
+            );
         }
         let mut files = HashMap::new();
         for (file_name, file) in module.files.iter() {
-            files.insert(*file_name, self.collect_file_names(file));
+            files.insert(*file_name, self.collect_file_names(file, &modules));
         }
         RwArc::new(Module {
             name: module.name,
@@ -59,7 +307,7 @@ impl SemanticParser {
         })
     }
 
-    fn collect_file_names(&mut self, file: &syntax_ast::File) -> File {
+    fn collect_file_names(&mut self, file: &syntax_ast::File, modules: &[SymbolId]) -> File {
         let mut globals = HashMap::new();
         for (global_name, global) in file.globals.iter() {
             globals.insert(*global_name, Self::build_global_skeleton(global));
@@ -74,8 +322,11 @@ impl SemanticParser {
         }
         File {
             name: file.name,
-            module: file.module,
+            module: modules.to_vec(),
             imports: HashMap::new(),
+            item_imports: HashMap::new(),
+            sibling_types: HashMap::new(),
+            type_imports: HashMap::new(),
             globals,
             functions,
             types,
@@ -89,12 +340,14 @@ impl SemanticParser {
                 name: global.value.name,
                 mutable: global.value.mutable,
                 typ: Type::U8,
+                // Not a constant that can be copied, so it's never mistaken for one.
                 value: Expression {
-                    value: ExpressionValue::Literal(Literal::UInt(0)),
-                    typ: Type::U8,
+                    value: ExpressionValue::Literal(Literal::Tuple(Vec::new())),
+                    typ: Type::Unit,
                     span: global.value.value.span,
                 },
                 span: global.value.span,
+                poisoned: false,
             }),
         }
     }
@@ -114,7 +367,7 @@ impl SemanticParser {
                 name: function.value.name,
                 arguments,
                 return_type: None,
-                body: Vec::new(),
+                body: FunctionBody::Block(Vec::new()),
                 span: function.value.span,
             }),
         }
@@ -124,18 +377,26 @@ impl SemanticParser {
         let body = match &typ.value.body {
             syntax_ast::TypeDefBody::Struct(struct_) => {
                 let mut fields = HashMap::new();
-                for (name, _) in struct_.iter() {
+                for (name, _) in struct_.fields.iter() {
                     fields.insert(*name, Type::U8);
                 }
                 TypeDefBody::Struct(fields)
             }
-            syntax_ast::TypeDefBody::Enum(enum_) => {
-                let mut fields = HashMap::new();
-                for (name, _) in enum_.iter() {
-                    fields.insert(*name, 0);
-                }
-                TypeDefBody::Enum(fields)
-            }
+            syntax_ast::TypeDefBody::Enum(enum_) => TypeDefBody::Enum(Enum {
+                backing: Type::U64,
+                variants: enum_.variants.clone(),
+            }),
+            syntax_ast::TypeDefBody::TaggedEnum(tagged) => TypeDefBody::TaggedEnum(TaggedEnum {
+                tags: Enum {
+                    backing: Type::U64,
+                    variants: tagged.tags.variants.clone(),
+                },
+                payloads: tagged
+                    .payloads
+                    .keys()
+                    .map(|variant| (*variant, Vec::new()))
+                    .collect(),
+            }),
             syntax_ast::TypeDefBody::Union(union) => {
                 let mut fields = HashMap::new();
                 for (name, _) in union.iter() {
@@ -154,13 +415,145 @@ impl SemanticParser {
                 name: typ.value.name,
                 body,
                 size: 0,
+                align: 1,
+                packed: false,
+                derives: Vec::new(),
+                defaults: HashMap::new(),
                 span: typ.value.span,
             }),
         }
     }
 }
 
-fn resolve_module_deps(syn_module: &syntax_ast::Module, sem_ast: &mut Ast) -> Result<(), Error> {
+/// Calls f on every file of every module, along with its syntax counterpart.
+/// The module holding the file is read-locked during the call.
+fn for_each_file<F>(ast: &syntax_ast::Ast, sem_ast: &Ast, f: &mut F) -> Result<(), Error>
+where
+    F: FnMut(&syntax_ast::File, &File) -> Result<(), Error>,
+{
+    for (name, syn_module) in iter_by_id(&ast.modules) {
+        for_each_module_file(syn_module, &sem_ast.modules[&name], f)?;
+    }
+    Ok(())
+}
+
+fn for_each_module_file<F>(
+    syn_module: &syntax_ast::Module,
+    sem_module: &RwArc<Module>,
+    f: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&syntax_ast::File, &File) -> Result<(), Error>,
+{
+    let guard = sem_module.read().unwrap();
+    for (name, syn_file) in iter_by_id(&syn_module.files) {
+        f(syn_file, &guard.files[&name])?;
+    }
+    for (name, syn_submodule) in iter_by_id(&syn_module.submodules) {
+        for_each_module_file(syn_submodule, &guard.submodules[&name], f)?;
+    }
+    Ok(())
+}
+
+/// Binds the imports of every file in a top-level module and its submodules.
+/// Items may be imported from anywhere in the file's own module, so they're
+///     all resolved with the modules read-locked before any of them are stored.
+fn resolve_imports(
+    syn_module: &syntax_ast::Module,
+    sem_module: &RwArc<Module>,
+    sem_ast: &Ast,
+) -> Result<(), Error> {
+    let imports = {
+        let top = sem_module.read().unwrap();
+        collect_module_imports(syn_module, &top, &top, sem_ast)?
+    };
+    store_module_imports(sem_module, imports);
+    Ok(())
+}
+
+/// What the files of a module and its submodules import.
+#[derive(Default)]
+struct ModuleImports {
+    files: HashMap<SymbolId, FileImports>,
+    submodules: HashMap<SymbolId, ModuleImports>,
+}
+
+/// The top module is the top-level module the module is in, or the module itself.
+fn collect_module_imports(
+    syn_module: &syntax_ast::Module,
+    module: &Module,
+    top: &Module,
+    sem_ast: &Ast,
+) -> Result<ModuleImports, Error> {
+    resolve_module_deps(syn_module, sem_ast)?;
+    let mut imports = ModuleImports::default();
+    for (name, syn_file) in iter_by_id(&syn_module.files) {
+        let file_imports =
+            resolve_file_imports(syn_module, syn_file, &module.files[&name], top, sem_ast)?;
+        imports.files.insert(name, file_imports);
+    }
+    for (name, syn_submodule) in iter_by_id(&syn_module.submodules) {
+        let submodule = module.submodules[&name].read().unwrap();
+        let submodule_imports = collect_module_imports(syn_submodule, &submodule, top, sem_ast)?;
+        imports.submodules.insert(name, submodule_imports);
+    }
+    Ok(imports)
+}
+
+fn store_module_imports(sem_module: &RwArc<Module>, imports: ModuleImports) {
+    let mut guard = sem_module.write().unwrap();
+    let mut siblings: HashMap<_, _> = guard
+        .files
+        .keys()
+        .map(|name| (*name, sibling_types(&guard.files, *name)))
+        .collect();
+    for (name, file_imports) in imports.files {
+        let sem_file = guard.files.get_mut(&name).unwrap();
+        sem_file.sibling_types = siblings.remove(&name).unwrap();
+        sem_file.imports = file_imports.modules;
+        sem_file.item_imports = file_imports.items;
+        sem_file.type_imports = file_imports.types;
+    }
+    for (name, submodule_imports) in imports.submodules {
+        store_module_imports(&guard.submodules[&name], submodule_imports);
+    }
+}
+
+/// The types the file can use from the other files of its module.
+fn sibling_types(
+    files: &HashMap<SymbolId, File>,
+    file_name: SymbolId,
+) -> HashMap<SymbolId, RwArc<TypeDef>> {
+    let mut types = HashMap::new();
+    let mut ambiguous = HashSet::new();
+    for (_, file) in files.iter().filter(|(name, _)| **name != file_name) {
+        for (type_name, typ) in file.types.iter() {
+            if typ.visibility == Visibility::Private {
+                continue;
+            }
+            if types.insert(*type_name, typ.value.clone()).is_some() {
+                ambiguous.insert(*type_name);
+            }
+        }
+    }
+    types.retain(|name, _| !ambiguous.contains(name));
+    types
+}
+
+/// A type named without a path, defined in the file or one of its siblings,
+///     or imported by name.
+fn local_type(sem_file: &File, name: SymbolId) -> Option<RwArc<TypeDef>> {
+    match sem_file.types.get(&name) {
+        Some(typ) => Some(typ.value.clone()),
+        None => sem_file
+            .sibling_types
+            .get(&name)
+            .or_else(|| sem_file.type_imports.get(&name))
+            .cloned(),
+    }
+}
+
+fn resolve_module_deps(syn_module: &syntax_ast::Module, sem_ast: &Ast) -> Result<(), Error> {
     for dep in syn_module.dependencies.iter() {
         if !sem_ast.modules.contains_key(dep) {
             return Err(Error {
@@ -173,38 +566,159 @@ fn resolve_module_deps(syn_module: &syntax_ast::Module, sem_ast: &mut Ast) -> Re
     Ok(())
 }
 
+/// What a file imports.
+struct FileImports {
+    modules: HashMap<SymbolId, RwArc<Module>>,
+    items: HashMap<SymbolId, Identifier>,
+    types: HashMap<SymbolId, RwArc<TypeDef>>,
+}
+
+/// A global or a function, or a type, imported by name.
+enum ImportedItem {
+    Value(Identifier),
+    Type(RwArc<TypeDef>),
+}
+
 fn resolve_file_imports(
     syn_module: &syntax_ast::Module,
     syn_file: &syntax_ast::File,
-    sem_file: &mut File,
+    sem_file: &File,
+    top: &Module,
     sem_ast: &Ast,
-) -> Result<(), Error> {
-    for (import, span) in syn_file.imports.iter() {
-        if !syn_module.dependencies.contains(import) {
+) -> Result<FileImports, Error> {
+    let mut imports = FileImports {
+        modules: HashMap::new(),
+        items: HashMap::new(),
+        types: HashMap::new(),
+    };
+    for (import, span) in iter_by_id(&syn_file.imports) {
+        if !syn_module.dependencies.contains(&import) {
             return Err(Error {
                 typ: ErrorType::Import,
                 msg: "Importing undeclared module",
                 span: *span,
             });
         }
-        let imported = sem_ast.modules.get(import).unwrap();
-        sem_file.imports.insert(*import, (imported).clone());
+        let imported = sem_ast.modules.get(&import).unwrap();
+        imports.modules.insert(import, (imported).clone());
     }
-    Ok(())
+    for (_, import) in iter_by_id(&syn_file.item_imports) {
+        match resolve_item_import(syn_module, import, sem_file, top, sem_ast)? {
+            ImportedItem::Value(value) => {
+                imports.items.insert(import.item, value);
+            }
+            ImportedItem::Type(typ) => {
+                imports.types.insert(import.item, typ);
+            }
+        }
+    }
+    Ok(imports)
+}
+
+/// Items can be imported from dependencies, or from the top-level module
+///     the file is in, which is already locked.
+fn resolve_item_import(
+    syn_module: &syntax_ast::Module,
+    import: &syntax_ast::ItemImport,
+    sem_file: &File,
+    top: &Module,
+    sem_ast: &Ast,
+) -> Result<ImportedItem, Error> {
+    let error = |msg| Error {
+        typ: ErrorType::Import,
+        msg,
+        span: import.span,
+    };
+    let module_name = import.path[0];
+    let own = module_name == sem_file.module[0];
+    if !own && !syn_module.dependencies.contains(&module_name) {
+        return Err(error("Importing undeclared module"));
+    }
+    let name = import.item;
+    if sem_file.globals.contains_key(&name)
+        || sem_file.functions.contains_key(&name)
+        || sem_file.types.contains_key(&name)
+    {
+        return Err(error("Imported item collides with a local name"));
+    }
+    let (path, found) = if own {
+        find_import(top, import)
+    } else {
+        find_import(&sem_ast.modules[&module_name].read().unwrap(), import)
+    }
+    .map_err(error)?;
+    if !is_visible(found.visibility, &path, &sem_file.module) {
+        return Err(error("Imported item isn't visible"));
+    }
+    Ok(found.value)
+}
+
+/// Finds an imported item in the module its path starts with, along with
+///     the path of the file defining it. `module::item` names an item
+///     defined in one of the module's files.
+fn find_import(
+    module: &Module,
+    import: &syntax_ast::ItemImport,
+) -> Result<(syntax_ast::Name, Scope<ImportedItem>), &'static str> {
+    if import.path.len() > 1 {
+        return with_file_in(module, &import.path[1..], |file| {
+            lookup_item(file, import.item)
+        })
+        .map(|found| (import.path.clone(), found))
+        .ok_or("Imported item doesn't exist");
+    }
+    let mut found = module
+        .files
+        .iter()
+        .filter_map(|(file_name, file)| Some((*file_name, lookup_item(file, import.item)?)));
+    match (found.next(), found.next()) {
+        (Some((file_name, item)), None) => Ok((vec![import.path[0], file_name], item)),
+        (Some(_), Some(_)) => Err("Imported item is defined in several files"),
+        (None, _) => Err("Imported item doesn't exist"),
+    }
+}
+
+/// Looks a name up among the globals, functions and types of a file.
+fn lookup_item(file: &File, name: SymbolId) -> Option<Scope<ImportedItem>> {
+    if let Some(found) = expression::lookup_file(file, name) {
+        return Some(Scope {
+            visibility: found.visibility,
+            value: ImportedItem::Value(found.value),
+        });
+    }
+    file.types.get(&name).map(|typ| Scope {
+        visibility: typ.visibility,
+        value: ImportedItem::Type(typ.value.clone()),
+    })
 }
 
-fn resolve_func_sig(sem_file: &File, sig: &syntax_ast::FunctionSig) -> Result<Type, Error> {
+fn resolve_func_sig(
+    sem_file: &File,
+    pool: &InternPool,
+    sig: &syntax_ast::FunctionSig,
+) -> Result<Type, Error> {
     let mut args = Vec::new();
     for arg in sig.args.iter() {
-        args.push(resolve_type_annot(sem_file, arg)?);
+        args.push(resolve_type_annot(sem_file, pool, arg)?);
     }
-    let ret = match &sig.ret {
-        Some(ret) => Some(Box::new(resolve_type_annot(sem_file, &ret)?)),
-        None => None,
-    };
+    let ret = resolve_return_type(sem_file, pool, sig.ret.as_deref())?.map(Box::new);
     Ok(Type::Function(FunctionType { args, ret }))
 }
 
+/// Returning `void` is the same as having no return type,
+///     so both kinds of signatures are the same type.
+fn resolve_return_type(
+    sem_file: &File,
+    pool: &InternPool,
+    ret: Option<&syntax_ast::TypeAnnot>,
+) -> Result<Option<Type>, Error> {
+    let Some(ret) = ret else {
+        return Ok(None);
+    };
+    let typ = resolve_type_annot(sem_file, pool, ret)?;
+    Ok(if typ == Type::Unit { None } else { Some(typ) })
+}
+
 fn keyword_to_primitive(kwd: TokenType) -> Option<Type> {
     // I'm really sinful for designing such a bad thing.
     // Repent!
@@ -223,6 +737,7 @@ fn keyword_to_primitive(kwd: TokenType) -> Option<Type> {
         TokenType::F32 => Type::F32,
         TokenType::F64 => Type::F64,
         TokenType::Bool => Type::Bool,
+        TokenType::Void => Type::Unit,
         _ => return None,
     })
 }
@@ -231,10 +746,7 @@ fn resolve_immediate_type(sem_file: &File, type_name: SymbolId, span: Span) -> R
     let ret = if intern_pool::is_keyword(&type_name) {
         keyword_to_primitive(intern_pool::get_keyword(&type_name))
     } else {
-        match sem_file.types.get(&type_name) {
-            Some(typ) => Some(Type::Custom(typ.value.clone())),
-            None => None,
-        }
+        local_type(sem_file, type_name).map(Type::Custom)
     };
     match ret {
         Some(typ) => Ok(typ),
@@ -246,75 +758,2687 @@ fn resolve_immediate_type(sem_file: &File, type_name: SymbolId, span: Span) -> R
     }
 }
 
-fn resolve_type_annot(sem_file: &File, type_annot: &syntax_ast::TypeAnnot) -> Result<Type, Error> {
-    todo!("also search the submodules!");
-    todo!("scopes matters!");
+/// Walks `module::submodule::...::file` starting from the imports of the file
+///     and calls f on the file found. The modules on the way are read-locked.
+fn with_imported_file<T>(
+    sem_file: &File,
+    path: &[SymbolId],
+    f: impl FnOnce(&File) -> Option<T>,
+) -> Option<T> {
+    let (module_name, rest) = path.split_first()?;
+    with_module_file(sem_file.imports.get(module_name)?.clone(), rest, f)
+}
+
+/// Walks `submodule::...::file` starting from the module.
+fn with_module_file<T>(
+    module: RwArc<Module>,
+    path: &[SymbolId],
+    f: impl FnOnce(&File) -> Option<T>,
+) -> Option<T> {
+    with_file_in(&module.read().unwrap(), path, f)
+}
+
+/// Like `with_module_file`, for a module that's already locked.
+fn with_file_in<T>(
+    module: &Module,
+    path: &[SymbolId],
+    f: impl FnOnce(&File) -> Option<T>,
+) -> Option<T> {
+    match path {
+        [file_name] => f(module.files.get(file_name)?),
+        [submodule_name, rest @ ..] => {
+            with_module_file(module.submodules.get(submodule_name)?.clone(), rest, f)
+        }
+        [] => None,
+    }
+}
+
+/// Whether an item of the given visibility, defined in the file at
+///     `module::submodule::...::file`, can be used from a file of `module`.
+/// Modules are told apart by their whole chain, outermost first, since
+///     submodules of different modules can share a name. Items reached
+///     through a path are never in the same file, so private ones are never visible.
+fn is_visible(visibility: Visibility, path: &[SymbolId], module: &[SymbolId]) -> bool {
+    let modules = &path[..path.len() - 1];
+    let owner = modules == module;
+    match visibility {
+        Visibility::Public => true,
+        Visibility::Private => false,
+        Visibility::Module => owner,
+        Visibility::Super => {
+            owner
+                || modules
+                    .split_last()
+                    .is_some_and(|(_, parent)| parent == module)
+        }
+    }
+}
+
+/// Modifiers are written outermost-first, so `[]var []let u8` is a mutable
+///     slice of immutable slices of u8. A modifier's mutability applies to
+///     what it points to, which is why they're wrapped around the base in reverse.
+fn resolve_type_annot(
+    sem_file: &File,
+    pool: &InternPool,
+    type_annot: &syntax_ast::TypeAnnot,
+) -> Result<Type, Error> {
+    let mut typ = resolve_type_base(sem_file, pool, type_annot)?;
+    for modifier in type_annot.modifiers.iter().rev() {
+        let inner = Box::new(typ);
+        let mutable = modifier.mutable;
+        typ = match &modifier.typ {
+            syntax_ast::TypeModifierType::Pointer => Type::Pointer { inner, mutable },
+            syntax_ast::TypeModifierType::Slice => Type::Slice { inner, mutable },
+            syntax_ast::TypeModifierType::Array(size) => Type::Array {
+                inner,
+                size: resolve_array_size(sem_file, pool, size)?,
+                mutable,
+            },
+        };
+    }
+    Ok(typ)
+}
+
+/// Array sizes are constant expressions. They may refer to immutable
+///     globals whose values are constant, which are inlined and folded.
+/// No type is expected, since the globals referred to may not have
+///     their types resolved yet. Any non-negative integer will do.
+fn resolve_array_size(
+    sem_file: &File,
+    pool: &InternPool,
+    size: &syntax_ast::Expression,
+) -> Result<u64, Error> {
+    Resolver::new(sem_file, pool, Type::Unit).resolve_size(size)
+}
+
+impl Resolver<'_> {
+    /// Resolves an array size, which must fold to a non-negative integer.
+    fn resolve_size(&mut self, size: &syntax_ast::Expression) -> Result<u64, Error> {
+        let inline_constants = std::mem::replace(&mut self.inline_constants, true);
+        let value = self
+            .resolve_expression(size, None)
+            .and_then(|value| fold_constants(value, self.pool));
+        self.inline_constants = inline_constants;
+        match value?.value {
+            ExpressionValue::Literal(Literal::UInt(uint)) => Ok(uint),
+            ExpressionValue::Literal(Literal::Int(int)) if int >= 0 => Ok(int as u64),
+            ExpressionValue::Literal(Literal::Int(_)) => Err(Error {
+                typ: ErrorType::Type,
+                msg: "Array size can't be negative",
+                span: size.span,
+            }),
+            _ => Err(Error {
+                typ: ErrorType::Type,
+                msg: "Array size isn't a compile-time constant",
+                span: size.span,
+            }),
+        }
+    }
+}
+
+fn resolve_type_base(
+    sem_file: &File,
+    pool: &InternPool,
+    type_annot: &syntax_ast::TypeAnnot,
+) -> Result<Type, Error> {
     let name = match &type_annot.base {
         syntax_ast::TypeAnnotBase::Normal(name) => name,
-        syntax_ast::TypeAnnotBase::Function(sig) => return resolve_func_sig(sem_file, sig),
+        syntax_ast::TypeAnnotBase::Function(sig) => return resolve_func_sig(sem_file, pool, sig),
+        syntax_ast::TypeAnnotBase::Tuple(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| resolve_type_annot(sem_file, pool, element))
+                .collect::<Result<_, _>>()?;
+            return Ok(Type::Tuple(elements));
+        }
     };
-    let ret = 'block: {
-        if name.len() == 2 {
-            break 'block None;
-        }
-        if name.len() == 1 {
-            break 'block Some(resolve_immediate_type(sem_file, name[0], type_annot.span)?);
-        }
-        let mut module = match sem_file.imports.get(&name[0]) {
-            Some(module) => module.clone(),
-            None => break 'block None,
-        };
-        for module_name in &name[1..name.len() - 1] {
-            // This trick makes sure the module is not being borrowed and reassigned at the same time.
-            let tmp_module = module.clone();
-            let guard = tmp_module.read().unwrap();
-            module = match guard.submodules.get(module_name) {
-                Some(module) => module.clone(),
-                None => break 'block None,
-            };
+    if name.len() == 1 {
+        return resolve_immediate_type(sem_file, name[0], type_annot.span);
+    }
+    let (type_name, path) = name.split_last().unwrap();
+    let ret = with_imported_file(sem_file, path, |file| {
+        file.types
+            .get(type_name)
+            .filter(|typ| is_visible(typ.visibility, path, &sem_file.module))
+            .map(|typ| Type::Custom(typ.value.clone()))
+    });
+    ret.ok_or(Error {
+        typ: ErrorType::Type,
+        msg: "Can't resolve type name",
+        span: type_annot.span,
+    })
+}
+
+/// Constants may refer to each other, so this repeats until no more are found.
+fn resolve_constants(
+    ast: &syntax_ast::Ast,
+    sem_ast: &Ast,
+    pool: &InternPool,
+    filter: fn(&Type) -> bool,
+) -> Result<(), Error> {
+    let mut found = true;
+    while found {
+        found = false;
+        for_each_file(ast, sem_ast, &mut |syn_file, sem_file| {
+            found |= resolve_file_constants(syn_file, sem_file, pool, filter);
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Evaluates the immutable globals of the types the filter accepts whose
+///     values fold to constants that can be copied.
+/// Values referring to globals that aren't evaluated yet don't fold,
+///     and errors are left for the bodies phase to report.
+/// Returns whether any new constant was found.
+fn resolve_file_constants(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+    filter: fn(&Type) -> bool,
+) -> bool {
+    let mut found = false;
+    for (name, syn_global) in syn_file.globals.iter() {
+        let sem_global = &sem_file.globals[name].value;
+        if syn_global.value.mutable || copy_constant(&sem_global.read().unwrap().value).is_some() {
+            continue;
         }
-        let guard = module.read().unwrap();
-        let file = match guard.files.get(&name[name.len() - 2]) {
-            Some(file) => file,
-            None => break 'block None,
+        let Ok(typ) = resolve_type_annot(sem_file, pool, &syn_global.value.typ) else {
+            continue;
         };
-        match file.types.get(&name[name.len() - 1]) {
-            Some(typ) => Some(Type::Custom(typ.value.clone())),
-            None => None,
+        if !filter(&typ) {
+            continue;
         }
-    };
-    match ret {
-        Some(typ) => Ok(typ),
-        None => Err(Error {
-            typ: ErrorType::Type,
-            msg: "Can't resolve type name",
-            span: type_annot.span,
-        }),
+        let mut resolver = Resolver::new(sem_file, pool, Type::Unit);
+        resolver.inline_constants = true;
+        let value = resolver.resolve_typed_expression(&syn_global.value.value, &typ);
+        let Ok(value) = value.and_then(|value| fold_constants(value, pool)) else {
+            continue;
+        };
+        if copy_constant(&value).is_some() {
+            let mut guard = sem_global.write().unwrap();
+            guard.typ = typ;
+            guard.value = value;
+            found = true;
+        }
+    }
+    found
+}
+
+fn resolve_file_types(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+) -> Result<(), Error> {
+    for (name, syn_typ) in iter_by_id(&syn_file.types) {
+        resolve_type(sem_file, &syn_typ.value, &sem_file.types[&name].value, pool)?;
     }
+    Ok(())
 }
 
-fn resolve_file_types(syn_file: &syntax_ast::File, sem_file: &mut File) -> Result<(), Error> {}
+fn resolve_fields(
+    sem_file: &File,
+    pool: &InternPool,
+    fields: &HashMap<SymbolId, syntax_ast::TypeAnnot>,
+) -> Result<HashMap<SymbolId, Type>, Error> {
+    let mut ret = HashMap::with_capacity(fields.len());
+    for (name, type_annot) in fields.iter() {
+        ret.insert(*name, resolve_type_annot(sem_file, pool, type_annot)?);
+    }
+    Ok(ret)
+}
 
 fn resolve_type(
     sem_file: &File,
-    syn_typ: syntax_ast::TypeDef,
-    sem_typ: RwArc<TypeDef>,
-    type_status: HashMap<TypeId, TypeColor>,
+    syn_typ: &syntax_ast::TypeDef,
+    sem_typ: &RwArc<TypeDef>,
+    pool: &InternPool,
 ) -> Result<(), Error> {
-    let mut guard = sem_typ.write().unwrap();
-    match syn_typ.body {
-        syntax_ast::TypeDefBody::Enum(_) => {
-            guard.size = 8; // size_of(u64) is very meaningless
+    let (packed, derives) = resolve_type_attributes(syn_typ, pool)?;
+    let body = match &syn_typ.body {
+        syntax_ast::TypeDefBody::Enum(enum_) => {
+            TypeDefBody::Enum(resolve_enum(sem_file, pool, enum_, syn_typ.span)?)
+        }
+        syntax_ast::TypeDefBody::TaggedEnum(tagged) => {
+            let mut payloads = HashMap::with_capacity(tagged.payloads.len());
+            for (variant, payload) in tagged.payloads.iter() {
+                let payload = payload
+                    .iter()
+                    .map(|element| resolve_type_annot(sem_file, pool, element))
+                    .collect::<Result<_, _>>()?;
+                payloads.insert(*variant, payload);
+            }
+            TypeDefBody::TaggedEnum(TaggedEnum {
+                tags: resolve_enum(sem_file, pool, &tagged.tags, syn_typ.span)?,
+                payloads,
+            })
         }
         syntax_ast::TypeDefBody::Alias(alias) => {
-            guard.body = TypeDefBody::Alias(resolve_type_annot(sem_file, &alias)?);
+            TypeDefBody::Alias(resolve_type_annot(sem_file, pool, alias)?)
+        }
+        syntax_ast::TypeDefBody::Struct(struct_) => {
+            TypeDefBody::Struct(resolve_fields(sem_file, pool, &struct_.fields)?)
+        }
+        syntax_ast::TypeDefBody::Union(fields) => {
+            TypeDefBody::Union(resolve_fields(sem_file, pool, fields)?)
+        }
+    };
+    let mut guard = sem_typ.write().unwrap();
+    guard.body = body;
+    guard.packed = packed;
+    guard.derives = derives;
+    Ok(())
+}
+
+/// The backing type must be an integer type that can hold every value.
+fn resolve_enum(
+    sem_file: &File,
+    pool: &InternPool,
+    enum_: &syntax_ast::Enum,
+    span: Span,
+) -> Result<Enum, Error> {
+    let backing = match &enum_.backing {
+        Some(backing) => {
+            let typ = resolve_type_annot(sem_file, pool, backing)?;
+            if !typ.is_integer() {
+                return Err(Error {
+                    typ: ErrorType::Type,
+                    msg: "An enum must be backed by an integer type",
+                    span: backing.span,
+                });
+            }
+            typ
+        }
+        None => Type::U64,
+    };
+    let (min, max) = backing.integer_bounds().unwrap();
+    // Enums are unsigned by default, so a negative value is likely missing its backing type.
+    if min == 0 && enum_.variants.values().any(|value| *value < 0) {
+        return Err(Error {
+            typ: ErrorType::Type,
+            msg: "Enum value can't be negative unless the backing type is signed",
+            span,
+        });
+    }
+    if enum_
+        .variants
+        .values()
+        .any(|value| *value < min || *value > max)
+    {
+        return Err(Error {
+            typ: ErrorType::Type,
+            msg: "Enum value out of range of the backing type",
+            span,
+        });
+    }
+    Ok(Enum {
+        backing,
+        variants: enum_.variants.clone(),
+    })
+}
+
+/// Returns whether the type is `@packed`, and what it `@derive`s.
+fn resolve_type_attributes(
+    syn_typ: &syntax_ast::TypeDef,
+    pool: &InternPool,
+) -> Result<(bool, Vec<Derive>), Error> {
+    let mut packed = false;
+    let mut derives = Vec::new();
+    for attribute in syn_typ.attributes.iter() {
+        if Some(attribute.name) == pool.search_symbol("derive") {
+            resolve_derives(syn_typ, attribute, pool, &mut derives)?;
+            continue;
+        }
+        if Some(attribute.name) != pool.search_symbol("packed") {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Unknown attribute",
+                span: attribute.span,
+            });
+        }
+        if let Some((_, span)) = attribute.args.first() {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "`@packed` takes no arguments",
+                span: *span,
+            });
         }
-        syntax_ast::TypeDefBody::Struct(fields) => {
-            for (field_name, type_annot) in fields.iter() {
-                let typ = sem_file.types.get(field_name).unwrap();
+        if !matches!(
+            syn_typ.body,
+            syntax_ast::TypeDefBody::Struct(_) | syntax_ast::TypeDefBody::Union(_)
+        ) {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Only structs and unions can be packed",
+                span: attribute.span,
+            });
+        }
+        packed = true;
+    }
+    Ok((packed, derives))
+}
+
+/// Only structs and enums derive. An alias is the type it stands for,
+///     and which field of a union is in use isn't known.
+fn resolve_derives(
+    syn_typ: &syntax_ast::TypeDef,
+    attribute: &syntax_ast::Attribute,
+    pool: &InternPool,
+    derives: &mut Vec<Derive>,
+) -> Result<(), Error> {
+    if matches!(
+        syn_typ.body,
+        syntax_ast::TypeDefBody::Alias(_) | syntax_ast::TypeDefBody::Union(_)
+    ) {
+        return Err(Error {
+            typ: ErrorType::Type,
+            msg: "Only structs and enums can derive",
+            span: attribute.span,
+        });
+    }
+    if attribute.args.is_empty() {
+        return Err(Error {
+            typ: ErrorType::Type,
+            msg: "Expected what to derive",
+            span: attribute.span,
+        });
+    }
+    for &(name, span) in attribute.args.iter() {
+        let derive = if Some(name) == pool.search_symbol("Eq") {
+            Derive::Eq
+        } else if Some(name) == pool.search_symbol("Print") {
+            Derive::Print
+        } else {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Unknown derive",
+                span,
+            });
+        };
+        if derives.contains(&derive) {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Derived twice",
+                span,
+            });
+        }
+        derives.push(derive);
+    }
+    Ok(())
+}
+
+/// Every field of a type that derives, and every element of its variants'
+///     payloads, must have what it derives. The error is at the type's name.
+/// This runs after layouts, which reject types that contain themselves,
+///     so a field's type is never the one being checked.
+fn check_file_derives(sem_file: &File) -> Result<(), Error> {
+    for (_, typ) in iter_by_id(&sem_file.types) {
+        let typ = typ.value.read().unwrap();
+        let fields: Vec<&Type> = match &typ.body {
+            TypeDefBody::Struct(fields) => fields.values().collect(),
+            TypeDefBody::TaggedEnum(tagged) => tagged.payloads.values().flatten().collect(),
+            _ => Vec::new(),
+        };
+        for &derive in typ.derives.iter() {
+            if fields.iter().all(|field| has_derive(field, derive)) {
+                continue;
             }
+            let msg = match derive {
+                Derive::Eq => "A field can't be compared for equality",
+                Derive::Print => "A field can't be printed",
+            };
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg,
+                span: typ.span,
+            });
         }
-        syntax_ast::TypeDefBody::Union(fields) => {}
     }
     Ok(())
 }
+
+/// Whether values of the type have the capability, built in or derived.
+fn has_derive(typ: &Type, derive: Derive) -> bool {
+    match derive {
+        Derive::Eq => expression::has_equality(typ),
+        Derive::Print => is_printable(typ),
+    }
+}
+
+/// Numbers, bools, enums without payloads, types deriving `Print`, and
+///     arrays, slices and tuples of them, which includes strings.
+fn is_printable(typ: &Type) -> bool {
+    match typ.resolve_alias() {
+        Type::Bool => true,
+        Type::Array { inner, .. } | Type::Slice { inner, .. } => is_printable(&inner),
+        Type::Tuple(elements) => elements.iter().all(is_printable),
+        Type::Custom(def) => {
+            let def = def.read().unwrap();
+            matches!(def.body, TypeDefBody::Enum(_)) || def.derives.contains(&Derive::Print)
+        }
+        typ => typ.is_numeric(),
+    }
+}
+
+fn resolve_file_signatures(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+) -> Result<(), Error> {
+    for (name, syn_global) in iter_by_id(&syn_file.globals) {
+        let typ = resolve_type_annot(sem_file, pool, &syn_global.value.typ)?;
+        sem_file.globals[&name].value.write().unwrap().typ = typ;
+    }
+    for (name, syn_function) in iter_by_id(&syn_file.functions) {
+        let sem_function = &sem_file.functions[&name].value;
+        let guard = sem_function.read().unwrap();
+        let args = syn_function.value.arguments.iter();
+        for (syn_arg, sem_arg) in args.zip(guard.arguments.iter()) {
+            sem_arg.write().unwrap().typ = resolve_type_annot(sem_file, pool, &syn_arg.typ)?;
+        }
+        drop(guard);
+        let return_type =
+            resolve_return_type(sem_file, pool, syn_function.value.return_type.as_ref())?;
+        sem_function.write().unwrap().return_type = return_type;
+    }
+    Ok(())
+}
+
+/// Resolves the defaults of the structs whose defaults aren't resolved yet.
+/// Unless strict, the structs failing to resolve are left for later.
+fn resolve_file_defaults(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+    strict: bool,
+) -> Result<(), Error> {
+    for (name, syn_typ) in iter_by_id(&syn_file.types) {
+        let syntax_ast::TypeDefBody::Struct(struct_) = &syn_typ.value.body else {
+            continue;
+        };
+        let sem_typ = &sem_file.types[&name].value;
+        if sem_typ.read().unwrap().defaults.len() == struct_.defaults.len() {
+            continue;
+        }
+        let resolved = resolve_field_defaults(sem_file, pool, struct_, sem_typ);
+        if strict {
+            resolved?;
+        }
+    }
+    Ok(())
+}
+
+/// Reports and poisons the immutable globals that could be constants but
+///     fail to resolve, so that what inlines them doesn't report errors of its own.
+/// A global inlining a poisoned one is poisoned in turn, which may take
+///     another call to find. Returns whether any new global was poisoned.
+fn poison_file_globals(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+    diagnostics: &mut Diagnostics,
+) -> bool {
+    let mut found = false;
+    for (name, syn_global) in syn_file.globals.iter() {
+        let sem_global = &sem_file.globals[name].value;
+        let (typ, skip) = {
+            let guard = sem_global.read().unwrap();
+            let skip = guard.mutable
+                || guard.poisoned
+                || !can_be_constant(&guard.typ)
+                || copy_constant(&guard.value).is_some();
+            (guard.typ.clone(), skip)
+        };
+        if skip {
+            continue;
+        }
+        let mut resolver = Resolver::new(sem_file, pool, Type::Unit);
+        resolver.inline_constants = true;
+        let value = resolver.resolve_typed_expression(&syn_global.value.value, &typ);
+        if let Err(err) = value.and_then(|value| fold_constants(value, pool)) {
+            diagnostics.report(err);
+            sem_global.write().unwrap().poisoned = true;
+            found = true;
+        }
+    }
+    found
+}
+
+/// Field defaults are filled into struct literals as they are,
+///     so they must be literals or enum variants after folding.
+fn resolve_field_defaults(
+    sem_file: &File,
+    pool: &InternPool,
+    struct_: &syntax_ast::Struct,
+    sem_typ: &RwArc<TypeDef>,
+) -> Result<(), Error> {
+    let mut defaults = HashMap::with_capacity(struct_.defaults.len());
+    for (name, default) in struct_.defaults.iter() {
+        let TypeDefBody::Struct(fields) = &sem_typ.read().unwrap().body else {
+            unreachable!("Struct definitions resolve to struct bodies");
+        };
+        let mut resolver = Resolver::new(sem_file, pool, Type::Unit);
+        resolver.inline_constants = true;
+        let value = resolver.resolve_typed_expression(default, &fields[name])?;
+        let value = fold_constants(value, pool)?;
+        if copy_constant(&value).is_none() {
+            return Err(Error {
+                typ: ErrorType::Type,
+                msg: "Field default isn't a compile-time constant",
+                span: default.span,
+            });
+        }
+        defaults.insert(*name, value);
+    }
+    sem_typ.write().unwrap().defaults = defaults;
+    Ok(())
+}
+
+/// Whether values of the type can be constants that `copy_constant` copies.
+fn can_be_constant(typ: &Type) -> bool {
+    match typ.resolve_alias() {
+        Type::Bool | Type::Slice { .. } => true,
+        Type::Custom(def) => matches!(def.read().unwrap().body, TypeDefBody::Enum(_)),
+        typ => typ.is_numeric(),
+    }
+}
+
+/// Copies a literal of a primitive type or an enum variant.
+/// None for anything else, which isn't a constant that can be copied.
+fn copy_constant(exp: &Expression) -> Option<Expression> {
+    let value = match &exp.value {
+        ExpressionValue::Literal(literal) => ExpressionValue::Literal(match literal {
+            Literal::String(string) => Literal::String(*string),
+            Literal::UInt(uint) => Literal::UInt(*uint),
+            Literal::Int(int) => Literal::Int(*int),
+            Literal::Float(float) => Literal::Float(*float),
+            Literal::Bool(bool) => Literal::Bool(*bool),
+            _ => return None,
+        }),
+        ExpressionValue::Identifier(identifier @ Identifier::EnumVariant(..)) => {
+            ExpressionValue::Identifier(identifier.clone())
+        }
+        _ => return None,
+    };
+    Some(Expression {
+        value,
+        typ: exp.typ.clone(),
+        span: exp.span,
+    })
+}
+
+fn resolve_file_bodies(
+    syn_file: &syntax_ast::File,
+    sem_file: &File,
+    pool: &InternPool,
+    diagnostics: &mut Diagnostics,
+) {
+    for (name, syn_global) in syn_file.globals.iter() {
+        let sem_global = &sem_file.globals[name].value;
+        if sem_global.read().unwrap().poisoned {
+            continue;
+        }
+        let typ = sem_global.read().unwrap().typ.clone();
+        let mut resolver = Resolver::new(sem_file, pool, Type::Unit);
+        // Keeps the values of constants the same as when they were evaluated.
+        resolver.inline_constants = true;
+        let value = resolver.resolve_typed_expression(&syn_global.value.value, &typ);
+        match value.and_then(|value| fold_constants(value, pool)) {
+            Ok(value) => sem_global.write().unwrap().value = value,
+            Err(err) => diagnostics.report(err),
+        }
+        diagnostics.warnings.append(&mut resolver.warnings);
+    }
+    for (name, syn_function) in syn_file.functions.iter() {
+        let sem_function = &sem_file.functions[name].value;
+        match Resolver::resolve_function(
+            sem_file,
+            pool,
+            &syn_function.value,
+            sem_function,
+            &mut diagnostics.warnings,
+        ) {
+            Ok(body) => sem_function.write().unwrap().body = body,
+            Err(err) => diagnostics.report(err),
+        }
+    }
+}
+
+impl<'a> Resolver<'a> {
+    fn new(file: &'a File, pool: &'a InternPool, return_type: Type) -> Self {
+        Self {
+            file,
+            pool,
+            scopes: Vec::new(),
+            return_type,
+            inline_constants: false,
+            warnings: Vec::new(),
+            loops: Vec::new(),
+        }
+    }
+
+    /// Resolves the body of a function whose signature is already resolved.
+    fn resolve_function(
+        file: &'a File,
+        pool: &'a InternPool,
+        syn_function: &syntax_ast::Function,
+        sem_function: &RwArc<Function>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<FunctionBody, Error> {
+        let guard = sem_function.read().unwrap();
+        let return_type = guard.return_type.clone().unwrap_or(Type::Unit);
+        let mut resolver = Self::new(file, pool, return_type);
+        let mut arguments = HashMap::new();
+        for arg in guard.arguments.iter() {
+            let (name, span) = {
+                let arg = arg.read().unwrap();
+                (arg.name, arg.span)
+            };
+            if arguments
+                .insert(name, Identifier::Argument(arg.clone()))
+                .is_some()
+            {
+                return Err(Error {
+                    typ: ErrorType::Statement,
+                    msg: "Duplicated argument name",
+                    span,
+                });
+            }
+        }
+        drop(guard);
+        resolver.scopes.push(arguments);
+        let body = match &syn_function.body {
+            syntax_ast::FunctionBody::Block(block) => {
+                let mut block = resolver.resolve_block(block)?;
+                fold_block(&mut block, pool)?;
+                FunctionBody::Block(block)
+            }
+            syntax_ast::FunctionBody::Asm(asm) => {
+                check_asm_placeholders(asm, syn_function.span)?;
+                FunctionBody::Asm(Asm {
+                    code: asm.code.clone(),
+                    operands: asm
+                        .operands
+                        .iter()
+                        .map(|operand| resolver.resolve_expression(operand, None))
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+        };
+        warnings.append(&mut resolver.warnings);
+        Ok(body)
+    }
+}
+
+/// Every `{N}` in the code must refer to one of the operands.
+/// Braces around anything other than digits are left alone.
+fn check_asm_placeholders(asm: &syntax_ast::Asm, span: Span) -> Result<(), Error> {
+    for (start, _) in asm.code.match_indices('{') {
+        let rest = &asm.code[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let digits = &rest[..end];
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            continue;
+        }
+        if !digits
+            .parse()
+            .is_ok_and(|index: usize| index < asm.operands.len())
+        {
+            return Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Asm placeholder has no operand",
+                span,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic_ast::Binary;
+    use crate::syntactic_parser::SyntacticParser;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use syntax_ast::BinaryOp;
+
+    /// Analyzes a single file in a module, both named "test".
+    /// The module declaration is added in front of the code.
+    fn analyze(code: &str) -> (Result<Ast, Error>, InternPool) {
+        let (ast, pool) = syntax(code);
+        (SemanticParser::parse(&ast, &pool), pool)
+    }
+
+    /// Like `analyze`, but returns the diagnostics instead.
+    fn diagnose(code: &str) -> (Diagnostics, InternPool) {
+        let (ast, pool) = syntax(code);
+        (SemanticParser::analyze(&ast, &pool).1, pool)
+    }
+
+    /// Parses the test file into a syntax AST.
+    fn syntax(code: &str) -> (syntax_ast::Ast, InternPool) {
+        let code = format!("module test;\n{code}");
+        let mut pool = InternPool::new();
+        let path = pool.insert_path(PathBuf::from("test.code"));
+        let name = pool.insert_symbol("test".to_string());
+        let file = SyntacticParser::parse_code(path, &code, name, name, &mut pool).unwrap();
+        let module = syntax_ast::Module {
+            name,
+            files: HashMap::from([(name, file)]),
+            submodules: HashMap::new(),
+            dependencies: HashSet::new(),
+            path,
+        };
+        let ast = syntax_ast::Ast {
+            entry: name,
+            modules: HashMap::from([(name, module)]),
+        };
+        (ast, pool)
+    }
+
+    /// Analyzes modules with a single file named "lib" each, given as the
+    ///     module name and its code. The first one is the entry,
+    ///     and every module depends on all the others.
+    fn analyze_modules(modules: &[(&str, &str)]) -> (Result<Ast, Error>, InternPool) {
+        let mut pool = InternPool::new();
+        let lib = pool.insert_symbol("lib".to_string());
+        let names: Vec<_> = modules
+            .iter()
+            .map(|(name, _)| pool.insert_symbol(name.to_string()))
+            .collect();
+        let mut syn_modules = HashMap::new();
+        for (&name, (module_name, code)) in names.iter().zip(modules) {
+            let code = format!("module {module_name};\n{code}");
+            let path = pool.insert_path(PathBuf::from(format!("{module_name}/lib.code")));
+            let file = SyntacticParser::parse_code(path, &code, lib, name, &mut pool).unwrap();
+            let module = syntax_ast::Module {
+                name,
+                files: HashMap::from([(lib, file)]),
+                submodules: HashMap::new(),
+                dependencies: names.iter().copied().filter(|&dep| dep != name).collect(),
+                path,
+            };
+            syn_modules.insert(name, module);
+        }
+        let ast = syntax_ast::Ast {
+            entry: names[0],
+            modules: syn_modules,
+        };
+        (SemanticParser::parse(&ast, &pool), pool)
+    }
+
+    /// The value of a global declared in the test file.
+    fn global_value<T>(
+        ast: &Ast,
+        pool: &InternPool,
+        name: &str,
+        f: impl FnOnce(&Expression) -> T,
+    ) -> T {
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let global = &module.files[&test].globals[&pool.search_symbol(name).unwrap()];
+        f(&global.value.read().unwrap().value)
+    }
+
+    #[test]
+    fn direct_call() {
+        let (ast, pool) = analyze(
+            "prv fn add(a: i32, b: i32) -> i32 { return a + b; }
+             prv let sum: i32 = add(1, 2);",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "sum", |value| {
+            assert_eq!(value.typ, Type::I32);
+            let ExpressionValue::Call(call) = &value.value else {
+                panic!("expected a call");
+            };
+            assert!(matches!(
+                call.function.value,
+                ExpressionValue::Identifier(Identifier::Function(_))
+            ));
+            assert_eq!(call.args[0].typ, Type::I32);
+        });
+    }
+
+    #[test]
+    fn call_through_function_pointer() {
+        let (ast, pool) = analyze(
+            "prv fn add(a: i32, b: i32) -> i32 { return a + b; }
+             prv let op: fn(i32, i32) -> i32 = add;
+             prv let sum: i32 = op(1, 2);",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "sum", |value| {
+            assert_eq!(value.typ, Type::I32);
+            let ExpressionValue::Call(call) = &value.value else {
+                panic!("expected a call");
+            };
+            assert!(matches!(
+                call.function.value,
+                ExpressionValue::Identifier(Identifier::Declaraction(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn call_arity_mismatch() {
+        let (ast, _) = analyze(
+            "prv fn add(a: i32, b: i32) -> i32 { return a + b; }
+             prv let sum: i32 = add(1);",
+        );
+        let err = ast.unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Call));
+        assert_eq!(err.msg, "Too few arguments");
+
+        let (ast, _) = analyze(
+            "prv fn add(a: i32, b: i32) -> i32 { return a + b; }
+             prv let sum: i32 = add(1, 2, 3);",
+        );
+        let err = ast.unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Call));
+        assert_eq!(err.msg, "Too many arguments");
+    }
+
+    #[test]
+    fn call_argument_mismatch() {
+        let (ast, _) = analyze(
+            "prv fn not(a: bool) -> bool { return !a; }
+             prv let b: bool = not(1);",
+        );
+        let err = ast.unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Type));
+        assert_eq!(err.span.line, 3);
+        assert_eq!(err.span.size, 1);
+    }
+
+    #[test]
+    fn struct_update() {
+        let (ast, pool) = analyze(
+            "prv struct Point { x: i32, y: i32 }
+             prv let origin: Point = Point { x: 0, y: 0 };
+             prv let moved: Point = Point { ..origin, x: 10 };",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "moved", |value| {
+            let ExpressionValue::Literal(Literal::Struct(literal)) = &value.value else {
+                panic!("expected a struct literal");
+            };
+            assert!(literal.base.is_some());
+            assert_eq!(literal.fields.len(), 1);
+        });
+
+        let (ast, _) = analyze(
+            "prv struct Point { x: i32, y: i32 }
+             prv let origin: Point = Point { x: 0, y: 0 };
+             prv let moved: Point = Point { ..origin, z: 10 };",
+        );
+        assert_eq!(ast.unwrap_err().msg, "No such field");
+    }
+
+    #[test]
+    fn struct_literals() {
+        let point = "prv struct Point { x: i32, y: i32 }\n";
+        let (ast, pool) = analyze(&format!(
+            "{point}prv let p: Point = Point {{ y: 2, x: 1 }};"
+        ));
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "p", |value| {
+            let ExpressionValue::Literal(Literal::Struct(literal)) = &value.value else {
+                panic!("expected a struct literal");
+            };
+            assert_eq!(literal.fields.len(), 2);
+        });
+        let cases = [
+            (
+                "Point { x: 1 }",
+                ("Missing field in struct literal", (3, 20, 14)),
+            ),
+            ("Point { x: 1, y: 2, z: 3 }", ("No such field", (3, 43, 1))),
+            ("Point { x: 1, y: true }", ("Mismatched types", (3, 37, 4))),
+            (
+                "Size { x: 1, y: 2 }",
+                ("Can't resolve type name", (3, 20, 4)),
+            ),
+        ];
+        for (literal, expected) in cases {
+            assert_eq!(
+                error_at(&format!("{point}prv let p: Point = {literal};")),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn struct_update_non_struct() {
+        let (ast, _) = analyze(
+            "prv struct Point { x: i32, y: i32 }
+             prv let n: i32 = 0;
+             prv let moved: Point = Point { ..n, x: 10 };",
+        );
+        let err = ast.unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Type));
+        assert_eq!(err.msg, "Spreading a non-struct value");
+        assert_eq!(err.span.line, 4);
+    }
+
+    #[test]
+    fn format_call() {
+        let (ast, _) = analyze(
+            "prv fn show(age: i32, ok: bool) {
+                 print(\"age: {}, ok: {}, again: {0}\", age, ok);
+                 format(\"{}\", 1);
+             }",
+        );
+        assert!(ast.is_ok());
+    }
+
+    #[test]
+    fn format_placeholder_mismatch() {
+        let (ast, _) = analyze(
+            "prv fn show(age: i32) {
+                 print(\"{} {}\", age);
+             }",
+        );
+        let err = ast.unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Call));
+        assert_eq!(err.msg, "Too few format arguments");
+        assert_eq!(err.span.line, 3);
+
+        let (ast, _) = analyze(
+            "prv fn show(age: i32) {
+                 print(\"{1}\", age, age);
+             }",
+        );
+        assert_eq!(ast.unwrap_err().msg, "Too many format arguments");
+    }
+
+    #[test]
+    fn format_escaped_braces() {
+        let (ast, _) = analyze(
+            "prv fn show(age: i32) {
+                 print(\"{{}} {} }}{{\", age);
+             }",
+        );
+        assert!(ast.is_ok());
+
+        let (ast, _) = analyze(
+            "prv fn show(age: i32) {
+                 print(\"{{}}\", age);
+             }",
+        );
+        assert_eq!(ast.unwrap_err().msg, "Too many format arguments");
+
+        let (ast, _) = analyze(
+            "prv fn show(age: i32) {
+                 print(\"} {}\", age);
+             }",
+        );
+        assert_eq!(ast.unwrap_err().msg, "Unmatched `}` in format string");
+    }
+
+    #[test]
+    fn tuple_element_access() {
+        let (ast, pool) = analyze(
+            "prv fn pair() -> (i32, bool) { return (1, true); }
+             prv let first: i32 = pair().0;
+             prv let second: bool = pair().1;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "second", |value| {
+            assert_eq!(value.typ, Type::Bool);
+            let ExpressionValue::Binary(binary) = &value.value else {
+                panic!("expected a field access");
+            };
+            assert_eq!(binary.left.typ, Type::Tuple(vec![Type::I32, Type::Bool]));
+        });
+
+        let (ast, _) = analyze(
+            "prv fn pair() -> (i32, bool) { return (1, true); }
+             prv let third: i32 = pair().2;",
+        );
+        assert_eq!(ast.unwrap_err().msg, "No such tuple element");
+
+        let (ast, _) = analyze("prv fn pair() -> (i32, bool) { return (true, 1); }");
+        assert!(matches!(ast.unwrap_err().typ, ErrorType::Type));
+    }
+
+    /// Analyzes code that must fail and returns the error's message and
+    ///     (line, column, size). User code starts on line 2.
+    fn error_at(code: &str) -> (&'static str, (usize, usize, usize)) {
+        let err = analyze(code).0.unwrap_err();
+        (err.msg, (err.span.line, err.span.column, err.span.size))
+    }
+
+    #[test]
+    fn error_spans() {
+        assert_eq!(
+            error_at("prv let x: Missing = 1;"),
+            ("Can't resolve type name", (2, 12, 7))
+        );
+        assert_eq!(
+            error_at("prv fn f(a: i32, b: []let Missing) {}"),
+            ("Can't resolve type name", (2, 21, 13))
+        );
+        assert_eq!(
+            error_at("prv let x: std::io::Missing = 1;"),
+            ("Can't resolve type name", (2, 12, 16))
+        );
+        assert_eq!(
+            error_at("prv struct S { a: i32, b: Missing }"),
+            ("Can't resolve type name", (2, 27, 7))
+        );
+        assert_eq!(
+            error_at("prv let x: bool = true + false;"),
+            ("Arithmetic on a non-numeric value", (2, 19, 4))
+        );
+        assert_eq!(
+            error_at("prv fn f(a: bool) { a += true; }"),
+            (
+                "Invalid operand types for the assignment operator",
+                (2, 21, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn constant_folding() {
+        let (ast, pool) = analyze(
+            "prv let a: i32 = 2 + 3 * 4;
+             prv let b: u32 = 1 << 4;
+             prv let c: bool = 2 * 3 > 5 and !false;
+             prv let d: u8 = ~1;
+             prv let e: f64 = 1.5 * 2.0;",
+        );
+        let ast = ast.unwrap();
+        let literal = |name| {
+            global_value(&ast, &pool, name, |value| match &value.value {
+                ExpressionValue::Literal(literal) => (format!("{literal:?}"), value.typ.clone()),
+                _ => panic!("expected a folded literal"),
+            })
+        };
+        assert_eq!(literal("a"), ("UInt(14)".to_string(), Type::I32));
+        assert_eq!(literal("b"), ("UInt(16)".to_string(), Type::U32));
+        assert_eq!(literal("c"), ("Bool(true)".to_string(), Type::Bool));
+        assert_eq!(literal("d"), ("UInt(254)".to_string(), Type::U8));
+        assert_eq!(literal("e"), ("Float(3.0)".to_string(), Type::F64));
+
+        let (ast, _) = analyze("prv let x: u8 = 200 + 100;");
+        let err = ast.unwrap_err();
+        assert_eq!(err.msg, "Constant overflow");
+        assert_eq!((err.span.line, err.span.column), (2, 21));
+        let (ast, _) = analyze("prv let x: i32 = -2147483647 - 2;");
+        assert_eq!(ast.unwrap_err().msg, "Constant overflow");
+    }
+
+    #[test]
+    fn enum_variant() {
+        let (ast, pool) = analyze(
+            "prv enum Color { Red, Blue = 5 }
+             prv let c: Color = Color::Blue;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "c", |value| {
+            let ExpressionValue::Identifier(Identifier::EnumVariant(typ, variant)) = &value.value
+            else {
+                panic!("expected an enum variant");
+            };
+            assert_eq!(*variant, pool.search_symbol("Blue").unwrap());
+            assert_eq!(
+                typ.read().unwrap().name,
+                pool.search_symbol("Color").unwrap()
+            );
+            assert_eq!(value.typ, Type::Custom(typ.clone()));
+        });
+
+        let (ast, _) = analyze(
+            "prv enum Color { Red, Blue = 5 }
+             prv let c: Color = Color::Purple;",
+        );
+        let err = ast.unwrap_err();
+        assert_eq!(err.msg, "Enum has no such variant");
+        assert_eq!((err.span.line, err.span.column), (3, 33));
+    }
+
+    #[test]
+    fn array_sizes() {
+        let (ast, pool) = analyze(
+            "prv let M: u64 = N / 2;
+             prv let N: u64 = 4;
+             prv let a: [4]let u8 = {1, 2, 3, 4};
+             prv let b: [2 + 2]let u8 = {1, 2, 3, 4};
+             prv let c: [N]let u8 = {1, 2, 3, 4};
+             prv let d: [M * 2]let u8 = {1, 2, 3, 4};",
+        );
+        let ast = ast.unwrap();
+        let four = Type::Array {
+            inner: Box::new(Type::U8),
+            size: 4,
+            mutable: false,
+        };
+        for name in ["a", "b", "c", "d"] {
+            global_value(&ast, &pool, name, |value| assert_eq!(value.typ, four));
+        }
+
+        let (ast, _) = analyze(
+            "prv var N: u64 = 4;
+             prv let a: [N]let u8 = {1, 2, 3, 4};",
+        );
+        let err = ast.unwrap_err();
+        assert_eq!(err.msg, "Array size isn't a compile-time constant");
+        assert_eq!((err.span.line, err.span.column), (3, 26));
+        let (ast, _) = analyze("prv let a: [1 - 2]let u8 = {};");
+        assert_eq!(ast.unwrap_err().msg, "Array size can't be negative");
+        let (ast, _) = analyze("prv let a: [-1]let u8 = {};");
+        let err = ast.unwrap_err();
+        assert_eq!(err.msg, "Array size can't be negative");
+        assert_eq!((err.span.column, err.span.size), (13, 2));
+    }
+
+    #[test]
+    fn visibility_rules() {
+        let (_, ids) = InternPool::from_symbols(&["outer", "inner", "file", "other"]);
+        let [outer, inner, file, other] = ids[..] else {
+            unreachable!()
+        };
+        let path = [outer, inner, file];
+        let visible = |visibility, module: &[_]| is_visible(visibility, &path, module);
+        assert!(visible(Visibility::Public, &[other]));
+        assert!(!visible(Visibility::Private, &[outer, inner]));
+        assert!(visible(Visibility::Module, &[outer, inner]));
+        assert!(!visible(Visibility::Module, &[outer]));
+        assert!(visible(Visibility::Super, &[outer, inner]));
+        assert!(visible(Visibility::Super, &[outer]));
+        assert!(!visible(Visibility::Super, &[other]));
+        // A module of the same name somewhere else isn't the same module.
+        assert!(!visible(Visibility::Module, &[inner]));
+        assert!(!visible(Visibility::Module, &[other, inner]));
+        assert!(!visible(Visibility::Super, &[other, outer]));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(
+            error_at("prv let x: i32 = 1 / 0;"),
+            ("Division by zero", (2, 20, 3))
+        );
+        assert_eq!(
+            error_at("prv let x: u8 = 5 % (1 - 1);"),
+            ("Division by zero", (2, 19, 9))
+        );
+        assert_eq!(
+            error_at("prv fn f(x: i32) -> i32 { return x / 0; }"),
+            ("Division by zero", (2, 36, 3))
+        );
+
+        let (ast, pool) = analyze(
+            "prv var x: i32 = 2;
+             prv let y: i32 = 1 / x;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "y", |value| {
+            assert!(matches!(value.value, ExpressionValue::Binary(_)));
+        });
+    }
+
+    /// The size and alignment of a type defined in the test file.
+    fn type_layout(ast: &Ast, pool: &InternPool, name: &str) -> (usize, usize) {
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let typ = &module.files[&test].types[&pool.search_symbol(name).unwrap()];
+        let guard = typ.value.read().unwrap();
+        (guard.size, guard.align)
+    }
+
+    #[test]
+    fn packed_struct_size() {
+        let (ast, pool) = analyze(
+            "@packed prv struct Packed { a: u8, b: u32 }
+             prv struct Aligned { a: u8, b: u32 }
+             prv struct Outer { inner: Packed, c: u16 }
+             @packed prv union Bytes { a: u8, b: [3]let u8 }",
+        );
+        let ast = ast.unwrap();
+        assert_eq!(type_layout(&ast, &pool, "Packed"), (5, 1));
+        assert_eq!(type_layout(&ast, &pool, "Aligned"), (8, 4));
+        assert_eq!(type_layout(&ast, &pool, "Outer"), (8, 2));
+        assert_eq!(type_layout(&ast, &pool, "Bytes"), (3, 1));
+
+        let (ast, _) = analyze("@packed prv enum E { A }");
+        assert_eq!(
+            ast.unwrap_err().msg,
+            "Only structs and unions can be packed"
+        );
+        let (ast, _) = analyze("@aligned prv struct S { a: u8 }");
+        assert_eq!(ast.unwrap_err().msg, "Unknown attribute");
+        let (ast, _) = analyze("prv struct S { a: u8, next: S }");
+        assert_eq!(ast.unwrap_err().msg, "Type contains itself");
+    }
+
+    #[test]
+    fn enum_backing_types() {
+        let (ast, pool) = analyze(
+            "prv enum Flags : i8 { A = -1, B }
+             prv enum Wide : u16 { A = 300, B }
+             prv enum Default { A }",
+        );
+        let ast = ast.unwrap();
+        assert_eq!(type_layout(&ast, &pool, "Flags"), (1, 1));
+        assert_eq!(type_layout(&ast, &pool, "Wide"), (2, 2));
+        assert_eq!(type_layout(&ast, &pool, "Default"), (8, 8));
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let variants = |name| {
+            let typ = module.files[&test].types[&pool.search_symbol(name).unwrap()]
+                .value
+                .read()
+                .unwrap();
+            let TypeDefBody::Enum(enum_) = &typ.body else {
+                panic!("expected an enum");
+            };
+            let value = |variant| enum_.variants[&pool.search_symbol(variant).unwrap()];
+            (value("A"), value("B"))
+        };
+        assert_eq!(variants("Flags"), (-1, 0));
+        assert_eq!(variants("Wide"), (300, 301));
+
+        let (ast, _) = analyze("prv enum E : u8 { A = 255, B }");
+        assert_eq!(
+            ast.unwrap_err().msg,
+            "Enum value out of range of the backing type"
+        );
+        for code in ["prv enum E { A = -1 }", "prv enum E : u8 { A = -1 }"] {
+            assert_eq!(
+                analyze(code).0.unwrap_err().msg,
+                "Enum value can't be negative unless the backing type is signed"
+            );
+        }
+        let (ast, _) = analyze("prv enum E : f32 { A }");
+        assert_eq!(
+            ast.unwrap_err().msg,
+            "An enum must be backed by an integer type"
+        );
+    }
+
+    #[test]
+    fn casts() {
+        let (ast, pool) = analyze(
+            "prv enum E : u8 { A }
+             prv let a: i64 = -1 as i64 + 2;
+             prv let b: u8 = E::A as u8;
+             prv let c: f32 = 3 as f32;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "a", |value| assert_eq!(value.typ, Type::I64));
+        global_value(&ast, &pool, "b", |value| {
+            assert!(matches!(value.value, ExpressionValue::Cast(_)));
+        });
+        let (ast, _) = analyze("prv let x: bool = 1 as bool;");
+        assert_eq!(ast.unwrap_err().msg, "Invalid cast");
+    }
+
+    #[test]
+    fn loop_values() {
+        let (ast, pool) = analyze(
+            "prv let x: i32 = loop { break 5; };
+             prv let y: bool = loop { loop { break; } break true; };",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "x", |value| {
+            assert!(matches!(value.value, ExpressionValue::Loop(_)));
+            assert_eq!(value.typ, Type::I32);
+        });
+        global_value(&ast, &pool, "y", |value| assert_eq!(value.typ, Type::Bool));
+        assert_eq!(
+            error_at(
+                "prv fn f(c: bool) -> i32 {
+                 return loop { if (c) { break 1; } break true; };
+             }"
+            ),
+            ("Mismatched break types", (3, 52, 10))
+        );
+        assert_eq!(
+            error_at("prv fn f() { while { break 1; } }"),
+            ("Only `loop` expressions can break with a value", (2, 22, 7))
+        );
+    }
+
+    #[test]
+    fn array_repeat() {
+        let (ast, pool) = analyze(
+            "prv let N: u64 = 8;
+             prv let a: [16]let u8 = {0; 16};
+             prv let b: [16]let u8 = {0; N * 2};
+             prv use Block = [16]let u8;
+             prv let c: Block = {0; 16};",
+        );
+        let ast = ast.unwrap();
+        let expected = Type::Array {
+            inner: Box::new(Type::U8),
+            size: 16,
+            mutable: false,
+        };
+        for name in ["a", "b", "c"] {
+            global_value(&ast, &pool, name, |value| {
+                assert_eq!(value.typ, expected);
+                assert!(matches!(
+                    value.value,
+                    ExpressionValue::Literal(Literal::ArrayRepeat { count: 16, .. })
+                ));
+            });
+        }
+        assert_eq!(
+            error_at("prv let a: [4]let u8 = {0; 3};"),
+            ("Wrong number of array elements", (2, 24, 6))
+        );
+        assert_eq!(
+            error_at("prv use A = [4]let u8;\nprv let a: A = {0; 3};").0,
+            "Wrong number of array elements"
+        );
+        assert_eq!(
+            error_at("prv fn f(n: u64) { let a: [2]let u8 = {0; n}; }"),
+            ("Array size isn't a compile-time constant", (2, 43, 1))
+        );
+    }
+
+    #[test]
+    fn void_return() {
+        let (ast, _) = analyze(
+            "prv fn f() -> void {}
+             prv fn g() {}
+             prv let p: fn() -> void = g;
+             prv let q: fn() = f;",
+        );
+        assert!(ast.is_ok());
+        assert_eq!(
+            error_at("prv fn f() -> void { return 1; }"),
+            (
+                "Returning a value from a function without a return type",
+                (2, 29, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn f32_literals() {
+        let (ast, pool) = analyze(
+            "prv let a: f32 = 1.5f32;
+             prv let b: f32 = 0.1;
+             prv let c: f64 = 0.1;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "a", |value| assert_eq!(value.typ, Type::F32));
+        global_value(&ast, &pool, "b", |value| {
+            assert_eq!(value.typ, Type::F32);
+            assert_eq!(
+                value.value,
+                ExpressionValue::Literal(Literal::Float(0.1f32 as f64))
+            );
+        });
+        global_value(&ast, &pool, "c", |value| {
+            assert_eq!(value.value, ExpressionValue::Literal(Literal::Float(0.1)));
+        });
+        assert_eq!(
+            error_at("prv let x: f64 = 1.5f32;"),
+            ("Mismatched types", (2, 18, 6))
+        );
+        assert_eq!(
+            error_at("prv let x: f32 = 3.14159265358979;"),
+            ("Literal not representable as f32", (2, 18, 16))
+        );
+    }
+
+    #[test]
+    fn type_modifiers() {
+        let (ast, pool) =
+            analyze("prv fn f(a: *let u8, b: []var i32, c: [4]let bool, d: []var []let u8) {}");
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let f = &module.files[&test].functions[&pool.search_symbol("f").unwrap()];
+        let args = f.value.read().unwrap().signature().args;
+        assert_eq!(
+            args[0],
+            Type::Pointer {
+                inner: Box::new(Type::U8),
+                mutable: false,
+            }
+        );
+        assert_eq!(
+            args[1],
+            Type::Slice {
+                inner: Box::new(Type::I32),
+                mutable: true,
+            }
+        );
+        assert_eq!(
+            args[2],
+            Type::Array {
+                inner: Box::new(Type::Bool),
+                size: 4,
+                mutable: false,
+            }
+        );
+        assert_eq!(
+            args[3],
+            Type::Slice {
+                inner: Box::new(Type::Slice {
+                    inner: Box::new(Type::U8),
+                    mutable: false,
+                }),
+                mutable: true,
+            }
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let (diagnostics, _) = diagnose(
+            "prv fn f(a: i32) -> i32 {
+                 let a: i32 = 1;
+                 return a;
+             }",
+        );
+        assert!(diagnostics.errors.is_empty());
+        assert_eq!(diagnostics.warnings.len(), 1);
+        let warning = &diagnostics.warnings[0];
+        assert!(matches!(warning.typ, WarningType::Shadowing));
+        assert_eq!(warning.msg, "Declaration shadows an outer variable");
+        assert_eq!((warning.span.line, warning.span.column), (3, 18));
+        let levels: Vec<_> = diagnostics.iter().map(|(level, ..)| level).collect();
+        assert_eq!(levels, [Level::Warning]);
+    }
+
+    #[test]
+    fn item_imports() {
+        let shapes = "pub fn area(side: i32) -> i32 { return side * side; }
+                      pub enum Shape { Square, Circle }
+                      prv fn secret() {}";
+        let (ast, pool) = analyze_modules(&[
+            (
+                "app",
+                "import shapes::lib::area;
+                 prv let size: i32 = area(3);",
+            ),
+            ("shapes", shapes),
+        ]);
+        let ast = ast.unwrap();
+        let app = pool.search_symbol("app").unwrap();
+        let lib = pool.search_symbol("lib").unwrap();
+        let size = pool.search_symbol("size").unwrap();
+        let module = ast.modules[&app].read().unwrap();
+        let global = module.files[&lib].globals[&size].value.read().unwrap();
+        let ExpressionValue::Call(call) = &global.value.value else {
+            panic!("expected a call");
+        };
+        assert!(matches!(
+            call.function.value,
+            ExpressionValue::Identifier(Identifier::Function(_))
+        ));
+        assert_eq!(global.value.typ, Type::I32);
+
+        let (ast, _) = analyze_modules(&[
+            (
+                "app",
+                "import shapes::area;
+                 import shapes::lib::Shape;
+                 prv fn measure(shape: Shape) -> i32 { return area(2); }",
+            ),
+            ("shapes", shapes),
+        ]);
+        ast.unwrap();
+        let (ast, _) = analyze_files(&[
+            (
+                "main",
+                "import app::shapes::area;
+                 import app::Shape;
+                 prv fn measure(shape: Shape) -> i32 { return area(2); }",
+            ),
+            ("shapes", shapes),
+        ]);
+        ast.unwrap();
+
+        let failing = [
+            (
+                "import shapes::lib::area;\nprv fn area() {}",
+                "Imported item collides with a local name",
+            ),
+            ("import shapes::lib::secret;", "Imported item isn't visible"),
+            ("import shapes::lib::volume;", "Imported item doesn't exist"),
+        ];
+        for (code, msg) in failing {
+            let (ast, _) = analyze_modules(&[("app", code), ("shapes", shapes)]);
+            let err = ast.unwrap_err();
+            assert_eq!(err.msg, msg);
+            assert_eq!((err.span.line, err.span.column), (2, 8));
+        }
+    }
+
+    #[test]
+    fn match_expressions() {
+        let (ast, pool) = analyze(
+            "prv enum Color { Red, Green }
+             prv fn pick(flag: bool, color: Color) -> i32 {
+                 let a: i32 = match (flag) { true => 1, false => { let b: i32 = 2; b } };
+                 let c: i32 = match (color) { Color::Red => 1, Color::Green => 2 };
+                 return a + c;
+             }
+             prv let big: u64 = match (3) { 1 => 10, _ => 20 };",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "big", |value| {
+            assert_eq!(value.typ, Type::U64);
+            assert!(matches!(value.value, ExpressionValue::Match(_)));
+        });
+
+        let failing = [
+            (
+                "prv let x: i32 = match (true) { true => 1 };",
+                "Match expression isn't exhaustive",
+                (2, 18, 26),
+            ),
+            (
+                "prv let x: i32 = match (1) { 1 => 1 };",
+                "Match expression needs a default arm",
+                (2, 18, 20),
+            ),
+            (
+                "prv let x: i32 = match (true) { true => 1, _ => false };",
+                "Mismatched match arm types",
+                (2, 49, 5),
+            ),
+        ];
+        for (code, msg, position) in failing {
+            assert_eq!(error_at(code), (msg, position));
+        }
+    }
+
+    #[test]
+    fn match_exhaustiveness() {
+        let (ast, _) = analyze(
+            "prv enum Color { Red, Green, Blue }
+             prv fn f(flag: bool, color: Color, n: i32) {
+                 match (flag) { true => {} false => {} }
+                 match (color) { Color::Red => {} _ => {} }
+                 match (n) { 1 => {} }
+                 match (flag) { !false => {} false => {} }
+             }
+             prv let x: i32 = match (true) { 1 < 2 => 1, false => 0 };",
+        );
+        assert!(ast.is_ok());
+
+        let missing = |code| {
+            let (ast, mut pool) = analyze(code);
+            let err = ast.unwrap_err();
+            assert_eq!(err.msg, "Match isn't exhaustive");
+            let ErrorType::NonExhaustive { missing } = err.typ else {
+                panic!("expected a non-exhaustive match, got {:?}", err.typ);
+            };
+            missing
+                .iter()
+                .map(|&id| pool.symbol_reverse_lookup(id).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            missing("prv fn f(flag: bool) { match (flag) { true => {} } }"),
+            vec!["false"]
+        );
+        assert_eq!(
+            missing(
+                "prv enum Color { Red, Green, Blue }
+                 prv fn f(color: Color) { match (color) { Color::Green => {} } }"
+            ),
+            vec!["Red", "Blue"]
+        );
+        assert_eq!(
+            missing(
+                "prv enum Color { Red, Green }
+                 prv use Hue = Color;
+                 prv fn f(hue: Hue) { match (hue) { Color::Red => {} } }"
+            ),
+            vec!["Green"]
+        );
+        assert_eq!(
+            missing(
+                "prv enum Shape { Circle(f32), Square(i32) }
+                 prv use Form = Shape;
+                 prv fn f(form: Form) { match (form) { Shape::Circle(r) => {} } }"
+            ),
+            vec!["Square"]
+        );
+    }
+
+    #[test]
+    fn lengths() {
+        let (ast, pool) = analyze(
+            "prv struct Buffer { len: u8 }
+             prv fn count(list: []let i32) -> usize { return list.len; }
+             prv let numbers: [3]let i32 = {1, 2, 3};
+             prv let size: usize = numbers.len;
+             prv let buffer: Buffer = Buffer { len: 1 };
+             prv let used: u8 = buffer.len;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "size", |value| {
+            assert_eq!(value.typ, Type::Usize);
+            assert_eq!(value.value, ExpressionValue::Literal(Literal::UInt(3)));
+        });
+        global_value(&ast, &pool, "used", |value| assert_eq!(value.typ, Type::U8));
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let count = &module.files[&test].functions[&pool.search_symbol("count").unwrap()];
+        let FunctionBody::Block(body) = &count.value.read().unwrap().body else {
+            panic!("expected a block");
+        };
+        let crate::semantic_ast::Statement::Return(Some(value)) = &body[0] else {
+            panic!("expected a return");
+        };
+        assert_eq!(value.typ, Type::Usize);
+        let ExpressionValue::Binary(binary) = &value.value else {
+            panic!("expected a field access");
+        };
+        assert_eq!(
+            binary.right.value,
+            ExpressionValue::Identifier(Identifier::Length)
+        );
+
+        let (ast, pool) = analyze(
+            "prv use Ints = []let i32;
+             prv use Triple = [3]let i32;
+             prv fn count(list: Ints) -> usize { return list.len; }
+             prv let numbers: Triple = {1, 2, 3};
+             prv let size: usize = numbers.len;",
+        );
+        global_value(&ast.unwrap(), &pool, "size", |value| {
+            assert_eq!(value.value, ExpressionValue::Literal(Literal::UInt(3)));
+        });
+
+        assert_eq!(
+            error_at("prv let n: i32 = 1;\nprv let x: usize = n.len;"),
+            ("Only slices and arrays have a length", (3, 22, 3))
+        );
+    }
+
+    #[test]
+    fn pointer_arithmetic() {
+        let (ast, _) = analyze(
+            "prv fn f(p: *let i32, q: *let i32) {
+                 let next: *let i32 = p + 1;
+                 let previous: *let i32 = p - 2;
+                 let distance: isize = p - q;
+                 let address: usize = p as usize;
+                 let back: *let i32 = address as *let i32;
+             }",
+        );
+        assert!(ast.is_ok());
+
+        let f = "prv fn f(p: *let i32, q: *let i32, n: usize) {\n";
+        let failing = [
+            (
+                "let r: *let i32 = p + q;",
+                ("Invalid pointer arithmetic", (3, 23, 1)),
+            ),
+            (
+                "let r: *let i32 = p * q;",
+                ("Arithmetic on a non-numeric value", (3, 19, 1)),
+            ),
+            ("let r: *let i32 = n;", ("Mismatched types", (3, 19, 1))),
+            ("let r: usize = p;", ("Mismatched types", (3, 16, 1))),
+        ];
+        for (statement, expected) in failing {
+            assert_eq!(error_at(&format!("{f}{statement}\n}}")), expected);
+        }
+    }
+
+    #[test]
+    fn bounded_errors() {
+        let code: String = (0..300)
+            .map(|i| format!("prv fn func{i}() {{ missing{i}(); }}\n"))
+            .collect();
+        let (diagnostics, _) = diagnose(&code);
+        assert_eq!(diagnostics.errors.len(), 100);
+        assert_eq!(diagnostics.notes.len(), 1);
+        let levels: Vec<Level> = diagnostics.iter().map(|(level, _, _)| level).collect();
+        assert_eq!(levels.last(), Some(&Level::Note));
+
+        let (ast, pool) = syntax(&code);
+        let options = SemanticOptions { max_errors: 5 };
+        let (sem_ast, diagnostics) = SemanticParser::analyze_with_options(&ast, &pool, options);
+        assert!(sem_ast.is_none());
+        assert_eq!(diagnostics.errors.len(), 5);
+        assert_eq!(
+            diagnostics.notes[0].msg,
+            "Too many errors, the rest aren't reported"
+        );
+        // The errors kept are the first ones in the file, followed by the note.
+        let lines: Vec<usize> = diagnostics.errors.iter().map(|e| e.span.line).collect();
+        assert_eq!(lines, [2, 3, 4, 5, 6]);
+        assert_eq!(diagnostics.notes[0].span.line, 7);
+
+        let (diagnostics, _) = diagnose("prv fn f() { a(); }\nprv fn g() { b(); }");
+        assert_eq!(diagnostics.errors.len(), 2);
+        assert!(diagnostics.notes.is_empty());
+    }
+
+    #[test]
+    fn field_defaults() {
+        let point = "prv struct Point { x: i32, y: i32 = 0 }\n";
+        let (ast, pool) = analyze(&format!("{point}prv let p: Point = Point {{ x: 5 }};"));
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "p", |value| {
+            let ExpressionValue::Literal(Literal::Struct(literal)) = &value.value else {
+                panic!("expected a struct literal");
+            };
+            let y = &literal.fields[&pool.search_symbol("y").unwrap()];
+            assert_eq!(y.value, ExpressionValue::Literal(Literal::UInt(0)));
+            assert_eq!(y.typ, Type::I32);
+        });
+        let (ast, _) = analyze(&format!(
+            "{point}prv let p: Point = Point {{ x: 5, y: 6 }};"
+        ));
+        assert!(ast.is_ok());
+
+        let code = "prv let N: i32 = 4;\nprv struct Point { x: i32 = N * 2 }\nprv let p: Point = Point {};";
+        assert!(analyze(code).0.is_ok());
+        let (ast, pool) = analyze(
+            "prv struct Scaled { by: f32 = SCALE, on: bool = !OFF }
+             prv let SCALE: f32 = 1.5;
+             prv let OFF: bool = false;
+             prv let s: Scaled = Scaled {};",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "s", |value| {
+            let ExpressionValue::Literal(Literal::Struct(literal)) = &value.value else {
+                panic!("expected a struct literal");
+            };
+            let by = &literal.fields[&pool.search_symbol("by").unwrap()];
+            assert_eq!(by.value, ExpressionValue::Literal(Literal::Float(1.5)));
+            let on = &literal.fields[&pool.search_symbol("on").unwrap()];
+            assert_eq!(on.value, ExpressionValue::Literal(Literal::Bool(true)));
+        });
+        let cases = [
+            (
+                "prv var n: i32 = 4;\nprv struct Point { x: i32 = n }",
+                ("Field default isn't a compile-time constant", (3, 29, 1)),
+            ),
+            (
+                "prv struct Point { x: i32 = true }",
+                ("Mismatched types", (2, 29, 4)),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(error_at(code), expected);
+        }
+    }
+
+    #[test]
+    fn literal_visitor() {
+        #[derive(Default)]
+        struct LiteralCounter {
+            literals: usize,
+        }
+
+        impl crate::visitor::Visitor for LiteralCounter {
+            fn visit_expression(&mut self, exp: &mut Expression) {
+                if matches!(exp.value, ExpressionValue::Literal(_)) {
+                    self.literals += 1;
+                }
+                crate::visitor::walk_expression(self, exp);
+            }
+        }
+
+        let (ast, pool) = analyze(
+            "prv fn f(y: i32) -> i32 {
+                 let x: i32 = 1 + y;
+                 let p: (i32, bool) = (x, true);
+                 if (x > 2) { return 3; }
+                 return x * (4 + 5);
+             }",
+        );
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let f = &module.files[&test].functions[&pool.search_symbol("f").unwrap()];
+        let FunctionBody::Block(body) = &mut f.value.write().unwrap().body else {
+            panic!("expected a block body");
+        };
+        let mut counter = LiteralCounter::default();
+        crate::visitor::walk_block(&mut counter, body);
+        // The tuple and its `true`, `1`, `2`, `3` and `4 + 5` folded into `9`.
+        assert_eq!(counter.literals, 6);
+    }
+
+    #[test]
+    fn tagged_enum_size() {
+        let (ast, pool) = analyze(
+            "prv enum Shape { Circle(f64), Rect(f64, f64), Empty }
+             prv enum Small : u8 { A(u8), B(u16) }
+             prv enum Bare : u8 { A(), B }",
+        );
+        let ast = ast.unwrap();
+        assert_eq!(type_layout(&ast, &pool, "Shape"), (24, 8));
+        assert_eq!(type_layout(&ast, &pool, "Small"), (4, 2));
+        assert_eq!(type_layout(&ast, &pool, "Bare"), (1, 1));
+    }
+
+    #[test]
+    fn tagged_enum_patterns() {
+        let shape = "prv enum Shape { Circle(f64), Rect(f64, f64), Empty }\n";
+        let (ast, pool) = analyze(&format!(
+            "{shape}prv fn area(s: Shape) -> f64 {{
+                 return match (s) {{
+                     Circle(r) => 3.0 * r * r,
+                     Shape::Rect(w, _) => {{ let h: f64 = 2.0; w * h }},
+                     Empty => 0.0,
+                 }};
+             }}
+             prv fn width(s: Shape) -> f64 {{
+                 match (s) {{
+                     Rect(w, h) => {{ return w; }}
+                     _ => {{}}
+                 }}
+                 return 0.0;
+             }}
+             prv let c: Shape = Shape::Circle(1.0);
+             prv let e: Shape = Shape::Empty;"
+        ));
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "c", |value| {
+            let ExpressionValue::Literal(Literal::Variant { variant, payload }) = &value.value
+            else {
+                panic!("expected a variant literal");
+            };
+            assert_eq!(*variant, pool.search_symbol("Circle").unwrap());
+            assert_eq!(payload.len(), 1);
+        });
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let area = &module.files[&test].functions[&pool.search_symbol("area").unwrap()];
+        let FunctionBody::Block(body) = &area.value.read().unwrap().body else {
+            panic!("expected a block body");
+        };
+        let crate::semantic_ast::Statement::Return(Some(value)) = &body[0] else {
+            panic!("expected a return");
+        };
+        let ExpressionValue::Match(match_) = &value.value else {
+            panic!("expected a match expression");
+        };
+        let bound = |arm: usize| -> Vec<(SymbolId, Type)> {
+            match_.cases[arm]
+                .body
+                .statements
+                .iter()
+                .filter_map(|statement| match statement {
+                    crate::semantic_ast::Statement::Declaration(declaration) => {
+                        let declaration = declaration.read().unwrap();
+                        matches!(
+                            declaration.value.value,
+                            ExpressionValue::Identifier(Identifier::Payload(_))
+                        )
+                        .then(|| (declaration.name, declaration.typ.clone()))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(
+            bound(0),
+            vec![(pool.search_symbol("r").unwrap(), Type::F64)]
+        );
+        assert_eq!(
+            bound(1),
+            vec![(pool.search_symbol("w").unwrap(), Type::F64)]
+        );
+        assert!(bound(2).is_empty());
+
+        let cases = [
+            (
+                "prv fn f(s: Shape) { match (s) { Circle(r) => {} } }",
+                "Match isn't exhaustive",
+            ),
+            (
+                "prv fn f(s: Shape) { match (s) { Rect(w) => {} _ => {} } }",
+                "Wrong number of bindings",
+            ),
+            (
+                "prv fn f(s: Shape) { match (s) { Square => {} _ => {} } }",
+                "Not a variant of the matched enum",
+            ),
+            (
+                "prv fn f(s: Shape) { match (s) { Circle(1) => {} _ => {} } }",
+                "Expected a binding name",
+            ),
+            (
+                "prv let c: Shape = Shape::Circle;",
+                "Variant needs a payload",
+            ),
+            ("prv let c: Shape = Shape::Circle(1);", "Mismatched types"),
+            ("prv let c: Shape = Shape::Rect(1.0);", "Too few arguments"),
+        ];
+        for (code, msg) in cases {
+            assert_eq!(analyze(&format!("{shape}{code}")).0.unwrap_err().msg, msg);
+        }
+    }
+
+    #[test]
+    fn sorted_errors() {
+        let code: String = (0..30)
+            .map(|i| format!("prv fn func{i}() {{ let x: i32 = 1;\nmissing{i}(); }}\n"))
+            .collect();
+        let (diagnostics, _) = diagnose(&code);
+        let lines: Vec<usize> = diagnostics.errors.iter().map(|e| e.span.line).collect();
+        let expected: Vec<usize> = (0..30).map(|i| i * 2 + 3).collect();
+        assert_eq!(lines, expected);
+
+        // Paths are ordered by value, not by when they were interned.
+        let (pool, mut paths) = InternPool::from_paths(&["b.code", "a.code"]);
+        paths.reverse();
+        let error = |span: Span| Error {
+            typ: ErrorType::Name,
+            msg: "",
+            span,
+        };
+        let at = |path, line, column| Span {
+            path,
+            line,
+            column,
+            index: 0,
+            size: 1,
+        };
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.errors.push(error(at(paths[1], 1, 1)));
+        diagnostics.errors.push(error(at(paths[0], 3, 2)));
+        diagnostics.errors.push(error(at(paths[0], 3, 1)));
+        diagnostics.errors.push(error(Span::path_only(paths[0])));
+        diagnostics.errors.push(error(at(paths[0], 1, 8)));
+        diagnostics.sort(&pool);
+        let spans: Vec<Span> = diagnostics.errors.iter().map(|e| e.span).collect();
+        assert_eq!(
+            spans,
+            [
+                Span::path_only(paths[0]),
+                at(paths[0], 1, 8),
+                at(paths[0], 3, 1),
+                at(paths[0], 3, 2),
+                at(paths[1], 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_return() {
+        let (ast, pool) = analyze("prv fn f() { return; }");
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let f = &module.files[&test].functions[&pool.search_symbol("f").unwrap()];
+        let FunctionBody::Block(body) = &f.value.read().unwrap().body else {
+            panic!("expected a block body");
+        };
+        assert!(matches!(
+            body[..],
+            [crate::semantic_ast::Statement::Return(None)]
+        ));
+        assert_eq!(
+            error_at("prv fn f() -> i32 { return; }"),
+            ("Missing return value", (2, 21, 6))
+        );
+    }
+
+    #[test]
+    fn transparent_aliases() {
+        let (ast, pool) = analyze(
+            "prv use Int = i32;
+             prv use Count = Int;
+             prv let x: Count = 5;
+             prv let y: i32 = x;
+             prv let z: f32 = 1.5 as Real;
+             prv use Real = f32;
+             prv fn next(a: Int) -> i32 { return a + 1; }",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "x", |value| {
+            assert!(matches!(value.typ, Type::Custom(_)));
+            assert_eq!(value.typ, Type::I32);
+            assert_eq!(value.typ.resolve_alias(), Type::I32);
+            assert!(matches!(value.typ.resolve_alias(), Type::I32));
+            assert_ne!(value.typ, Type::U32);
+        });
+        assert_eq!(
+            error_at("prv use Int = i32;\nprv let x: Int = true;"),
+            ("Mismatched types", (3, 18, 4))
+        );
+        let (msg, position) = error_at("prv use A = B;\nprv use B = A;\nprv let x: A = 1;");
+        assert_eq!(msg, "Type contains itself");
+        // Types are laid out in the order they're named, so `A` is reported.
+        assert_eq!(position, (2, 9, 1));
+    }
+
+    #[test]
+    fn asm_operands() {
+        let (ast, pool) =
+            analyze(r#"prv fn out(port: u16, value: u8) asm("out {0}, {1}" : port, value);"#);
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let out = &module.files[&test].functions[&pool.search_symbol("out").unwrap()];
+        let FunctionBody::Asm(asm) = &out.value.read().unwrap().body else {
+            panic!("expected an asm body");
+        };
+        let types: Vec<&Type> = asm.operands.iter().map(|operand| &operand.typ).collect();
+        assert_eq!(types, [&Type::U16, &Type::U8]);
+        assert_eq!(
+            error_at(r#"prv fn out(port: u16) asm("out {0}, {1}" : port, missing);"#),
+            ("Can't resolve name", (2, 50, 7))
+        );
+        assert_eq!(
+            error_at(r#"prv fn out(port: u16) asm("out {0}, {1}" : port);"#).0,
+            "Asm placeholder has no operand"
+        );
+        assert_eq!(
+            error_at(r#"prv fn halt() asm("jmp {99999999999999999999}");"#).0,
+            "Asm placeholder has no operand"
+        );
+        assert!(
+            analyze(r#"prv fn f(x: u8) asm("mov al, {0}" "{x} {}" : x);"#)
+                .0
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn comparison_operands() {
+        let (ast, _) = analyze(
+            "prv enum Color { Red, Green }
+             prv let a: bool = 1 < 2;
+             prv let b: bool = 1.5 >= 2.0;
+             prv let c: bool = true == false;
+             prv let d: bool = Color::Red != Color::Green;
+             prv fn same(p: *let i32, q: *let i32) -> bool { return p == q; }",
+        );
+        assert!(ast.is_ok());
+        assert_eq!(
+            error_at("prv let x: bool = true < false;"),
+            ("Ordering a non-numeric value", (2, 19, 4))
+        );
+        assert_eq!(
+            error_at("prv fn f(p: *let i32, q: *let i32) -> bool { return p < q; }"),
+            ("Ordering a non-numeric value", (2, 53, 1))
+        );
+        let point = "prv struct Point { x: i32, y: i32 }\n";
+        assert_eq!(
+            error_at(&format!(
+                "{point}prv fn f(a: Point, b: Point) -> bool {{ return a == b; }}"
+            )),
+            (
+                "Values of this type can't be compared for equality",
+                (3, 47, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn immutable_assignments() {
+        let (ast, _) = analyze(
+            "prv struct Point { x: i32, y: i32 }
+             prv var counter: i32 = 0;
+             prv fn f(p: *var i32, s: []var i32, n: i32) {
+                 var x: i32 = 1;
+                 x = 2;
+                 x += 1;
+                 counter = x;
+                 *p = 3;
+                 s[0] = 4;
+                 n = 5;
+                 var point: Point = Point { x: 1, y: 2 };
+                 point.x = 6;
+                 var values: [2]var i32 = {1, 2};
+                 values[1] = 7;
+             }",
+        );
+        assert!(ast.is_ok());
+        let f = "prv struct Point { x: i32, y: i32 }\nprv fn f(p: *let i32, s: []let i32) {\n";
+        let failing = [
+            ("let x: i32 = 1;\nx = 2;", (5, 1, 6)),
+            ("let x: i32 = 1;\nx += 2;", (5, 1, 7)),
+            ("*p = 3;", (4, 1, 7)),
+            ("s[0] = 4;", (4, 1, 9)),
+            (
+                "let point: Point = Point { x: 1, y: 2 };\npoint.x = 6;",
+                (5, 1, 12),
+            ),
+            (
+                "var values: [2]let i32 = {1, 2};\nvalues[1] = 7;",
+                (5, 1, 14),
+            ),
+            (
+                "let values: [2]var i32 = {1, 2};\nvalues[1] = 7;",
+                (5, 1, 14),
+            ),
+        ];
+        for (statement, span) in failing {
+            assert_eq!(
+                error_at(&format!("{f}{statement}\n}}")),
+                ("Assigning to an immutable value", span),
+                "{statement}"
+            );
+        }
+        assert_eq!(
+            error_at("prv let limit: i32 = 1;\nprv fn f() { limit = 2; }"),
+            ("Assigning to an immutable value", (3, 14, 10))
+        );
+    }
+
+    #[test]
+    fn block_scope() {
+        let (ast, _) = analyze("prv fn f() { var x: i32 = 1; { var x: i32 = 2; x = 3; } x = 4; }");
+        assert!(ast.is_ok());
+        assert_eq!(
+            error_at("prv fn f() { { let x: i32 = 1; } x; }"),
+            ("Can't resolve name", (2, 34, 1))
+        );
+    }
+
+    #[test]
+    fn call_through_field() {
+        let (ast, pool) = analyze(
+            "prv fn double(x: i32) -> i32 { return x * 2; }
+             prv struct Op { f: fn(i32) -> i32, n: i32 }
+             prv let op: Op = Op { f: double, n: 1 };
+             prv let result: i32 = op.f(3);",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "result", |value| {
+            assert_eq!(value.typ, Type::I32);
+            let ExpressionValue::Call(call) = &value.value else {
+                panic!("expected a call");
+            };
+            assert!(matches!(
+                &call.function.value,
+                ExpressionValue::Binary(Binary {
+                    op: BinaryOp::FieldAccess,
+                    ..
+                })
+            ));
+        });
+
+        assert_eq!(
+            error_at(
+                "prv struct Op { n: i32 }
+                 prv let op: Op = Op { n: 1 };
+                 prv let result: i32 = op.n(3);"
+            ),
+            ("Calling a non-function value", (4, 42, 2))
+        );
+    }
+
+    #[test]
+    fn field_access_through_pointer() {
+        let point = "prv struct Point { x: i32, y: i64 }\n";
+        let (ast, _) = analyze(&format!(
+            "{point}prv fn get(p: *let Point) -> i64 {{ return p.y; }}
+             prv fn set(p: *var Point) {{ p.x = 1; }}
+             prv fn get2(p: *let *let Point) -> i32 {{ return (*p).x; }}"
+        ));
+        assert!(ast.is_ok());
+        assert_eq!(
+            error_at(&format!(
+                "{point}prv fn get(p: *let Point) -> i32 {{ return p.y; }}"
+            ))
+            .0,
+            "Mismatched types"
+        );
+        assert_eq!(
+            error_at(&format!("{point}prv fn set(p: *let Point) {{ p.x = 1; }}")).0,
+            "Assigning to an immutable value"
+        );
+        assert_eq!(
+            error_at(&format!(
+                "{point}prv fn get(p: *let *let Point) -> i32 {{ return p.x; }}"
+            )),
+            ("No such field", (3, 50, 1))
+        );
+    }
+
+    #[test]
+    fn derives() {
+        let (ast, pool) = analyze(
+            "@derive(Eq, Print) prv struct P { a: u8, b: bool }
+             @derive(Eq) prv struct Q { p: P, next: *let Q, f: fn() }
+             @derive(Print) prv enum Shape { Circle(f32), Square(P) }
+             prv fn same(a: Q, b: Q) -> bool { return a == b; }",
+        );
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let p = &module.files[&test].types[&pool.search_symbol("P").unwrap()];
+        assert_eq!(p.value.read().unwrap().derives, [Derive::Eq, Derive::Print]);
+
+        assert_eq!(
+            error_at("prv struct P { a: u8 }\n@derive(Eq) prv struct Q { p: P }"),
+            ("A field can't be compared for equality", (3, 24, 1))
+        );
+        assert_eq!(
+            error_at("@derive(Print) prv struct P { p: *let u8 }").0,
+            "A field can't be printed"
+        );
+        assert_eq!(
+            error_at("@derive(Eq, Ord) prv struct P { a: u8 }"),
+            ("Unknown derive", (2, 13, 3))
+        );
+        assert_eq!(
+            error_at("@derive(Eq, Eq) prv struct P { a: u8 }").0,
+            "Derived twice"
+        );
+        assert_eq!(
+            error_at("@derive(Eq) prv union U { a: u8 }").0,
+            "Only structs and enums can derive"
+        );
+    }
+
+    #[test]
+    fn string_constants() {
+        let (ast, pool) = analyze(
+            "prv let N: usize = \"hello\".len;
+             prv let WIDE: usize = \"héllo\".len;
+             prv let A: u8 = \"abc\"[0];
+             prv let TABLE: [\"abc\".len]let u8 = {1, 2, 3};",
+        );
+        let ast = ast.unwrap();
+        let folded = |name| {
+            let mut literal = None;
+            global_value(&ast, &pool, name, |value| {
+                let ExpressionValue::Literal(Literal::UInt(uint)) = value.value else {
+                    panic!("expected {name} to fold");
+                };
+                literal = Some(uint);
+            });
+            literal.unwrap()
+        };
+        assert_eq!(folded("N"), 5);
+        // The length is in bytes, and `é` takes two.
+        assert_eq!(folded("WIDE"), 6);
+        assert_eq!(folded("A"), b'a' as u64);
+
+        assert_eq!(
+            error_at("prv let A: u8 = \"abc\"[3];"),
+            ("Index out of bounds", (2, 23, 1))
+        );
+    }
+
+    #[test]
+    fn expression_body() {
+        let (ast, pool) = analyze(
+            "prv fn double(x: i32) -> i32 = x * 2;
+             prv fn one() -> u8 = 1;",
+        );
+        let ast = ast.unwrap();
+        let test = pool.search_symbol("test").unwrap();
+        let module = ast.modules[&test].read().unwrap();
+        let return_type = |name| {
+            let f = &module.files[&test].functions[&pool.search_symbol(name).unwrap()];
+            let FunctionBody::Block(body) = &f.value.read().unwrap().body else {
+                panic!("expected a block body");
+            };
+            let [crate::semantic_ast::Statement::Return(Some(value))] = &body[..] else {
+                panic!("expected a single return");
+            };
+            value.typ.clone()
+        };
+        assert_eq!(return_type("double"), Type::I32);
+        assert_eq!(return_type("one"), Type::U8);
+
+        assert_eq!(
+            error_at("prv fn double(x: i32) -> bool = x * 2;").0,
+            "Mismatched types"
+        );
+    }
+
+    #[test]
+    fn break_in_match() {
+        let (ast, _) = analyze(
+            "prv fn f(x: i32) {
+                 while (true) { match (x) { 1 => { continue; } _ => { break; } } }
+             }
+             prv let y: i32 = loop { match (true) { true => { break 1; } false => { break 2; } } };",
+        );
+        ast.unwrap();
+        assert_eq!(
+            error_at("prv fn f(x: i32) { match (x) { _ => { break; } } }"),
+            ("`break` outside of a loop", (2, 39, 5))
+        );
+        assert_eq!(
+            error_at("prv fn f() { continue; }"),
+            ("`continue` outside of a loop", (2, 14, 8))
+        );
+    }
+
+    /// Analyzes one module named "app" made of the given files.
+    fn analyze_files(files: &[(&str, &str)]) -> (Result<Ast, Error>, InternPool) {
+        let mut pool = InternPool::new();
+        let app = pool.insert_symbol("app".to_string());
+        let mut syn_files = HashMap::new();
+        let mut path = None;
+        for (file_name, code) in files {
+            let code = format!("module app;\n{code}");
+            let name = pool.insert_symbol(file_name.to_string());
+            let file_path = pool.insert_path(PathBuf::from(format!("app/{file_name}.code")));
+            let file = SyntacticParser::parse_code(file_path, &code, name, app, &mut pool).unwrap();
+            syn_files.insert(name, file);
+            path.get_or_insert(file_path);
+        }
+        let module = syntax_ast::Module {
+            name: app,
+            files: syn_files,
+            submodules: HashMap::new(),
+            dependencies: HashSet::new(),
+            path: path.unwrap(),
+        };
+        let ast = syntax_ast::Ast {
+            entry: app,
+            modules: HashMap::from([(app, module)]),
+        };
+        (SemanticParser::parse(&ast, &pool), pool)
+    }
+
+    #[test]
+    fn sibling_types() {
+        let (ast, pool) = analyze_files(&[
+            (
+                "shapes",
+                "mod struct Point { x: i32 } mod enum Color { Red }",
+            ),
+            (
+                "main",
+                "prv let origin: Point = Point { x: 0 };
+                 prv let red: Color = Color::Red;",
+            ),
+        ]);
+        let ast = ast.unwrap();
+        let app = pool.search_symbol("app").unwrap();
+        let shapes = pool.search_symbol("shapes").unwrap();
+        let main = pool.search_symbol("main").unwrap();
+        let module = ast.modules[&app].read().unwrap();
+        let point = &module.files[&shapes].types[&pool.search_symbol("Point").unwrap()];
+        let origin = &module.files[&main].globals[&pool.search_symbol("origin").unwrap()];
+        let Type::Custom(typ) = &origin.value.read().unwrap().typ else {
+            panic!("expected a custom type");
+        };
+        assert!(std::sync::Arc::ptr_eq(typ, &point.value));
+
+        let failing = [
+            // Private types stay in their file.
+            (
+                "prv struct Point { x: i32 }",
+                "prv let p: Point = Point { x: 0 };",
+            ),
+            // So do names defined by more than one sibling.
+            (
+                "mod struct Point { x: i32 }",
+                "prv let p: Point = Point { x: 0 };",
+            ),
+        ];
+        let (ast, _) = analyze_files(&[("a", failing[0].0), ("b", failing[0].1)]);
+        assert_eq!(ast.unwrap_err().msg, "Can't resolve type name");
+        let (ast, _) = analyze_files(&[
+            ("a", failing[1].0),
+            ("b", failing[1].0),
+            ("c", failing[1].1),
+        ]);
+        assert_eq!(ast.unwrap_err().msg, "Can't resolve type name");
+    }
+
+    #[test]
+    fn shift_folding() {
+        let (ast, pool) = analyze(
+            "prv let a: u8 = 1 << 7;
+             prv let b: i64 = -1 >> 63;
+             prv let c: i32 = 1 << 31;
+             prv let d: i8 = 3 << 7;
+             prv let e: u8 = 255 << 4;",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "a", |value| {
+            assert!(matches!(
+                value.value,
+                ExpressionValue::Literal(Literal::UInt(128))
+            ));
+        });
+        global_value(&ast, &pool, "b", |value| {
+            assert!(matches!(
+                value.value,
+                ExpressionValue::Literal(Literal::Int(-1))
+            ));
+        });
+        assert_eq!(
+            error_at("prv let a: u8 = 1 << 8;"),
+            ("Shift amount out of range", (2, 22, 1))
+        );
+        assert_eq!(
+            error_at("prv fn f(x: u32) -> u32 { return x >> -1; }"),
+            ("Shift amount out of range", (2, 39, 2))
+        );
+        // Shifting into the sign bit or past the top drops the bits shifted out.
+        let expected = [
+            ("c", Literal::Int(i32::MIN as i64)),
+            ("d", Literal::Int(-128)),
+            ("e", Literal::UInt(0xf0)),
+        ];
+        for (name, literal) in expected {
+            global_value(&ast, &pool, name, |value| {
+                assert_eq!(value.value, ExpressionValue::Literal(literal));
+            });
+        }
+    }
+
+    #[test]
+    fn binding_scopes() {
+        let shape = "prv enum Shape { Circle(f64), Rect(i32, i32) }\n";
+        let (ast, _) = analyze(&format!(
+            "{shape}prv fn f(s: Shape) {{
+                 match (s) {{
+                     Shape::Circle(r) => {{ let x: f64 = r * 2.0; }}
+                     Shape::Rect(w, h) => {{ let y: i32 = w + h; }}
+                 }}
+             }}"
+        ));
+        ast.unwrap();
+        assert_eq!(
+            error_at(&format!(
+                "{shape}prv fn f(s: Shape) {{
+                 match (s) {{ Shape::Circle(r) => {{ let x: i32 = r; }} _ => {{}} }}
+             }}"
+            )),
+            ("Mismatched types", (4, 65, 1))
+        );
+        // Bindings only live in their own arm.
+        assert_eq!(
+            error_at(&format!(
+                "{shape}prv fn f(s: Shape) {{
+                 match (s) {{ Shape::Circle(r) => {{}} _ => {{ let x: f64 = r; }} }}
+             }}"
+            ))
+            .0,
+            "Can't resolve name"
+        );
+    }
+
+    #[test]
+    fn array_literal_inference() {
+        let (ast, pool) = analyze(
+            "prv let a: []let i32 = {};
+             prv let b: []let u8 = {1, 2, 3};
+             prv let c: i32 = {1, 2, 3}[1];",
+        );
+        let ast = ast.unwrap();
+        let slice = |inner| Type::Slice {
+            inner: Box::new(inner),
+            mutable: false,
+        };
+        global_value(&ast, &pool, "a", |value| {
+            assert!(
+                matches!(&value.value, ExpressionValue::Literal(Literal::Array(v)) if v.is_empty())
+            );
+            assert_eq!(value.typ, slice(Type::I32));
+        });
+        global_value(&ast, &pool, "b", |value| {
+            let ExpressionValue::Literal(Literal::Array(elements)) = &value.value else {
+                panic!("expected an array literal");
+            };
+            assert!(elements.iter().all(|element| element.typ == Type::U8));
+            assert_eq!(value.typ, slice(Type::U8));
+        });
+        global_value(&ast, &pool, "c", |value| {
+            let ExpressionValue::Binary(Binary { left, .. }) = &value.value else {
+                panic!("expected indexing");
+            };
+            assert_eq!(
+                left.typ,
+                Type::Array {
+                    inner: Box::new(Type::I32),
+                    size: 3,
+                    mutable: false,
+                }
+            );
+        });
+
+        let (ast, pool) = analyze(
+            "prv use MySlice = []let i32;
+             prv use Bytes = [2]let u8;
+             prv let a: MySlice = {};
+             prv let b: Bytes = {1, 2};",
+        );
+        let ast = ast.unwrap();
+        global_value(&ast, &pool, "a", |value| {
+            assert_eq!(value.typ, slice(Type::I32))
+        });
+        global_value(&ast, &pool, "b", |value| {
+            let ExpressionValue::Literal(Literal::Array(elements)) = &value.value else {
+                panic!("expected an array literal");
+            };
+            assert!(elements.iter().all(|element| element.typ == Type::U8));
+        });
+
+        assert_eq!(
+            error_at("prv let c: i32 = {1, true}[0];"),
+            ("Array elements have different types", (2, 22, 4))
+        );
+        assert_eq!(
+            error_at("prv let a: []let bool = {true, 1};"),
+            ("Mismatched types", (2, 32, 1))
+        );
+        assert_eq!(
+            error_at("prv let n: u64 = {}.len;"),
+            ("Can't infer the type of an empty array literal", (2, 18, 2))
+        );
+    }
+
+    #[test]
+    fn assignments_through_aliases() {
+        let (ast, _) = analyze(
+            "prv use Ints = []var i32;
+             prv use Ptr = *var i32;
+             prv fn f(s: Ints, p: Ptr) { s[0] = 1; *p = 2; }",
+        );
+        assert!(ast.is_ok());
+        assert_eq!(
+            error_at("prv use Ints = []let i32;\nprv fn f(s: Ints) { s[0] = 1; }").0,
+            "Assigning to an immutable value"
+        );
+        assert_eq!(
+            error_at(
+                "prv struct Point { x: i32 }
+                 prv use PointRef = *let Point;
+                 prv fn f(p: PointRef) { p.x = 1; }"
+            )
+            .0,
+            "Assigning to an immutable value"
+        );
+        assert_eq!(
+            error_at("prv fn f(s: []var i32) { s.len = 3; }").0,
+            "Invalid assignment target"
+        );
+    }
+
+    #[test]
+    fn poisoned_globals() {
+        // Only the global's own error is reported, not one for what inlines it.
+        let (diagnostics, _) = diagnose(
+            "prv let SCALE: f32 = missing;
+             prv let DOUBLE: f32 = SCALE * 2.0;
+             prv var scaled: f32 = DOUBLE;
+             prv struct Scaled { by: f32 = DOUBLE }",
+        );
+        let errors: Vec<_> = diagnostics
+            .errors
+            .iter()
+            .map(|e| (e.msg, e.span.line))
+            .collect();
+        assert_eq!(errors, [("Can't resolve name", 2)]);
+
+        // Structs aren't constants, so a broken default is still reported.
+        let (diagnostics, _) = diagnose(
+            "prv struct Point { x: i32 = true }
+             prv let origin: Point = Point {};",
+        );
+        let errors: Vec<_> = diagnostics.errors.iter().map(|e| e.msg).collect();
+        assert_eq!(errors, ["Mismatched types"]);
+    }
+
+    #[test]
+    fn mutable_pointers() {
+        let (ast, _) = analyze(
+            "prv use Ptr = *var i32;
+             prv fn f(n: i32) {
+                 var x: i32 = 1;
+                 let y: i32 = 2;
+                 let p: *var i32 = &x;
+                 let q: Ptr = &n;
+                 let r: *let i32 = &y;
+                 *p = *r;
+             }",
+        );
+        assert!(ast.is_ok());
+        let failing = [
+            "prv fn f() { let x: i32 = 1; let p: *var i32 = &x; *p = 2; }",
+            "prv use Ptr = *var i32;\nprv fn f() { let x: i32 = 1; let p: Ptr = &x; }",
+            "prv fn f(s: []let i32) { let p: *var i32 = &s[0]; }",
+        ];
+        for code in failing {
+            assert_eq!(
+                error_at(code).0,
+                "Taking a mutable pointer to an immutable value"
+            );
+        }
+    }
+
+    #[test]
+    fn access_through_aliases() {
+        let (ast, pool) = analyze(
+            "prv struct P { x: i32 }
+             prv use Q = P;
+             prv use Pair = (i32, bool);
+             prv fn get(q: Q) -> i32 { return q.x; }
+             prv fn set(q: *var Q) { q.x = 1; }
+             prv fn pair() -> Pair { return (1, true); }
+             prv let second: bool = pair().1;",
+        );
+        global_value(&ast.unwrap(), &pool, "second", |value| {
+            assert_eq!(value.typ, Type::Bool);
+        });
+        assert_eq!(
+            error_at("prv use Pair = (i32, bool);\nprv fn f(p: Pair) -> i32 { return p.2; }").0,
+            "No such tuple element"
+        );
+        assert_eq!(
+            error_at(
+                "prv struct P { x: i32 }\nprv use Q = P;\nprv fn f(q: Q) -> i32 { return q.y; }"
+            )
+            .0,
+            "No such field"
+        );
+    }
+}
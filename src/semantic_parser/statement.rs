@@ -0,0 +1,717 @@
+use super::*;
+use crate::semantic_ast::{
+    Assignment, Break, Conditional, ConditionalBranch, Loop, Match, MatchArm, MatchExpression,
+    Statement, ValueBlock,
+};
+use syntax_ast::{AssignmentType, BinaryOp, UnaryOp};
+
+impl Resolver<'_> {
+    /// Resolves a block of statements in a new scope.
+    pub(super) fn resolve_block(
+        &mut self,
+        block: &[syntax_ast::Statement],
+    ) -> Result<Vec<Statement>, Error> {
+        self.scopes.push(HashMap::new());
+        let statements = block
+            .iter()
+            .map(|statement| self.resolve_statement(statement))
+            .collect();
+        self.scopes.pop();
+        statements
+    }
+
+    fn resolve_statement(&mut self, statement: &syntax_ast::Statement) -> Result<Statement, Error> {
+        Ok(match statement {
+            syntax_ast::Statement::Declaration(declaration) => {
+                Statement::Declaration(self.resolve_declaration(declaration)?)
+            }
+            syntax_ast::Statement::Assignment(assignment) => {
+                Statement::Assignment(self.resolve_assignment(assignment)?)
+            }
+            syntax_ast::Statement::Expression(exp) => {
+                Statement::Expression(self.resolve_expression(exp, None)?)
+            }
+            syntax_ast::Statement::Loop(syn_loop) => Statement::Loop(self.resolve_loop(syn_loop)?),
+            syntax_ast::Statement::Continue(span) => {
+                self.expect_loop("`continue` outside of a loop", *span)?;
+                Statement::Continue(*span)
+            }
+            syntax_ast::Statement::Break(syn_break) => {
+                Statement::Break(self.resolve_break(syn_break)?)
+            }
+            syntax_ast::Statement::Conditional(conditional) => {
+                Statement::Conditional(self.resolve_conditional(conditional)?)
+            }
+            syntax_ast::Statement::Match(syn_match) => {
+                Statement::Match(self.resolve_match(syn_match)?)
+            }
+            syntax_ast::Statement::Return(return_) => {
+                Statement::Return(self.resolve_return(return_)?)
+            }
+            syntax_ast::Statement::Block(block) => Statement::Block(self.resolve_block(block)?),
+        })
+    }
+
+    fn resolve_declaration(
+        &mut self,
+        declaration: &syntax_ast::Declaration,
+    ) -> Result<RwArc<Declaration>, Error> {
+        let typ = resolve_type_annot(self.file, self.pool, &declaration.typ)?;
+        // Folded right away so that later array sizes can inline the value.
+        let value = self.resolve_typed_expression(&declaration.value, &typ)?;
+        let value = fold_constants(value, self.pool)?;
+        let sem_declaration = RwArc::new(Declaration {
+            name: declaration.name,
+            mutable: declaration.mutable,
+            typ,
+            value,
+            span: declaration.span,
+            poisoned: false,
+        });
+        let (scope, outer) = self.scopes.split_last_mut().unwrap();
+        if !scope.contains_key(&declaration.name)
+            && outer.iter().any(|s| s.contains_key(&declaration.name))
+        {
+            self.warnings.push(Warning {
+                typ: WarningType::Shadowing,
+                msg: "Declaration shadows an outer variable",
+                span: declaration.span,
+            });
+        }
+        let identifier = Identifier::Declaraction(sem_declaration.clone());
+        if scope.insert(declaration.name, identifier).is_some() {
+            return Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Duplicated variable name",
+                span: declaration.span,
+            });
+        }
+        Ok(sem_declaration)
+    }
+
+    fn resolve_assignment(
+        &mut self,
+        assignment: &syntax_ast::Assignment,
+    ) -> Result<Assignment, Error> {
+        let left = self.resolve_expression(&assignment.left, None)?;
+        match place_mutability(&left) {
+            None => {
+                return Err(Error {
+                    typ: ErrorType::Statement,
+                    msg: "Invalid assignment target",
+                    span: left.span,
+                });
+            }
+            Some(false) => {
+                return Err(Error {
+                    typ: ErrorType::Statement,
+                    msg: "Assigning to an immutable value",
+                    span: assignment.span,
+                });
+            }
+            Some(true) => {}
+        }
+        let right = match assignment.typ {
+            AssignmentType::LeftShift | AssignmentType::RightShift => {
+                self.resolve_expression(&assignment.right, None)?
+            }
+            _ => self.resolve_typed_expression(&assignment.right, &left.typ)?,
+        };
+        // The operand that doesn't fit the operator, if any.
+        let invalid = match assignment.typ {
+            AssignmentType::Assign => None,
+            AssignmentType::Plus
+            | AssignmentType::Minus
+            | AssignmentType::Mul
+            | AssignmentType::Div
+            | AssignmentType::Mod => (!left.typ.is_numeric()).then_some(&left),
+            AssignmentType::LeftShift | AssignmentType::RightShift => [&left, &right]
+                .into_iter()
+                .find(|exp| !exp.typ.is_integer()),
+            AssignmentType::BitAnd | AssignmentType::BitOr | AssignmentType::BitXor => {
+                (!left.typ.is_integer()).then_some(&left)
+            }
+        };
+        if let Some(operand) = invalid {
+            return Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Invalid operand types for the assignment operator",
+                span: operand.span,
+            });
+        }
+        Ok(Assignment {
+            left,
+            right,
+            typ: assignment.typ,
+            span: assignment.span,
+        })
+    }
+
+    fn resolve_loop(&mut self, syn_loop: &syntax_ast::Loop) -> Result<Loop, Error> {
+        self.scopes.push(HashMap::new());
+        let init = syn_loop
+            .init
+            .iter()
+            .map(|declaration| self.resolve_declaration(declaration))
+            .collect::<Result<_, _>>()?;
+        let condition = match &syn_loop.condition {
+            Some(condition) => Some(self.resolve_typed_expression(condition, &Type::Bool)?),
+            None => None,
+        };
+        let update = syn_loop
+            .update
+            .iter()
+            .map(|statement| self.resolve_statement(statement))
+            .collect::<Result<_, _>>()?;
+        self.loops.push(None);
+        let body = self.resolve_block(&syn_loop.body);
+        self.loops.pop();
+        self.scopes.pop();
+        Ok(Loop {
+            init,
+            condition,
+            update,
+            body: body?,
+        })
+    }
+
+    /// A loop expression has the type of its `break`s. Without any, it never
+    ///     ends, so it's given the expected type or unit.
+    pub(super) fn resolve_loop_expression(
+        &mut self,
+        body: &[syntax_ast::Statement],
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        self.loops.push(Some(LoopValue {
+            hint: expected.cloned(),
+            typ: None,
+        }));
+        let body = self.resolve_block(body);
+        let value = self.loops.pop().unwrap().unwrap();
+        Ok(Expression {
+            value: ExpressionValue::Loop(body?),
+            typ: value.typ.or(value.hint).unwrap_or(Type::Unit),
+            span,
+        })
+    }
+
+    /// Only loops are targeted by `break` and `continue`, so inside a match
+    ///     they refer to the loop around it.
+    fn expect_loop(&self, msg: &'static str, span: Span) -> Result<(), Error> {
+        if self.loops.is_empty() {
+            return Err(Error {
+                typ: ErrorType::Statement,
+                msg,
+                span,
+            });
+        }
+        Ok(())
+    }
+
+    fn resolve_break(&mut self, syn_break: &syntax_ast::Break) -> Result<Break, Error> {
+        self.expect_loop("`break` outside of a loop", syn_break.span)?;
+        let hint = match self.loops.last() {
+            Some(Some(value)) => value.typ.clone().or(value.hint.clone()),
+            _ => None,
+        };
+        let value = match &syn_break.value {
+            Some(value) => Some(self.resolve_expression(value, hint.as_ref())?),
+            None => None,
+        };
+        let typ = value.as_ref().map_or(Type::Unit, |value| value.typ.clone());
+        match self.loops.last_mut() {
+            Some(Some(loop_value)) => match &loop_value.typ {
+                Some(expected) if *expected != typ => {
+                    return Err(Error {
+                        typ: ErrorType::Type,
+                        msg: "Mismatched break types",
+                        span: syn_break.span,
+                    });
+                }
+                Some(_) => {}
+                None => loop_value.typ = Some(typ),
+            },
+            _ if value.is_some() => {
+                return Err(Error {
+                    typ: ErrorType::Statement,
+                    msg: "Only `loop` expressions can break with a value",
+                    span: syn_break.span,
+                });
+            }
+            _ => {}
+        }
+        Ok(Break {
+            value,
+            span: syn_break.span,
+        })
+    }
+
+    fn resolve_conditional(
+        &mut self,
+        conditional: &syntax_ast::Conditional,
+    ) -> Result<Conditional, Error> {
+        let if_branch = self.resolve_branch(&conditional.if_branch, &Type::Bool)?;
+        let elif_branches = conditional
+            .elif_branches
+            .iter()
+            .map(|branch| self.resolve_branch(branch, &Type::Bool))
+            .collect::<Result<_, _>>()?;
+        let else_branch = match &conditional.else_branch {
+            Some(block) => Some(self.resolve_block(block)?),
+            None => None,
+        };
+        Ok(Conditional {
+            if_branch,
+            elif_branches,
+            else_branch,
+        })
+    }
+
+    /// Resolves a branch whose condition must be of the given type.
+    fn resolve_branch(
+        &mut self,
+        branch: &syntax_ast::ConditionalBranch,
+        typ: &Type,
+    ) -> Result<ConditionalBranch, Error> {
+        Ok(ConditionalBranch {
+            condition: self.resolve_typed_expression(&branch.condition, typ)?,
+            body: self.resolve_block(&branch.body)?,
+        })
+    }
+
+    /// Case conditions are folded right away, so that `!false` covers `true`
+    ///     when checking whether a match is exhaustive.
+    fn resolve_case_condition(
+        &mut self,
+        condition: &syntax_ast::Expression,
+        typ: &Type,
+    ) -> Result<Expression, Error> {
+        let condition = self.resolve_typed_expression(condition, typ)?;
+        fold_constants(condition, self.pool)
+    }
+
+    /// A match over a bool or an enum must cover every value, unless
+    ///     there's a default. Other matches may leave values unhandled.
+    fn resolve_match(&mut self, syn_match: &syntax_ast::Match) -> Result<Match, Error> {
+        let value = self.resolve_expression(&syn_match.value, None)?;
+        let tagged = tagged_enum(&value.typ);
+        let cases: Vec<_> = syn_match
+            .cases
+            .iter()
+            .map(|case| match &tagged {
+                Some(def) => self.resolve_pattern_branch(case, def),
+                None => Ok(ConditionalBranch {
+                    condition: self.resolve_case_condition(&case.condition, &value.typ)?,
+                    body: self.resolve_block(&case.body)?,
+                }),
+            })
+            .collect::<Result<_, _>>()?;
+        let default = match &syn_match.default {
+            Some(block) => Some(self.resolve_block(block)?),
+            None => None,
+        };
+        if default.is_none()
+            && let Some(missing) =
+                missing_cases(&value.typ, cases.iter().map(|case| &case.condition))
+            && !missing.is_empty()
+        {
+            return Err(non_exhaustive(
+                "Match isn't exhaustive",
+                missing,
+                syn_match.value.span,
+            ));
+        }
+        Ok(Match {
+            value,
+            cases,
+            default,
+        })
+    }
+
+    /// The first arm decides the type of the match, or the expected type
+    ///     if the arm's value can be of it. Since a value must be produced,
+    ///     the match must be exhaustive.
+    pub(super) fn resolve_match_expression(
+        &mut self,
+        syn_match: &syntax_ast::MatchExpression,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Expression, Error> {
+        let value = self.resolve_expression(&syn_match.value, None)?;
+        let tagged = tagged_enum(&value.typ);
+        let mut typ = None;
+        let mut cases = Vec::with_capacity(syn_match.cases.len());
+        for case in syn_match.cases.iter() {
+            let Some(def) = &tagged else {
+                let condition = self.resolve_case_condition(&case.condition, &value.typ)?;
+                let body = self.resolve_value_block(&case.body, &mut typ, expected)?;
+                cases.push(MatchArm { condition, body });
+                continue;
+            };
+            let (condition, bindings) = self.resolve_pattern(&case.condition, def)?;
+            let mut body = self.with_bindings(&bindings, |resolver| {
+                resolver.resolve_value_block(&case.body, &mut typ, expected)
+            })?;
+            let bindings = bindings.into_iter().map(Statement::Declaration);
+            body.statements.splice(0..0, bindings);
+            cases.push(MatchArm { condition, body });
+        }
+        let default = match &syn_match.default {
+            Some(block) => Some(self.resolve_value_block(block, &mut typ, expected)?),
+            None => None,
+        };
+        if default.is_none() {
+            let conditions = cases.iter().map(|case| &case.condition);
+            match missing_cases(&value.typ, conditions) {
+                Some(missing) if missing.is_empty() => {}
+                Some(missing) => {
+                    return Err(non_exhaustive(
+                        "Match expression isn't exhaustive",
+                        missing,
+                        span,
+                    ));
+                }
+                None => {
+                    return Err(Error {
+                        typ: ErrorType::Statement,
+                        msg: "Match expression needs a default arm",
+                        span,
+                    });
+                }
+            }
+        }
+        Ok(Expression {
+            value: ExpressionValue::Match(Box::new(MatchExpression {
+                value,
+                cases,
+                default,
+            })),
+            typ: typ.or(expected.cloned()).unwrap_or(Type::Unit),
+            span,
+        })
+    }
+
+    /// Resolves a branch of a match over a tagged enum. The bindings of the
+    ///     pattern are declared at the start of the body.
+    fn resolve_pattern_branch(
+        &mut self,
+        branch: &syntax_ast::ConditionalBranch,
+        def: &RwArc<TypeDef>,
+    ) -> Result<ConditionalBranch, Error> {
+        let (condition, bindings) = self.resolve_pattern(&branch.condition, def)?;
+        let mut body =
+            self.with_bindings(&bindings, |resolver| resolver.resolve_block(&branch.body))?;
+        body.splice(0..0, bindings.into_iter().map(Statement::Declaration));
+        Ok(ConditionalBranch { condition, body })
+    }
+
+    /// A pattern is a variant of the matched enum, qualified or not,
+    ///     optionally followed by the names its payload is bound to, like
+    ///     `Circle(r)`. `_` skips an element, and a variant without names
+    ///     skips the whole payload. The condition is the variant itself.
+    fn resolve_pattern(
+        &mut self,
+        pattern: &syntax_ast::Expression,
+        def: &RwArc<TypeDef>,
+    ) -> Result<(Expression, Vec<RwArc<Declaration>>), Error> {
+        let (name, names) = match &pattern.value {
+            syntax_ast::ExpressionValue::Identifier(name) => (name, &[][..]),
+            syntax_ast::ExpressionValue::Call(call) => match &call.function.value {
+                syntax_ast::ExpressionValue::Identifier(name) => (name, &call.args[..]),
+                _ => return Err(expected_pattern(pattern.span)),
+            },
+            _ => return Err(expected_pattern(pattern.span)),
+        };
+        let variant = self.pattern_variant(name, def, pattern.span)?;
+        let guard = def.read().unwrap();
+        let TypeDefBody::TaggedEnum(tagged) = &guard.body else {
+            unreachable!("patterns are only resolved for tagged enums");
+        };
+        let payload = &tagged.payloads[&variant];
+        if !names.is_empty() && names.len() != payload.len() {
+            return Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Wrong number of bindings",
+                span: pattern.span,
+            });
+        }
+        let mut bindings: Vec<RwArc<Declaration>> = Vec::with_capacity(names.len());
+        for (index, (name, typ)) in names.iter().zip(payload.iter()).enumerate() {
+            let binding = match &name.value {
+                syntax_ast::ExpressionValue::Identifier(binding) if binding.len() == 1 => {
+                    binding[0]
+                }
+                _ => {
+                    return Err(Error {
+                        typ: ErrorType::Statement,
+                        msg: "Expected a binding name",
+                        span: name.span,
+                    });
+                }
+            };
+            if Some(binding) == self.pool.search_symbol("_") {
+                continue;
+            }
+            if bindings.iter().any(|b| b.read().unwrap().name == binding) {
+                return Err(Error {
+                    typ: ErrorType::Statement,
+                    msg: "Duplicated variable name",
+                    span: name.span,
+                });
+            }
+            bindings.push(RwArc::new(Declaration {
+                name: binding,
+                mutable: false,
+                typ: typ.clone(),
+                value: Expression {
+                    value: ExpressionValue::Identifier(Identifier::Payload(index)),
+                    typ: typ.clone(),
+                    span: name.span,
+                },
+                span: name.span,
+                poisoned: false,
+            }));
+        }
+        drop(guard);
+        let condition = Expression {
+            value: ExpressionValue::Identifier(Identifier::EnumVariant(def.clone(), variant)),
+            typ: Type::Custom(def.clone()),
+            span: pattern.span,
+        };
+        Ok((condition, bindings))
+    }
+
+    /// An unqualified name is looked up among the variants of the matched enum.
+    fn pattern_variant(
+        &self,
+        name: &syntax_ast::Name,
+        def: &RwArc<TypeDef>,
+        span: Span,
+    ) -> Result<SymbolId, Error> {
+        if let [variant] = name[..] {
+            if def
+                .read()
+                .unwrap()
+                .body
+                .as_enum()
+                .unwrap()
+                .variants
+                .contains_key(&variant)
+            {
+                return Ok(variant);
+            }
+        } else if let Some(Identifier::EnumVariant(typ, variant)) = self.enum_variant(name, span)?
+            && Type::Custom(typ) == Type::Custom(def.clone())
+        {
+            return Ok(variant);
+        }
+        Err(Error {
+            typ: ErrorType::Statement,
+            msg: "Not a variant of the matched enum",
+            span,
+        })
+    }
+
+    /// Runs f in a new scope holding the bindings of a pattern.
+    fn with_bindings<T>(
+        &mut self,
+        bindings: &[RwArc<Declaration>],
+        f: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let scope = bindings
+            .iter()
+            .map(|binding| {
+                let name = binding.read().unwrap().name;
+                (name, Identifier::Declaraction(binding.clone()))
+            })
+            .collect();
+        self.scopes.push(scope);
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// Resolves an arm of a match expression. The type of the first arm
+    ///     is stored in `typ`, and the rest must have the same.
+    fn resolve_value_block(
+        &mut self,
+        block: &syntax_ast::ValueBlock,
+        typ: &mut Option<Type>,
+        expected: Option<&Type>,
+    ) -> Result<ValueBlock, Error> {
+        self.scopes.push(HashMap::new());
+        let body = self.resolve_value_block_body(block, typ.as_ref().or(expected));
+        self.scopes.pop();
+        let body = body?;
+        match typ {
+            Some(typ) if *typ != body.value.typ => Err(Error {
+                typ: ErrorType::Type,
+                msg: "Mismatched match arm types",
+                span: body.value.span,
+            }),
+            Some(_) => Ok(body),
+            None => {
+                *typ = Some(body.value.typ.clone());
+                Ok(body)
+            }
+        }
+    }
+
+    fn resolve_value_block_body(
+        &mut self,
+        block: &syntax_ast::ValueBlock,
+        hint: Option<&Type>,
+    ) -> Result<ValueBlock, Error> {
+        let statements = block
+            .statements
+            .iter()
+            .map(|statement| self.resolve_statement(statement))
+            .collect::<Result<_, _>>()?;
+        Ok(ValueBlock {
+            statements,
+            value: self.resolve_expression(&block.value, hint)?,
+        })
+    }
+
+    /// A bare `return` is only allowed in a function without a return type,
+    ///     and a value is only allowed in one with a return type.
+    fn resolve_return(
+        &mut self,
+        return_: &syntax_ast::Return,
+    ) -> Result<Option<Expression>, Error> {
+        match &return_.value {
+            None if self.return_type != Type::Unit => Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Missing return value",
+                span: return_.span,
+            }),
+            None => Ok(None),
+            Some(exp) if self.return_type == Type::Unit => Err(Error {
+                typ: ErrorType::Statement,
+                msg: "Returning a value from a function without a return type",
+                span: exp.span,
+            }),
+            Some(exp) => {
+                let return_type = self.return_type.clone();
+                Ok(Some(self.resolve_typed_expression(exp, &return_type)?))
+            }
+        }
+    }
+}
+
+/// Whether the memory location an expression refers to can be assigned to.
+/// None if it isn't a memory location. `var` declarations are mutable and
+///     `let` ones aren't. Arguments are the function's own copies, so they
+///     can be. Through a pointer or a slice, it's up to its `var` or `let`.
+///     Fields and array elements are stored in their parent, so they need
+///     it to be mutable too. A slice's `.len` isn't stored anywhere.
+pub(super) fn place_mutability(exp: &Expression) -> Option<bool> {
+    match &exp.value {
+        ExpressionValue::Identifier(Identifier::Declaraction(declaration)) => {
+            Some(declaration.read().unwrap().mutable)
+        }
+        ExpressionValue::Identifier(Identifier::Argument(_)) => Some(true),
+        ExpressionValue::Unary(unary) if unary.op == UnaryOp::Dereference => {
+            match unary.operand.typ.resolve_alias() {
+                Type::Pointer { mutable, .. } => Some(mutable),
+                _ => None,
+            }
+        }
+        ExpressionValue::Binary(binary) => match (binary.op, binary.left.typ.resolve_alias()) {
+            (BinaryOp::Indexing, Type::Slice { mutable, .. }) => Some(mutable),
+            (BinaryOp::Indexing, Type::Array { mutable, .. }) => {
+                place_mutability(&binary.left).map(|parent| parent && mutable)
+            }
+            (BinaryOp::FieldAccess, _)
+                if matches!(
+                    binary.right.value,
+                    ExpressionValue::Identifier(Identifier::Length)
+                ) =>
+            {
+                None
+            }
+            (BinaryOp::FieldAccess, _) => place_mutability(&binary.left),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The values of the type not covered by the conditions, if they can be
+///     told apart. Only bools and enums can be covered, by `true` and
+///     `false` or by every variant, so other types give None.
+/// Aliases are followed, so an alias of an enum is covered the same way.
+fn missing_cases<'a>(
+    typ: &Type,
+    conditions: impl Iterator<Item = &'a Expression> + Clone,
+) -> Option<Vec<SymbolId>> {
+    match typ.resolve_alias() {
+        Type::Bool => Some(
+            [TokenType::True, TokenType::False]
+                .into_iter()
+                .filter(|&keyword| {
+                    !conditions.clone().any(|condition| {
+                        matches!(
+                            condition.value,
+                            ExpressionValue::Literal(Literal::Bool(value))
+                                if value == (keyword == TokenType::True)
+                        )
+                    })
+                })
+                .map(intern_pool::get_token_type_symbol_id)
+                .collect(),
+        ),
+        Type::Custom(def) => {
+            let guard = def.read().unwrap();
+            let enum_ = guard.body.as_enum()?;
+            let mut variants: Vec<_> = enum_.variants.iter().collect();
+            variants.sort_by_key(|(_, value)| **value);
+            Some(
+                variants
+                    .into_iter()
+                    .map(|(variant, _)| *variant)
+                    .filter(|variant| {
+                        !conditions.clone().any(|condition| {
+                            matches!(
+                                &condition.value,
+                                ExpressionValue::Identifier(Identifier::EnumVariant(_, found))
+                                    if found == variant
+                            )
+                        })
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// The definition of a tagged enum type, or of what an alias of one stands for,
+///     whose matches take patterns.
+fn tagged_enum(typ: &Type) -> Option<RwArc<TypeDef>> {
+    match typ.resolve_alias() {
+        Type::Custom(def) if matches!(def.read().unwrap().body, TypeDefBody::TaggedEnum(_)) => {
+            Some(def)
+        }
+        _ => None,
+    }
+}
+
+fn expected_pattern(span: Span) -> Error {
+    Error {
+        typ: ErrorType::Statement,
+        msg: "Expected a variant pattern",
+        span,
+    }
+}
+
+fn non_exhaustive(msg: &'static str, missing: Vec<SymbolId>, span: Span) -> Error {
+    Error {
+        typ: ErrorType::NonExhaustive {
+            missing: missing.into_boxed_slice(),
+        },
+        msg,
+        span,
+    }
+}
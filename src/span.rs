@@ -1,7 +1,9 @@
 /// This file defines Span.
-use crate::intern_pool::PathId;
+use crate::intern_pool::{InternPool, PathId};
 use serde::Serialize;
+use std::fmt;
 use std::ops::Sub;
+use std::path::PathBuf;
 
 /// A Span holds the file path and a text span within that file.
 /// So using a Span, you can locate a specific chunk of the text.
@@ -31,6 +33,76 @@ impl Span {
             size: 0,
         }
     }
+
+    /// Returns the smallest span covering both spans.
+    /// The order doesn't matter, but both spans must be in the same file.
+    pub(crate) fn merge(self, other: Span) -> Span {
+        std::debug_assert!(self.path == other.path);
+        let (start, end) = if self.index <= other.index {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Span {
+            path: start.path,
+            line: start.line,
+            column: start.column,
+            index: start.index,
+            size: (start.index + start.size).max(end.index + end.size) - start.index,
+        }
+    }
+
+    /// Returns the text the span covers in the source of its file.
+    /// Indices count chars, not bytes, so they're converted first.
+    /// Path-only spans and spans past the end cover nothing.
+    pub(crate) fn slice<'a>(&self, source: &'a str) -> &'a str {
+        let mut boundaries = source
+            .char_indices()
+            .map(|(byte, _)| byte)
+            .chain(std::iter::once(source.len()));
+        let Some(start) = boundaries.nth(self.index) else {
+            return "";
+        };
+        let end = match self.size {
+            0 => start,
+            size => boundaries.nth(size - 1).unwrap_or(source.len()),
+        };
+        &source[start..end]
+    }
+
+    /// Looks up the path of the span, so it can be debug-printed as
+    ///     `Span { foo.code:3:5, size: 2 }` instead of with a raw PathId.
+    pub(crate) fn debug_with_pool(self, pool: &mut InternPool) -> DebugWithPool {
+        DebugWithPool {
+            span: self,
+            path: pool.path_reverse_lookup(self.path),
+        }
+    }
+}
+
+/// A span with its path resolved, created by `Span::debug_with_pool`.
+/// A path missing from the pool falls back to the PathId.
+pub(crate) struct DebugWithPool {
+    span: Span,
+    path: Option<PathBuf>,
+}
+
+impl fmt::Debug for DebugWithPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Span {{ ")?;
+        match &self.path {
+            Some(path) => write!(f, "{}", path.display())?,
+            None => write!(f, "{:?}", self.span.path)?,
+        }
+        if self.span.size == 0 {
+            return write!(f, " }}");
+        }
+        write!(
+            f,
+            ":{}:{}, size: {} }}",
+            self.span.line, self.span.column, self.span.size
+        )
+    }
 }
 
 impl Sub for Span {
@@ -54,3 +126,79 @@ impl Sub for Span {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern_pool::TEST_PATH_ID;
+    use crate::lexer::Lexer;
+    use std::path::PathBuf;
+
+    fn span(line: usize, column: usize, index: usize, size: usize) -> Span {
+        Span {
+            path: TEST_PATH_ID,
+            line,
+            column,
+            index,
+            size,
+        }
+    }
+
+    #[test]
+    fn merge_forward() {
+        let merged = span(1, 1, 0, 3).merge(span(1, 7, 6, 2));
+        assert_eq!(merged, span(1, 1, 0, 8));
+    }
+
+    #[test]
+    fn merge_backward() {
+        let merged = span(1, 7, 6, 2).merge(span(1, 1, 0, 3));
+        assert_eq!(merged, span(1, 1, 0, 8));
+    }
+
+    #[test]
+    fn merge_across_lines() {
+        let merged = span(1, 5, 4, 1).merge(span(3, 2, 20, 4));
+        assert_eq!(merged, span(1, 5, 4, 20));
+    }
+
+    #[test]
+    fn slice_tokens() {
+        let source = "let café: []let u8 = \"naïve \\\"ü\\\"\";";
+        let mut pool = InternPool::new();
+        let path = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path, source, &mut pool).unwrap();
+        assert_eq!(tokens[1].span.slice(source), "café");
+        assert_eq!(tokens[8].span.slice(source), "\"naïve \\\"ü\\\"\"");
+        assert_eq!(tokens[9].span.slice(source), ";");
+        assert_eq!(Span::path_only(path).slice(source), "");
+        assert_eq!(span(1, 1, 100, 2).slice(source), "");
+    }
+
+    #[test]
+    fn merge_overlapping() {
+        let outer = span(2, 1, 10, 10);
+        assert_eq!(outer.merge(span(2, 3, 12, 2)), outer);
+        assert_eq!(span(2, 3, 12, 2).merge(outer), outer);
+    }
+
+    #[test]
+    fn debug_with_pool() {
+        let (mut pool, ids) = InternPool::from_paths(&["foo.code"]);
+        let span = Span {
+            path: ids[0],
+            line: 3,
+            column: 5,
+            index: 20,
+            size: 2,
+        };
+        assert_eq!(
+            format!("{:?}", span.debug_with_pool(&mut pool)),
+            "Span { foo.code:3:5, size: 2 }"
+        );
+        assert_eq!(
+            format!("{:?}", Span::path_only(ids[0]).debug_with_pool(&mut pool)),
+            "Span { foo.code }"
+        );
+    }
+}
@@ -2,9 +2,11 @@ use super::*;
 use syntax_ast::{Assignment, AssignmentType};
 
 impl SyntacticParser {
+    /// Statements end with `;`, but `for` updates are followed by `,` or `)`,
+    ///     which are left for the caller.
     pub(super) fn parse_assignment_or_expression(
         &mut self,
-        end_line: bool,
+        require_semicolon: bool,
     ) -> Result<Statement, Error> {
         let start = self.peek().unwrap().span;
         let left = self.parse_expression()?;
@@ -12,16 +14,16 @@ impl SyntacticParser {
         let TokenValue::Keyword(punc) = token.value else {
             return Err(self.error(ErrorType::Statement, "Expected assignment operator"));
         };
-        if punc == TokenType::Semicolon {
-            self.advance();
+        let Some(typ) = SyntacticParser::match_assignment_type(punc) else {
+            if require_semicolon {
+                self.end_line()?;
+            }
             return Ok(Statement::Expression(left));
-        }
-        let typ = SyntacticParser::match_assignment_type(punc)
-            .ok_or(self.error(ErrorType::Statement, "Invalid expression"))?;
+        };
         self.advance();
         let right = self.parse_expression()?;
         let end = self.peek();
-        if end_line {
+        if require_semicolon {
             self.end_line()?;
         }
         let end = end.unwrap().span;
@@ -29,7 +31,7 @@ impl SyntacticParser {
             left,
             right,
             typ,
-            span: end - start,
+            span: start.merge(end),
         }))
     }
 
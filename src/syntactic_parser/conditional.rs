@@ -2,14 +2,11 @@ use super::*;
 use syntax_ast::{Conditional, ConditionalBranch};
 
 impl SyntacticParser {
-    pub(super) fn parse_conditional(
-        &mut self,
-        pool: &mut InternPool,
-    ) -> Result<Statement, Error> {
+    pub(super) fn parse_conditional(&mut self) -> Result<Statement, Error> {
         std::debug_assert!(self.is_keyword(TokenType::If));
         self.advance();
         let if_condition = self.parse_paren_exp()?;
-        let if_block = self.parse_block(pool)?;
+        let if_block = self.parse_block()?;
         let if_branch = ConditionalBranch {
             condition: if_condition,
             body: if_block,
@@ -21,13 +18,16 @@ impl SyntacticParser {
             if self.is_keyword(TokenType::If) {
                 self.advance();
                 let elif_condition = self.parse_paren_exp()?;
-                let elif_block = self.parse_block(pool)?;
+                let elif_block = self.parse_block()?;
                 elif_branches.push(ConditionalBranch {
                     condition: elif_condition,
                     body: elif_block,
                 });
+            } else if self.is_keyword(TokenType::Match) {
+                else_branch = Some(vec![self.parse_match()?]);
+                break;
             } else {
-                else_branch = Some(self.parse_block(pool)?);
+                else_branch = Some(self.parse_block()?);
                 break;
             }
         }
@@ -6,6 +6,35 @@ impl SyntacticParser {
         let start = self.peek().unwrap().span;
         let mutable = self.is_mutable()?;
         self.advance();
+        let declaration = self.parse_declarator(mutable, start)?;
+        self.end_line()?;
+        Ok(declaration)
+    }
+
+    /// Parses `let a: T = x, b: U = y;`, where every variable
+    ///     shares the same `let` or `var`.
+    pub(super) fn parse_declaration_list(&mut self) -> Result<Vec<Declaration>, Error> {
+        let mut start = self.peek().unwrap().span;
+        let mutable = self.is_mutable()?;
+        self.advance();
+        let mut declarations = Vec::new();
+        loop {
+            declarations.push(self.parse_declarator(mutable, start)?);
+            if !self.is_keyword(TokenType::Comma) {
+                break;
+            }
+            self.advance();
+            start = self
+                .expect_token(ErrorType::Declaration, "Expected an identifier")?
+                .span;
+        }
+        self.end_line()?;
+        Ok(declarations)
+    }
+
+    /// Parses the `name: T = value` part of a declaration.
+    /// The span ends at the token after the value, which is `;` or `,`.
+    fn parse_declarator(&mut self, mutable: bool, start: Span) -> Result<Declaration, Error> {
         let id = self.expect_identifier(ErrorType::Declaration, "Expected an identifier")?;
         self.advance();
         if !self.is_keyword(TokenType::Colon) {
@@ -18,15 +47,15 @@ impl SyntacticParser {
         }
         self.advance();
         let expression = self.parse_expression()?;
-        let end = self.peek();
-        self.end_line()?;
-        let end = end.unwrap().span;
+        let end = self
+            .expect_token(ErrorType::LineEnd, "`;` expected at end of line")?
+            .span;
         Ok(Declaration {
             name: id,
             typ: type_annotation,
             value: expression,
             mutable,
-            span: end - start,
+            span: start.merge(end),
         })
     }
 }
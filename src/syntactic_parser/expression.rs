@@ -1,6 +1,17 @@
 use super::*;
 use std::collections::HashMap;
-use syntax_ast::{Binary, BinaryOp, Call, Expression, ExpressionValue, Unary, UnaryOp};
+use syntax_ast::{
+    Binary, BinaryOp, Call, Cast, Expression, ExpressionValue, StructLiteral, TypeAnnot,
+    TypeAnnotBase, Unary, UnaryOp,
+};
+
+/// Casts bind tighter than binary operators but looser than unary ones,
+///     so `-a as u32` is `(-a) as u32` and `a + b as i64` is `a + (b as i64)`.
+const CAST_PRECEDENCE: u8 = 93;
+
+/// Postfix operators bind as tightly as field access, so `a.f(x)` calls the
+///     field `a.f` and `a.b[i]` indexes it, instead of applying to the name alone.
+const POSTFIX_PRECEDENCE: u8 = 100;
 
 impl SyntacticParser {
     pub(super) fn parse_expression(&mut self) -> Result<Expression, Error> {
@@ -9,29 +20,65 @@ impl SyntacticParser {
 
     pub(super) fn parse_paren_exp(&mut self) -> Result<Expression, Error> {
         self.expect_keyword(TokenType::OpenParen, ErrorType::Expression, "Expected `(`")?;
+        let opener = self.peek().unwrap().span;
         self.advance();
-        self.parse_paren()
+        self.parse_paren(opener)
     }
 
-    fn parse_paren(&mut self) -> Result<Expression, Error> {
+    fn parse_paren(&mut self, opener: Span) -> Result<Expression, Error> {
         let exp = self.pratt_parse(0)?;
         if !self.is_keyword(TokenType::CloseParen) {
+            self.check_unclosed(TokenType::CloseParen, opener)?;
             return Err(self.error(ErrorType::Expression, "Unclosed parenthesis"));
         }
         self.advance();
         Ok(exp)
     }
 
-    fn parse_expression_list(&mut self, end: TokenType) -> Result<Vec<Expression>, Error> {
+    /// `(a)` is a parenthesized expression, while `(a, b)` is a tuple literal.
+    fn parse_paren_or_tuple(&mut self, start: Span) -> Result<Expression, Error> {
+        let first = self.pratt_parse(0)?;
+        if !self.is_keyword(TokenType::Comma) {
+            if !self.is_keyword(TokenType::CloseParen) {
+                self.check_unclosed(TokenType::CloseParen, start)?;
+                return Err(self.error(ErrorType::Expression, "Unclosed parenthesis"));
+            }
+            self.advance();
+            return Ok(first);
+        }
+        self.advance();
+        let mut elements = vec![first];
+        elements.extend(self.parse_expression_list(TokenType::CloseParen, start)?);
+        if elements.len() < 2 {
+            return Err(Error {
+                typ: ErrorType::Expression,
+                msg: "A tuple needs at least two elements",
+                span: start.merge(self.back()?.span),
+            });
+        }
+        Ok(Expression {
+            value: ExpressionValue::Literal(syntax_ast::Literal::Tuple(elements)),
+            span: start.merge(self.back()?.span),
+        })
+    }
+
+    /// Parses expressions separated by commas until `end`, which closes `opener`.
+    fn parse_expression_list(
+        &mut self,
+        end: TokenType,
+        opener: Span,
+    ) -> Result<Vec<Expression>, Error> {
         let mut list = Vec::new();
         loop {
             if self.is_keyword(end) {
                 break;
             }
+            self.check_unclosed(end, opener)?;
             list.push(self.parse_expression()?);
             if self.is_keyword(end) {
                 break;
             }
+            self.check_unclosed(end, opener)?;
             if !self.is_keyword(TokenType::Comma) {
                 return Err(self.error(ErrorType::Expression, "Expected `,`"));
             }
@@ -41,20 +88,68 @@ impl SyntacticParser {
         Ok(list)
     }
 
+    /// Parses `{a, b, ...}` or the repeat form `{value; count}`.
     fn parse_array_literal(&mut self) -> Result<Expression, Error> {
-        let start = self.back().span;
-        let ev = ExpressionValue::Literal(syntax_ast::Literal::Array(
-            self.parse_expression_list(TokenType::CloseBracket)?,
-        ));
-        let end = self.back().span;
+        let start = self.back()?.span;
+        let literal = if self.is_keyword(TokenType::CloseBracket) || self.peek().is_none() {
+            syntax_ast::Literal::Array(self.parse_expression_list(TokenType::CloseBracket, start)?)
+        } else {
+            let first = self.parse_expression()?;
+            if self.is_keyword(TokenType::Semicolon) {
+                self.parse_array_repeat(first, start)?
+            } else {
+                let mut elements = vec![first];
+                if self.is_keyword(TokenType::Comma) {
+                    self.advance();
+                    elements.extend(self.parse_expression_list(TokenType::CloseBracket, start)?);
+                } else {
+                    self.check_unclosed(TokenType::CloseBracket, start)?;
+                    if !self.is_keyword(TokenType::CloseBracket) {
+                        return Err(self.error(ErrorType::Expression, "Expected `,`"));
+                    }
+                    self.advance();
+                }
+                syntax_ast::Literal::Array(elements)
+            }
+        };
+        let end = self.back()?.span;
         Ok(Expression {
-            value: ev,
-            span: end - start,
+            value: ExpressionValue::Literal(literal),
+            span: start.merge(end),
         })
     }
 
-    fn parse_struct_literal(&mut self) -> Result<HashMap<SymbolId, Expression>, Error> {
-        let mut ret = HashMap::new();
+    fn parse_array_repeat(
+        &mut self,
+        value: Expression,
+        opener: Span,
+    ) -> Result<syntax_ast::Literal, Error> {
+        std::debug_assert!(self.is_keyword(TokenType::Semicolon));
+        self.advance();
+        let count = self.parse_expression()?;
+        self.check_unclosed(TokenType::CloseBracket, opener)?;
+        if !self.is_keyword(TokenType::CloseBracket) {
+            return Err(self.error(ErrorType::Expression, "Expected `}`"));
+        }
+        self.advance();
+        Ok(syntax_ast::Literal::ArrayRepeat {
+            value: Box::new(value),
+            count: Box::new(count),
+        })
+    }
+
+    /// The type name has already been parsed as an identifier expression.
+    fn parse_struct_literal(&mut self, name: Expression) -> Result<StructLiteral, Error> {
+        let ExpressionValue::Identifier(name_path) = name.value else {
+            panic!("Struct literal without a type name");
+        };
+        let typ = Box::new(TypeAnnot {
+            base: TypeAnnotBase::Normal(name_path),
+            modifiers: Vec::new(),
+            span: name.span,
+        });
+        let base = self.parse_struct_base()?;
+        let mut fields = HashMap::new();
         loop {
             if self.is_keyword(TokenType::CloseBracket) {
                 break;
@@ -66,20 +161,57 @@ impl SyntacticParser {
             }
             self.advance();
             let exp = self.parse_expression()?;
-            ret.insert(field, exp);
+            if fields.insert(field, exp).is_some() {
+                return Err(self.error(ErrorType::Expression, "Duplicated field"));
+            }
             if self.is_keyword(TokenType::CloseBracket) {
                 break;
             }
             if !self.is_keyword(TokenType::Comma) {
                 return Err(self.error(ErrorType::Expression, "Expected `,`"));
             }
+            self.advance();
         }
         self.advance();
-        Ok(ret)
+        Ok(StructLiteral { typ, fields, base })
+    }
+
+    /// Parses the optional leading `..base,` of a struct literal.
+    fn parse_struct_base(&mut self) -> Result<Option<Box<Expression>>, Error> {
+        if !self.is_keyword(TokenType::DotDot) {
+            return Ok(None);
+        }
+        self.advance();
+        let base = self.parse_expression()?;
+        if !self.is_keyword(TokenType::CloseBracket) {
+            self.expect_keyword(TokenType::Comma, ErrorType::Expression, "Expected `,`")?;
+            self.advance();
+        }
+        Ok(Some(Box::new(base)))
+    }
+
+    /// A `{` following an expression is only a struct literal if the expression
+    ///     is a type name and the `{` is followed by `}`, `..` or `field:`.
+    /// Otherwise, the `{` starts a block and the expression ends before it.
+    fn is_struct_literal(&self, left: &Expression) -> bool {
+        if !matches!(left.value, ExpressionValue::Identifier(_)) {
+            return false;
+        }
+        if self.is_keyword_n(1, TokenType::CloseBracket) || self.is_keyword_n(1, TokenType::DotDot)
+        {
+            return true;
+        }
+        matches!(
+            self.peek_n(1),
+            Some(Token {
+                value: TokenValue::Identifier(_),
+                ..
+            })
+        ) && self.is_keyword_n(2, TokenType::Colon)
     }
 
     fn parse_infix_op(&mut self, punc: TokenType) -> Result<Expression, Error> {
-        let start = self.back().span;
+        let start = self.back()?.span;
         let op = match punc {
             TokenType::Minus => UnaryOp::Negate,
             TokenType::Mul => UnaryOp::Dereference,
@@ -90,11 +222,13 @@ impl SyntacticParser {
                 return Err(self.error(ErrorType::Expression, "Invalid unary operator"));
             }
         };
-        let operand = Box::new(self.pratt_parse(100)?);
-        let end = self.back().span;
+        // Binds tighter than any binary operator except field access, and casts,
+        //     so `-a.b` is `-(a.b)` and `-a * b` is `(-a) * b`.
+        let operand = Box::new(self.pratt_parse(95)?);
+        let end = self.back()?.span;
         Ok(Expression {
             value: ExpressionValue::Unary(Unary { op, operand }),
-            span: end - start,
+            span: start.merge(end),
         })
     }
 
@@ -104,34 +238,77 @@ impl SyntacticParser {
         Ok(match token.value {
             TokenValue::Identifier(_) => Expression {
                 value: ExpressionValue::Identifier(self.parse_name()?),
-                span: self.back().span - start,
+                span: start.merge(self.back()?.span),
             },
             TokenValue::Literal(literal) => {
                 self.advance();
                 Expression {
                     value: ExpressionValue::Literal(match literal {
-                        token::Literal::UInt(uint) => syntax_ast::Literal::UInt(uint),
+                        token::Literal::UInt(uint, base) => syntax_ast::Literal::UInt(uint, base),
                         token::Literal::Int(int) => syntax_ast::Literal::Int(int),
-                        token::Literal::Float(float) => syntax_ast::Literal::Float(float),
+                        token::Literal::Float(float, suffix) => {
+                            syntax_ast::Literal::Float(float, suffix)
+                        }
                         token::Literal::String(string) => syntax_ast::Literal::String(string),
                     }),
-                    span: self.back().span - start,
+                    span: start.merge(self.back()?.span),
                 }
             }
+            TokenValue::Keyword(TokenType::MatchCase) => return Err(self.stray_match_case()),
             TokenValue::Keyword(punc) => {
                 self.advance();
                 match punc {
-                    TokenType::OpenParen => self.parse_paren()?,
+                    TokenType::OpenParen => self.parse_paren_or_tuple(start)?,
                     TokenType::OpenBracket => self.parse_array_literal()?,
+                    TokenType::Loop | TokenType::Match => {
+                        self.parse_control_expression(punc, start)?
+                    }
                     TokenType::True | TokenType::False => Expression {
                         value: ExpressionValue::Literal(syntax_ast::Literal::Bool(
                             punc == TokenType::True,
                         )),
-                        span: self.back().span - start,
+                        span: start.merge(self.back()?.span),
                     },
+                    _ if punc.is_primitive() => Self::primitive_name(punc, start),
                     _ => self.parse_infix_op(punc)?,
                 }
             }
+            TokenValue::Attribute(_) => {
+                return Err(self.error(ErrorType::Expression, "Unexpected attribute"));
+            }
+        })
+    }
+
+    /// Primitive type names are keywords, but they're names in expressions too.
+    /// Kept out of `parse_prefix` so its stack frame stays small for deep nesting.
+    #[inline(never)]
+    fn primitive_name(keyword: TokenType, span: Span) -> Expression {
+        Expression {
+            value: ExpressionValue::Identifier(vec![intern_pool::get_token_type_symbol_id(
+                keyword,
+            )]),
+            span,
+        }
+    }
+
+    /// `loop` and `match` expressions. Kept out of `parse_prefix`, and in one
+    ///     arm of it, so its stack frame stays small for deep nesting.
+    fn parse_control_expression(
+        &mut self,
+        keyword: TokenType,
+        start: Span,
+    ) -> Result<Expression, Error> {
+        match keyword {
+            TokenType::Match => self.parse_match_expression(start),
+            _ => self.parse_loop_expression(start),
+        }
+    }
+
+    fn parse_loop_expression(&mut self, start: Span) -> Result<Expression, Error> {
+        let body = self.parse_block()?;
+        Ok(Expression {
+            value: ExpressionValue::Loop(body),
+            span: start.merge(self.back()?.span),
         })
     }
 
@@ -142,13 +319,19 @@ impl SyntacticParser {
         )
     }
 
+    /// Spans start at the postfix operator, except for struct literals,
+    ///     which include their type name.
     fn parse_postfix(&mut self, punc: TokenType, left: Expression) -> Result<Expression, Error> {
-        let start = self.peek().unwrap().span;
+        let opener = self.peek().unwrap().span;
+        let start = match punc {
+            TokenType::OpenBracket => left.span,
+            _ => opener,
+        };
         self.advance();
         let ev = match punc {
             TokenType::OpenParen => ExpressionValue::Call(Call {
                 function: Box::new(left),
-                args: self.parse_expression_list(TokenType::CloseParen)?,
+                args: self.parse_expression_list(TokenType::CloseParen, opener)?,
             }),
             TokenType::OpenBrace => {
                 let exp = ExpressionValue::Binary(Binary {
@@ -157,25 +340,33 @@ impl SyntacticParser {
                     right: Box::new(self.parse_expression()?),
                 });
                 if !self.is_keyword(TokenType::CloseBrace) {
+                    self.check_unclosed(TokenType::CloseBrace, opener)?;
                     return Err(self.error(ErrorType::Expression, "Expected `]`"));
                 }
                 self.advance();
                 exp
             }
-            TokenType::OpenBracket => {
-                ExpressionValue::Literal(syntax_ast::Literal::Struct(self.parse_struct_literal()?))
-            }
+            TokenType::OpenBracket => ExpressionValue::Literal(syntax_ast::Literal::Struct(
+                self.parse_struct_literal(left)?,
+            )),
             _ => panic!("Not a postfix operator"),
         };
-        let end = self.back().span;
+        let end = self.back()?.span;
         Ok(Expression {
             value: ev,
-            span: end - start,
+            span: start.merge(end),
         })
     }
 
     fn pratt_parse(&mut self, left_precedence: u8) -> Result<Expression, Error> {
+        self.nested(|parser| parser.pratt_parse_nested(left_precedence))
+    }
+
+    /// Binary operators are left-associative, except comparisons,
+    ///     which can't be chained: `a < b < c` is an error.
+    fn pratt_parse_nested(&mut self, left_precedence: u8) -> Result<Expression, Error> {
         let mut exp = self.parse_prefix()?;
+        let mut after_comparison = false;
         loop {
             let Some(token) = self.peek() else {
                 return Ok(exp);
@@ -185,32 +376,62 @@ impl SyntacticParser {
                 return Err(self.error(ErrorType::Expression, "Expected an operator"));
             };
             if SyntacticParser::is_postfix_op(punc) {
+                if POSTFIX_PRECEDENCE <= left_precedence {
+                    return Ok(exp);
+                }
+                if punc == TokenType::OpenBracket && !self.is_struct_literal(&exp) {
+                    return Ok(exp);
+                }
                 exp = self.parse_postfix(punc, exp)?;
                 continue;
             }
+            if punc == TokenType::As {
+                if CAST_PRECEDENCE <= left_precedence {
+                    return Ok(exp);
+                }
+                self.advance();
+                let typ = self.parse_type_annotation()?;
+                let span = exp.span.merge(self.back()?.span);
+                exp = Expression {
+                    value: ExpressionValue::Cast(Cast {
+                        value: Box::new(exp),
+                        typ,
+                    }),
+                    span,
+                };
+                continue;
+            }
             let Some((precedence, op)) = SyntacticParser::match_infix_operator(punc) else {
                 return Ok(exp);
             };
-            if precedence < left_precedence {
+            if precedence <= left_precedence {
                 return Ok(exp);
             }
+            let comparison = op.is_comparison();
+            if comparison && after_comparison {
+                return Err(self.error(
+                    ErrorType::Expression,
+                    "Comparison operators can't be chained",
+                ));
+            }
+            after_comparison = comparison;
             self.advance();
             let right = Box::new(self.pratt_parse(precedence)?);
-            let end = self.back().span;
+            let end = self.back()?.span;
             exp = Expression {
                 value: ExpressionValue::Binary(Binary {
                     left: Box::new(exp),
                     right,
                     op,
                 }),
-                span: end - start,
+                span: start.merge(end),
             };
         }
     }
 
     fn match_infix_operator(infix: TokenType) -> Option<(u8, BinaryOp)> {
         Some(match infix {
-            TokenType::Dot => (100, BinaryOp::FieldAccess),
+            TokenType::Dot => (POSTFIX_PRECEDENCE, BinaryOp::FieldAccess),
             TokenType::Mul => (90, BinaryOp::Mul),
             TokenType::Div => (90, BinaryOp::Div),
             TokenType::Modulo => (90, BinaryOp::Mod),
@@ -1,6 +1,6 @@
 use super::*;
 use std::collections::HashMap;
-use syntax_ast::{Declaration, File, Function, Scope, TypeDef, Visibility};
+use syntax_ast::{Attribute, Declaration, File, Function, ItemImport, Scope, TypeDef, Visibility};
 
 impl SyntacticParser {
     pub(super) fn parse_file(
@@ -9,11 +9,9 @@ impl SyntacticParser {
         module_name: SymbolId,
         pool: &mut InternPool,
     ) -> Result<File, Error> {
-        let module = self.parse_module_declaration()?;
-        if module != module_name {
-            return Err(self.error(ErrorType::Module, "Incorrect module name"));
-        }
-        let imports = self.parse_imports()?;
+        let module = self.parse_module_declaration(module_name)?;
+        let mut item_imports = HashMap::new();
+        let imports = self.parse_imports(&mut item_imports)?;
         let mut types = HashMap::new();
         let mut globals = HashMap::new();
         let mut functions = HashMap::new();
@@ -24,6 +22,7 @@ impl SyntacticParser {
             name: filename,
             module,
             imports,
+            item_imports,
             globals,
             functions,
             types,
@@ -37,37 +36,52 @@ impl SyntacticParser {
         functions: &mut HashMap<SymbolId, Scope<Function>>,
         pool: &mut InternPool,
     ) -> Result<(), Error> {
-        let visibility = self.parse_visibility()?;
+        if self.is_keyword(TokenType::Import) {
+            return Err(self.error(ErrorType::Import, "Imports must come before definitions"));
+        }
+        let (attributes, included) = self.parse_attributes(pool)?;
+        let visibility = self.parse_visibility(pool)?;
         let token = self.expect_token(ErrorType::Module, "Missing symbol definition")?;
         let TokenValue::Keyword(kw) = token.value else {
             return Err(self.error(ErrorType::Module, "Expected keyword"));
         };
         match kw {
             TokenType::Struct | TokenType::Enum | TokenType::Union | TokenType::Use => {
-                let value = self.parse_type_definition()?;
-                if types
-                    .insert(value.name, Scope { visibility, value })
-                    .is_some()
+                let value = self.parse_type_definition(attributes)?;
+                let span = value.span;
+                if included
+                    && let Some(first) = types.insert(value.name, Scope { visibility, value })
                 {
-                    return Err(self.error(ErrorType::Module, "Duplicated type name"));
+                    return Err(duplicated("Duplicated type name", first.value.span, span));
                 }
             }
+            _ if !attributes.is_empty() => {
+                return Err(Error {
+                    typ: ErrorType::Module,
+                    msg: "Attributes are only allowed on type definitions",
+                    span: attributes[0].span,
+                });
+            }
             TokenType::Let | TokenType::Var => {
                 let value = self.parse_declaration()?;
-                if globals
-                    .insert(value.name, Scope { visibility, value })
-                    .is_some()
+                let span = value.span;
+                if included
+                    && let Some(first) = globals.insert(value.name, Scope { visibility, value })
                 {
-                    return Err(self.error(ErrorType::Module, "Duplicated global name"));
+                    return Err(duplicated("Duplicated global name", first.value.span, span));
                 }
             }
             TokenType::Fn => {
                 let value = self.parse_function(pool)?;
-                if functions
-                    .insert(value.name, Scope { visibility, value })
-                    .is_some()
+                let span = value.span;
+                if included
+                    && let Some(first) = functions.insert(value.name, Scope { visibility, value })
                 {
-                    return Err(self.error(ErrorType::Module, "Duplicated function name"));
+                    return Err(duplicated(
+                        "Duplicated function name",
+                        first.value.span,
+                        span,
+                    ));
                 }
             }
             _ => {
@@ -77,10 +91,81 @@ impl SyntacticParser {
         Ok(())
     }
 
-    fn parse_visibility(&mut self) -> Result<Visibility, Error> {
+    /// Attributes come before the visibility, as in `@packed pub struct`.
+    /// `@cfg(flag)` isn't kept. It decides whether the item is included,
+    ///     which it is only if every such flag is enabled in the options.
+    ///     Excluded items are still parsed, but left out of the file.
+    fn parse_attributes(&mut self, pool: &InternPool) -> Result<(Vec<Attribute>, bool), Error> {
+        let mut attributes = Vec::new();
+        let mut included = true;
+        while let Some(Token {
+            value: TokenValue::Attribute(name),
+            span,
+        }) = self.peek()
+        {
+            self.advance();
+            if Some(name) != pool.search_symbol("cfg") {
+                let args = self.parse_attribute_args()?;
+                attributes.push(Attribute { name, span, args });
+                continue;
+            }
+            self.expect_keyword(TokenType::OpenParen, ErrorType::Module, "Expected `(`")?;
+            self.advance();
+            let flag = self
+                .is_identifier()
+                .ok_or(self.error(ErrorType::Module, "Expected a flag name"))?;
+            self.advance();
+            self.expect_keyword(TokenType::CloseParen, ErrorType::Module, "Expected `)`")?;
+            self.advance();
+            let enabled = &self.options.cfg;
+            included &= enabled
+                .iter()
+                .any(|name| pool.search_symbol(name) == Some(flag));
+        }
+        Ok((attributes, included))
+    }
+
+    /// The arguments are optional, and are names separated by commas.
+    fn parse_attribute_args(&mut self) -> Result<Vec<(SymbolId, Span)>, Error> {
+        let mut args = Vec::new();
+        if !self.is_keyword(TokenType::OpenParen) {
+            return Ok(args);
+        }
+        self.advance();
+        while let Some(arg) = self.is_identifier() {
+            args.push((arg, self.peek().unwrap().span));
+            self.advance();
+            if !self.is_keyword(TokenType::Comma) {
+                break;
+            }
+            self.advance();
+        }
+        self.expect_keyword(TokenType::CloseParen, ErrorType::Module, "Expected `)`")?;
+        self.advance();
+        Ok(args)
+    }
+
+    /// `pub` may be narrowed with a scope: `pub(super)` or `pub(module)`.
+    fn parse_visibility(&mut self, pool: &InternPool) -> Result<Visibility, Error> {
         if self.is_keyword(TokenType::Pub) {
             self.advance();
-            Ok(Visibility::Public)
+            if !self.is_keyword(TokenType::OpenParen) {
+                return Ok(Visibility::Public);
+            }
+            self.advance();
+            let visibility = if self.is_keyword(TokenType::Module) {
+                Visibility::Module
+            } else if self.is_identifier().is_some()
+                && self.is_identifier() == pool.search_symbol("super")
+            {
+                Visibility::Super
+            } else {
+                return Err(self.error(ErrorType::Module, "Unknown visibility scope"));
+            };
+            self.advance();
+            self.expect_keyword(TokenType::CloseParen, ErrorType::Module, "Expected `)`")?;
+            self.advance();
+            Ok(visibility)
         } else if self.is_keyword(TokenType::Prv) {
             self.advance();
             Ok(Visibility::Private)
@@ -92,7 +177,9 @@ impl SyntacticParser {
         }
     }
 
-    fn parse_module_declaration(&mut self) -> Result<SymbolId, Error> {
+    /// The declared module name must equal the name of the directory
+    ///     containing the file, which is passed in as `module_name`.
+    fn parse_module_declaration(&mut self, module_name: SymbolId) -> Result<SymbolId, Error> {
         if !self.is_keyword(TokenType::Module) {
             return Err(self.error(
                 ErrorType::Module,
@@ -104,33 +191,72 @@ impl SyntacticParser {
             ErrorType::Module,
             "Keyword `module` must be followed by a valid identifier",
         ))?;
+        if name != module_name {
+            return Err(self.error(
+                ErrorType::ModuleName {
+                    declared: name,
+                    expected: module_name,
+                },
+                "Module declaration doesn't match the directory name",
+            ));
+        }
         self.advance();
         self.end_line()?;
         Ok(name)
     }
 
-    fn parse_imports(&mut self) -> Result<HashMap<SymbolId, Span>, Error> {
+    /// Module imports and item imports share one namespace,
+    ///     since both bring a bare name into the file.
+    fn parse_imports(
+        &mut self,
+        item_imports: &mut HashMap<SymbolId, ItemImport>,
+    ) -> Result<HashMap<SymbolId, Span>, Error> {
         let mut imports = HashMap::new();
         while self.is_keyword(TokenType::Import) {
-            let (name, span) = self.parse_import()?;
-            if !imports.contains_key(&name) {
+            let (mut path, span) = self.parse_import()?;
+            let name = path.pop().unwrap();
+            if imports.contains_key(&name) || item_imports.contains_key(&name) {
                 return Err(self.error(ErrorType::Import, "Duplicated imports"));
             }
-            imports.insert(name, span);
+            if path.is_empty() {
+                imports.insert(name, span);
+                continue;
+            }
+            item_imports.insert(
+                name,
+                ItemImport {
+                    path,
+                    item: name,
+                    span,
+                },
+            );
         }
         Ok(imports)
     }
 
-    fn parse_import(&mut self) -> Result<(SymbolId, Span), Error> {
+    fn parse_import(&mut self) -> Result<(Name, Span), Error> {
         std::debug_assert!(self.is_keyword(TokenType::Import));
         self.advance();
-        let name = self.is_identifier().ok_or(self.error(
-            ErrorType::Import,
-            "Keyword `import` must be followed by a valid identifier",
-        ))?;
-        let span = self.peek().unwrap().span;
-        self.advance();
+        if self.is_identifier().is_none() {
+            return Err(self.error(
+                ErrorType::Import,
+                "Keyword `import` must be followed by a valid identifier",
+            ));
+        }
+        let start = self.peek().unwrap().span;
+        let name = self.parse_name()?;
+        let span = start.merge(self.back()?.span);
         self.end_line()?;
         Ok((name, span))
     }
 }
+
+fn duplicated(msg: &'static str, first: Span, span: Span) -> Error {
+    Error {
+        typ: ErrorType::Duplicated {
+            first: Box::new(first),
+        },
+        msg,
+        span,
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use syntax_ast::{Function, FunctionArg, TypeAnnot};
+use syntax_ast::{Asm, Function, FunctionArg, FunctionBody, Return, TypeAnnot};
 
 impl SyntacticParser {
     pub(super) fn parse_function(
@@ -15,7 +15,13 @@ impl SyntacticParser {
         self.advance();
         let arguments = self.parse_arguments()?;
         let return_typ = self.parse_return_type()?;
-        let body = self.parse_block(pool)?;
+        let body = if self.is_keyword(TokenType::Asm) {
+            self.parse_asm(pool)?
+        } else if self.is_keyword(TokenType::Assign) {
+            FunctionBody::Block(self.parse_expression_body(return_typ.is_some())?)
+        } else {
+            FunctionBody::Block(self.parse_block()?)
+        };
         Ok(Function {
             name,
             arguments,
@@ -26,16 +32,70 @@ impl SyntacticParser {
     }
 
     fn parse_return_type(&mut self) -> Result<Option<TypeAnnot>, Error> {
-        if self.is_keyword(TokenType::OpenBracket) {
-            return Ok(None);
-        }
         if !self.is_keyword(TokenType::ReturnType) {
-            return Err(self.error(ErrorType::Function, "Expected function name"));
+            return Ok(None);
         }
         self.advance();
         Ok(Some(self.parse_type_annotation()?))
     }
 
+    /// `fn double(x: i32) -> i32 = x * 2;` is short for a body that returns
+    ///     the expression, which needs a return type to be the value of.
+    fn parse_expression_body(&mut self, has_return_type: bool) -> Result<Vec<Statement>, Error> {
+        if !has_return_type {
+            return Err(self.error(
+                ErrorType::Function,
+                "A function returning an expression needs a return type",
+            ));
+        }
+        let start = self.peek().unwrap().span;
+        self.advance();
+        let value = self.parse_expression()?;
+        let span = start.merge(self.back()?.span);
+        self.end_line()?;
+        Ok(vec![Statement::Return(Return {
+            value: Some(value),
+            span,
+        })])
+    }
+
+    /// An asm body is one or more string literals in parentheses, each being a line:
+    /// `fn halt() asm("cli" "hlt");`
+    /// Values are passed in as operands after a `:`, referred to by position:
+    /// `fn out(port: u16, value: u8) asm("out {0}, {1}" : port, value);`
+    fn parse_asm(&mut self, pool: &InternPool) -> Result<FunctionBody, Error> {
+        std::debug_assert!(self.is_keyword(TokenType::Asm));
+        self.advance();
+        self.expect_keyword(TokenType::OpenParen, ErrorType::Function, "Expected `(`")?;
+        self.advance();
+        let mut lines = Vec::new();
+        while let Some(Token {
+            value: TokenValue::Literal(token::Literal::String(line)),
+            ..
+        }) = self.peek()
+        {
+            lines.push(pool.get_string(line));
+            self.advance();
+        }
+        if lines.is_empty() {
+            return Err(self.error(ErrorType::Function, "Expected assembly code"));
+        }
+        let code = lines.join("\n");
+        let mut operands = Vec::new();
+        if self.is_keyword(TokenType::Colon) {
+            self.advance();
+            operands.push(self.parse_expression()?);
+            while self.is_keyword(TokenType::Comma) {
+                self.advance();
+                operands.push(self.parse_expression()?);
+            }
+        }
+        self.expect_keyword(TokenType::CloseParen, ErrorType::Function, "Expected `)`")?;
+        self.advance();
+        self.end_line()?;
+        Ok(FunctionBody::Asm(Asm { code, operands }))
+    }
+
     fn parse_arguments(&mut self) -> Result<Vec<FunctionArg>, Error> {
         if !self.is_keyword(TokenType::OpenParen) {
             return Err(self.error(ErrorType::Function, "Expected argument list"));
@@ -67,11 +127,11 @@ impl SyntacticParser {
         }
         self.advance();
         let typ = self.parse_type_annotation()?;
-        let end = self.back().span;
+        let end = self.back()?.span;
         Ok(FunctionArg {
             name,
             typ,
-            span: end - start,
+            span: start.merge(end),
         })
     }
 }
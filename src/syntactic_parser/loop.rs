@@ -1,27 +1,29 @@
 use super::*;
-use syntax_ast::Loop;
+use syntax_ast::{Break, Loop};
 
 impl SyntacticParser {
-    pub(super) fn parse_loop(&mut self, pool: &mut InternPool) -> Result<Statement, Error> {
+    pub(super) fn parse_loop(&mut self) -> Result<Statement, Error> {
         Ok(Statement::Loop(if self.is_keyword(TokenType::For) {
-            self.parse_for(pool)?
+            self.parse_for()?
         } else if self.is_keyword(TokenType::While) {
-            self.parse_while(pool)?
+            self.parse_while()?
+        } else if self.is_keyword(TokenType::Loop) {
+            self.parse_bare_loop()?
         } else {
             panic!("Invalid loop keyword");
         }))
     }
 
-    fn parse_for(&mut self, pool: &mut InternPool) -> Result<Loop, Error> {
+    fn parse_for(&mut self) -> Result<Loop, Error> {
         std::debug_assert!(self.is_keyword(TokenType::For));
         self.advance();
         self.expect_keyword(TokenType::OpenParen, ErrorType::Loop, "Expected `(`")?;
         self.advance();
         let initialization = if self.is_keyword(TokenType::Semicolon) {
             self.advance();
-            None
+            Vec::new()
         } else {
-            Some(self.parse_declaration()?)
+            self.parse_declaration_list()?
         };
         let condition = if self.is_keyword(TokenType::Semicolon) {
             None
@@ -31,15 +33,16 @@ impl SyntacticParser {
         self.expect_keyword(TokenType::Semicolon, ErrorType::Loop, "Expected `;`")?;
         self.advance();
         let mut update = Vec::new();
-        if !self.is_keyword(TokenType::OpenBracket) {
+        if !self.is_keyword(TokenType::CloseParen) {
             update.push(self.parse_assignment_or_expression(false)?);
             while self.is_keyword(TokenType::Comma) {
                 self.advance();
                 update.push(self.parse_assignment_or_expression(false)?);
             }
         }
+        self.expect_keyword(TokenType::CloseParen, ErrorType::Loop, "Expected `)`")?;
         self.advance();
-        let body = self.parse_block(pool)?;
+        let body = self.parse_block()?;
         Ok(Loop {
             init: initialization,
             condition,
@@ -48,7 +51,7 @@ impl SyntacticParser {
         })
     }
 
-    fn parse_while(&mut self, pool: &mut InternPool) -> Result<Loop, Error> {
+    fn parse_while(&mut self) -> Result<Loop, Error> {
         std::debug_assert!(self.is_keyword(TokenType::While));
         self.advance();
         let condition = if !self.is_keyword(TokenType::OpenBracket) {
@@ -61,12 +64,48 @@ impl SyntacticParser {
         } else {
             None
         };
-        let body = self.parse_block(pool)?;
+        let body = self.parse_block()?;
         Ok(Loop {
             condition,
-            init: None,
+            init: Vec::new(),
             update: Vec::new(),
             body,
         })
     }
+
+    /// `loop { ... }` as a statement is the same as `while { ... }`.
+    fn parse_bare_loop(&mut self) -> Result<Loop, Error> {
+        std::debug_assert!(self.is_keyword(TokenType::Loop));
+        self.advance();
+        let body = self.parse_block()?;
+        Ok(Loop {
+            condition: None,
+            init: Vec::new(),
+            update: Vec::new(),
+            body,
+        })
+    }
+
+    pub(super) fn parse_continue(&mut self) -> Result<Statement, Error> {
+        std::debug_assert!(self.is_keyword(TokenType::Continue));
+        let span = self.peek().unwrap().span;
+        self.advance();
+        self.end_line()?;
+        Ok(Statement::Continue(span))
+    }
+
+    /// The value is only allowed in `loop` expressions, which is checked in the semantic pass.
+    pub(super) fn parse_break(&mut self) -> Result<Statement, Error> {
+        std::debug_assert!(self.is_keyword(TokenType::Break));
+        let start = self.peek().unwrap().span;
+        self.advance();
+        let value = if self.is_keyword(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        let span = start.merge(self.back()?.span);
+        self.end_line()?;
+        Ok(Statement::Break(Break { value, span }))
+    }
 }
@@ -1,10 +1,49 @@
 use super::*;
-use syntax_ast::{ConditionalBranch, Match};
+use syntax_ast::{
+    ConditionalBranch, Expression, ExpressionValue, Match, MatchArm, MatchExpression, ValueBlock,
+};
+
+/// The value being matched, the conditions of the cases with their bodies,
+///     and the default body.
+type MatchBody<T> = (Expression, Vec<(Expression, T)>, Option<T>);
 
 impl SyntacticParser {
-    pub(super) fn parse_match(&mut self, pool: &mut InternPool) -> Result<Statement, Error> {
+    pub(super) fn parse_match(&mut self) -> Result<Statement, Error> {
         std::debug_assert!(self.is_keyword(TokenType::Match));
         self.advance();
+        let (value, cases, default) = self.parse_match_body(Self::parse_case_body)?;
+        Ok(Statement::Match(Match {
+            value,
+            cases: cases
+                .into_iter()
+                .map(|(condition, body)| ConditionalBranch { condition, body })
+                .collect(),
+            default,
+        }))
+    }
+
+    pub(super) fn parse_match_expression(&mut self, start: Span) -> Result<Expression, Error> {
+        let (value, cases, default) = self.parse_match_body(Self::parse_arm_value)?;
+        let cases = cases
+            .into_iter()
+            .map(|(condition, body)| MatchArm { condition, body })
+            .collect();
+        Ok(Expression {
+            value: ExpressionValue::Match(Box::new(MatchExpression {
+                value,
+                cases,
+                default,
+            })),
+            span: start.merge(self.back()?.span),
+        })
+    }
+
+    /// Parses the rest of a match after the `match` keyword.
+    /// Statements and expressions only differ in the bodies of their arms.
+    fn parse_match_body<T>(
+        &mut self,
+        parse_body: fn(&mut Self) -> Result<T, Error>,
+    ) -> Result<MatchBody<T>, Error> {
         self.expect_keyword(
             TokenType::OpenParen,
             ErrorType::Match,
@@ -19,41 +58,103 @@ impl SyntacticParser {
             ErrorType::Match,
             "Expected match body",
         )?;
+        let opener = self.peek().unwrap().span;
         self.advance();
         let mut cases = Vec::new();
         let mut default = None;
         while !self.is_keyword(TokenType::CloseBracket) {
+            self.check_unclosed(TokenType::CloseBracket, opener)?;
             if let Some(id) = self.is_identifier()
-                && id == intern_pool::get_keyword_symbol_id("_")
+                && id == self.wildcard
             {
                 if default.is_some() {
                     return Err(self.error(ErrorType::Match, "Multiple default branches"));
                 }
                 self.advance();
-                default = Some(self.parse_case_body(pool)?);
+                default = Some(parse_body(self)?);
             } else {
-                cases.push(self.parse_case(pool)?);
+                let condition = self.parse_expression()?;
+                cases.push((condition, parse_body(self)?));
             }
         }
         self.advance();
-        Ok(Statement::Match(Match {
-            value,
-            cases,
-            default,
-        }))
+        Ok((value, cases, default))
     }
 
-    fn parse_case(&mut self, pool: &mut InternPool) -> Result<ConditionalBranch, Error> {
-        let condition = self.parse_expression()?;
-        Ok(ConditionalBranch {
-            condition,
-            body: self.parse_case_body(pool)?,
-        })
+    fn parse_case_body(&mut self) -> Result<Vec<Statement>, Error> {
+        self.expect_keyword(TokenType::MatchCase, ErrorType::Match, "Expected case")?;
+        self.advance();
+        self.parse_block()
     }
 
-    fn parse_case_body(&mut self, pool: &mut InternPool) -> Result<Vec<Statement>, Error> {
+    /// An arm of a match expression is a value followed by `,`, or a block
+    ///     valued by its last expression. The `,` is optional after a block
+    ///     and after the last arm. Since the block comes first, an array
+    ///     literal as an arm's value has to be parenthesized.
+    fn parse_arm_value(&mut self) -> Result<ValueBlock, Error> {
         self.expect_keyword(TokenType::MatchCase, ErrorType::Match, "Expected case")?;
         self.advance();
-        self.parse_block(pool)
+        let is_block = self.is_keyword(TokenType::OpenBracket);
+        let body = if is_block {
+            self.parse_value_block()?
+        } else {
+            ValueBlock {
+                statements: Vec::new(),
+                value: self.parse_expression()?,
+            }
+        };
+        if self.is_keyword(TokenType::Comma) {
+            self.advance();
+        } else if !is_block && !self.is_keyword(TokenType::CloseBracket) {
+            return Err(self.error(ErrorType::Match, "Expected `,`"));
+        }
+        Ok(body)
+    }
+
+    fn parse_value_block(&mut self) -> Result<ValueBlock, Error> {
+        let opener = self.peek().unwrap().span;
+        self.advance();
+        self.nested(|parser| {
+            let mut statements = Vec::new();
+            loop {
+                parser.check_unclosed(TokenType::CloseBracket, opener)?;
+                if parser.is_keyword(TokenType::CloseBracket) {
+                    return Err(parser.error(ErrorType::Match, "Expected a value"));
+                }
+                if parser.starts_statement() {
+                    statements.push(parser.parse_statement()?);
+                    continue;
+                }
+                let statement = parser.parse_assignment_or_expression(false)?;
+                match statement {
+                    Statement::Expression(value) if parser.is_keyword(TokenType::CloseBracket) => {
+                        parser.advance();
+                        return Ok(ValueBlock { statements, value });
+                    }
+                    statement => {
+                        parser.end_line()?;
+                        statements.push(statement);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Whether the next token starts a statement that isn't an expression.
+    fn starts_statement(&self) -> bool {
+        [
+            TokenType::If,
+            TokenType::Match,
+            TokenType::For,
+            TokenType::While,
+            TokenType::Loop,
+            TokenType::Let,
+            TokenType::Var,
+            TokenType::Return,
+            TokenType::Continue,
+            TokenType::Break,
+        ]
+        .into_iter()
+        .any(|kw| self.is_keyword(kw))
     }
 }
@@ -6,6 +6,8 @@ use crate::syntax_ast;
 use crate::syntax_ast::{Name, Statement};
 use crate::token;
 use crate::token::{Token, TokenType, TokenValue};
+use std::collections::HashSet;
+use std::path::PathBuf;
 
 mod assignment;
 mod conditional;
@@ -24,10 +26,29 @@ mod utils;
 
 #[derive(Debug)]
 pub(crate) enum ErrorType {
+    #[allow(dead_code, reason = "only read through `Debug` for now")]
     Lexer(Box<crate::lexer::Error>),
+    #[allow(dead_code, reason = "only read through `Debug` for now")]
     Io(Box<std::io::Error>),
+    /// A source file isn't valid UTF-8. The span counts chars, so this is
+    ///     where the first invalid sequence starts in bytes.
+    InvalidUtf8 {
+        offset: usize,
+    },
+    #[allow(dead_code, reason = "only read through `Debug` for now")]
     ModuleFile(Box<serde_json::Error>),
     Module,
+    /// A dependency in `module.json`, as written, and the path it resolved
+    ///     to, which doesn't exist or has no `module.json`.
+    ModuleNotFound {
+        dependency: SymbolId,
+        resolved: PathId,
+    },
+    /// The `module` declaration names a different module than the directory.
+    ModuleName {
+        declared: SymbolId,
+        expected: SymbolId,
+    },
     Import,
     LineEnd,
     TypeDefinition,
@@ -39,6 +60,15 @@ pub(crate) enum ErrorType {
     Function,
     Match,
     Loop,
+    NestingTooDeep,
+    /// The input ended before a `(`, `[` or `{` was closed.
+    UnclosedDelimiter,
+    /// A name is defined twice in a file. The error points at the second
+    ///     definition, and this is where the first one is. Boxed to keep
+    ///     errors, and so every parsing stack frame, small.
+    Duplicated {
+        first: Box<Span>,
+    },
 }
 
 #[derive(Debug)]
@@ -48,10 +78,49 @@ pub(crate) struct Error {
     span: Span,
 }
 
+/// Cloning a parser snapshots its position, so a caller can try parsing
+///     one way and fall back to another. That's why tokens and the syntax
+///     AST are `Clone`.
+#[derive(Clone)]
 pub struct SyntacticParser {
     path: PathId,
     tokens: Vec<Token>,
     index: usize,
+    /// How many nested expressions and blocks are being parsed.
+    depth: usize,
+    options: ParserOptions,
+    /// `_` isn't a keyword, so its id is looked up once when the parser is created.
+    wildcard: SymbolId,
+}
+
+#[derive(Clone)]
+pub(crate) struct ParserOptions {
+    /// The deepest expressions and blocks can be nested before
+    ///     NestingTooDeep is returned. This keeps pathological inputs
+    ///     from overflowing the stack. The default is low enough for
+    ///     the 2MiB stack of a spawned thread in debug builds.
+    pub(crate) max_depth: usize,
+    /// The most modifiers like `*` and `[]` a single type annotation can
+    ///     have before NestingTooDeep is returned.
+    pub(crate) max_type_modifiers: usize,
+    /// The flags enabled for conditional compilation. Items marked with
+    ///     `@cfg(flag)` are left out unless their flag is one of these.
+    pub(crate) cfg: HashSet<String>,
+    /// Directories searched for dependencies that aren't found relative to
+    ///     the manifest, like the one `std` is installed in. They're tried
+    ///     in order, each joined with the dependency as written.
+    pub(crate) search_paths: Vec<PathBuf>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_type_modifiers: 64,
+            cfg: HashSet::new(),
+            search_paths: Vec::new(),
+        }
+    }
 }
 
 impl SyntacticParser {
@@ -61,6 +130,18 @@ impl SyntacticParser {
         filename: SymbolId,
         module_name: SymbolId,
         pool: &mut InternPool,
+    ) -> Result<syntax_ast::File, Error> {
+        let options = ParserOptions::default();
+        Self::parse_code_with_options(path, code, filename, module_name, pool, options)
+    }
+
+    pub(crate) fn parse_code_with_options(
+        path: PathId,
+        code: &str,
+        filename: SymbolId,
+        module_name: SymbolId,
+        pool: &mut InternPool,
+        options: ParserOptions,
     ) -> Result<syntax_ast::File, Error> {
         let tokens = match Lexer::lex(path, code, pool) {
             Ok(tokens) => tokens,
@@ -72,12 +153,51 @@ impl SyntacticParser {
                 });
             }
         };
-        let mut parser = Self {
+        let mut parser = Self::new(path, tokens, pool, options);
+        parser.parse(filename, module_name, pool)
+    }
+
+    /// Creates a parser over already lexed tokens, for tools that drive it
+    ///     themselves and need to know how far it got.
+    pub(crate) fn new(
+        path: PathId,
+        tokens: Vec<Token>,
+        pool: &mut InternPool,
+        options: ParserOptions,
+    ) -> Self {
+        Self {
             path,
             tokens,
             index: 0,
-        };
-        parser.parse_file(filename, module_name, pool)
+            depth: 0,
+            options,
+            wildcard: pool.insert_symbol("_".to_string()),
+        }
+    }
+
+    /// Parses the tokens as a whole file.
+    pub(crate) fn parse(
+        &mut self,
+        filename: SymbolId,
+        module_name: SymbolId,
+        pool: &mut InternPool,
+    ) -> Result<syntax_ast::File, Error> {
+        self.parse_file(filename, module_name, pool)
+    }
+
+    /// The index of the next token to be consumed.
+    pub(crate) fn position(&self) -> usize {
+        self.index
+    }
+
+    /// How many tokens haven't been consumed.
+    pub(crate) fn remaining(&self) -> usize {
+        self.tokens.len().saturating_sub(self.index)
+    }
+
+    /// Whether every token has been consumed.
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.remaining() == 0
     }
 }
 
@@ -85,7 +205,8 @@ impl SyntacticParser {
 mod tests {
     use std::path::PathBuf;
 
-    use crate::syntax_ast::File;
+    use crate::semantic_parser::SemanticParser;
+    use crate::syntax_ast::{Binary, BinaryOp, ExpressionValue, File};
 
     use super::*;
 
@@ -100,6 +221,368 @@ mod tests {
         ast
     }
 
+    /// Creates a fresh directory under the system temp dir and fills it with files.
+    /// Each file is given as a path relative to the directory and its content.
+    fn test_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("compiler_{}_{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn test_parser(code: &str) -> (SyntacticParser, InternPool) {
+        let mut pool = InternPool::new();
+        let path = pool.insert_path(PathBuf::new());
+        let tokens = Lexer::lex(path, code, &mut pool).unwrap();
+        let parser = SyntacticParser::new(path, tokens, &mut pool, ParserOptions::default());
+        (parser, pool)
+    }
+
+    #[test]
+    fn functions() {
+        let code = r#"module test_functions;
+
+prv fn apply(f: fn(i32) -> bool, x: i32) -> bool {
+    return f(x);
+}
+
+prv fn notify(handlers: []let fn(*var u8), done: fn()) {
+    done();
+}
+
+pub fn halt() asm("cli" "hlt");"#;
+        let ast = test_code(code, "test", "test_functions");
+        let mut settings = insta::Settings::clone_current();
+        settings.set_sort_maps(true);
+        settings.bind(|| {
+            insta::assert_yaml_snapshot!(ast);
+        });
+    }
+
+    #[test]
+    fn file_and_module_names() {
+        let dir = test_dir(
+            "names",
+            &[
+                ("demo/module.json", "[]"),
+                ("demo/main.code", "module demo;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let ast = SyntacticParser::parse_modules(&dir.join("demo"), &mut pool).unwrap();
+        let module = &ast.modules[&ast.entry];
+        let file = module.files.values().next().unwrap();
+        assert_eq!(module.files.len(), 1);
+        assert_eq!(pool.symbol_reverse_lookup(ast.entry).unwrap(), "demo");
+        assert_eq!(pool.symbol_reverse_lookup(file.name).unwrap(), "main");
+        assert_eq!(pool.symbol_reverse_lookup(file.module).unwrap(), "demo");
+        assert!(module.files.contains_key(&file.name));
+    }
+
+    #[test]
+    fn module_declaration_matches_directory() {
+        let dir = test_dir(
+            "module_match",
+            &[
+                ("shapes/module.json", "[]"),
+                ("shapes/circle.code", "module shapes;\n"),
+                ("shapes/square.code", "module shapes;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let ast = SyntacticParser::parse_modules(&dir.join("shapes"), &mut pool).unwrap();
+        let module = &ast.modules[&ast.entry];
+        assert_eq!(module.files.len(), 2);
+        assert!(module.files.values().all(|file| file.module == ast.entry));
+    }
+
+    #[test]
+    fn module_declaration_mismatch() {
+        let dir = test_dir(
+            "module_mismatch",
+            &[
+                ("shapes/module.json", "[]"),
+                ("shapes/circle.code", "module circle;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("shapes"), &mut pool).unwrap_err();
+        let ErrorType::ModuleName { declared, expected } = err.typ else {
+            panic!("expected a module name error, got {:?}", err.typ);
+        };
+        assert_eq!(pool.symbol_reverse_lookup(declared).unwrap(), "circle");
+        assert_eq!(pool.symbol_reverse_lookup(expected).unwrap(), "shapes");
+        assert_eq!(
+            err.msg,
+            "Module declaration doesn't match the directory name"
+        );
+        assert_eq!((err.span.line, err.span.column, err.span.size), (1, 8, 6));
+    }
+
+    #[test]
+    fn type_attributes() {
+        let code = "module test;\n@packed pub struct Header { a: u8, b: u32 }\nprv struct Plain {}";
+        let mut pool = InternPool::new();
+        let name = pool.insert_symbol("test".to_string());
+        let path = pool.insert_path(PathBuf::new());
+        let file = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap();
+        let header = &file.types[&pool.search_symbol("Header").unwrap()].value;
+        assert_eq!(header.attributes.len(), 1);
+        assert_eq!(
+            header.attributes[0].name,
+            pool.search_symbol("packed").unwrap()
+        );
+        assert_eq!(
+            (
+                header.attributes[0].span.column,
+                header.attributes[0].span.size
+            ),
+            (1, 7)
+        );
+        let plain = &file.types[&pool.search_symbol("Plain").unwrap()].value;
+        assert!(plain.attributes.is_empty());
+
+        let code = "module test;\n@packed prv fn f() {}";
+        let err = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Attributes are only allowed on type definitions");
+    }
+
+    #[test]
+    fn imports_between_comments() {
+        let code = r#"module test;
+// the standard library
+import std;
+
+// helpers
+import math; // trailing
+prv fn f() {}"#;
+        let ast = test_code(code, "test", "test");
+        assert_eq!(ast.imports.len(), 2);
+        assert_eq!(ast.functions.len(), 1);
+    }
+
+    #[test]
+    fn import_after_definition() {
+        let code = "module test;\nprv fn f() {}\nimport std;\n";
+        let mut pool = InternPool::new();
+        let name = pool.insert_symbol("test".to_string());
+        let path = pool.insert_path(PathBuf::new());
+        let err = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Import));
+        assert_eq!(err.msg, "Imports must come before definitions");
+        assert_eq!((err.span.line, err.span.column), (3, 1));
+    }
+
+    #[test]
+    fn visibility_scopes() {
+        let code = "module test;
+pub fn a() {}
+pub(super) fn b() {}
+pub(module) fn c() {}
+mod fn d() {}
+prv fn e() {}";
+        let (mut pool, ids) = InternPool::from_symbols(&["test", "a", "b", "c", "d", "e"]);
+        let path = pool.insert_path(PathBuf::new());
+        let file = SyntacticParser::parse_code(path, code, ids[0], ids[0], &mut pool).unwrap();
+        let visibilities: Vec<_> = ids[1..]
+            .iter()
+            .map(|id| file.functions[id].visibility)
+            .collect();
+        assert_eq!(
+            visibilities,
+            vec![
+                syntax_ast::Visibility::Public,
+                syntax_ast::Visibility::Super,
+                syntax_ast::Visibility::Module,
+                syntax_ast::Visibility::Module,
+                syntax_ast::Visibility::Private,
+            ]
+        );
+
+        let code = "module test;\npub(crate) fn f() {}";
+        let err = SyntacticParser::parse_code(path, code, ids[0], ids[0], &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Unknown visibility scope");
+        assert_eq!((err.span.line, err.span.column), (2, 5));
+        let code = "module test;\npub(super fn f() {}";
+        let err = SyntacticParser::parse_code(path, code, ids[0], ids[0], &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected `)`");
+    }
+
+    #[test]
+    fn lookahead() {
+        let (mut parser, _) = test_parser("a + b");
+        assert_eq!(parser.peek_n(0), parser.peek());
+        assert!(parser.is_keyword_n(1, TokenType::Plus));
+        assert_eq!(
+            parser.peek2().unwrap().value,
+            TokenValue::Keyword(TokenType::Plus)
+        );
+        assert!(parser.peek_n(2).is_some());
+        assert!(parser.peek_n(3).is_none());
+        parser.advance();
+        parser.advance();
+        assert!(parser.peek().is_some());
+        assert!(parser.peek2().is_none());
+        assert!(!parser.is_keyword_n(1, TokenType::Plus));
+        parser.advance();
+        assert!(parser.peek().is_none());
+        assert!(parser.peek_n(0).is_none());
+        assert!(parser.peek_n(10).is_none());
+    }
+
+    #[test]
+    fn struct_literal_lookahead() {
+        let (mut parser, _) = test_parser("Point { x: 1, y: 2 }");
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Struct(literal)) = exp.value
+        else {
+            panic!("Expected a struct literal");
+        };
+        assert_eq!(literal.fields.len(), 2);
+        assert!(parser.peek().is_none());
+
+        let (mut parser, _) = test_parser("Point {}");
+        let exp = parser.parse_expression().unwrap();
+        assert!(matches!(
+            exp.value,
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Struct(_))
+        ));
+
+        let (mut parser, _) = test_parser("cond { x = 1; }");
+        let exp = parser.parse_expression().unwrap();
+        assert!(matches!(
+            exp.value,
+            syntax_ast::ExpressionValue::Identifier(_)
+        ));
+        assert!(parser.is_keyword(TokenType::OpenBracket));
+    }
+
+    #[test]
+    fn struct_literal_fields() {
+        let (mut parser, pool) = test_parser("Point { x: 1, y: 2, z: 3 }");
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Struct(literal)) = exp.value
+        else {
+            panic!("Expected a struct literal");
+        };
+        for name in ["x", "y", "z"] {
+            assert!(
+                literal
+                    .fields
+                    .contains_key(&pool.search_symbol(name).unwrap())
+            );
+        }
+
+        let (mut parser, _) = test_parser("Point { x: 1; y: 2 }");
+        assert_eq!(parser.parse_expression().unwrap_err().msg, "Expected `,`");
+    }
+
+    #[test]
+    fn struct_update() {
+        let (mut parser, pool) = test_parser("Point { ..origin, x: 10 }");
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Struct(literal)) = exp.value
+        else {
+            panic!("Expected a struct literal");
+        };
+        let origin = pool.search_symbol("origin").unwrap();
+        let point = pool.search_symbol("Point").unwrap();
+        assert_eq!(
+            literal.typ.base,
+            syntax_ast::TypeAnnotBase::Normal(vec![point])
+        );
+        let base = literal.base.unwrap();
+        assert_eq!(
+            base.value,
+            syntax_ast::ExpressionValue::Identifier(vec![origin])
+        );
+        assert_eq!(literal.fields.len(), 1);
+        assert!(
+            literal
+                .fields
+                .contains_key(&pool.search_symbol("x").unwrap())
+        );
+        assert!(parser.peek().is_none());
+
+        let (mut parser, _) = test_parser("Point { ..origin }");
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Struct(literal)) = exp.value
+        else {
+            panic!("Expected a struct literal");
+        };
+        assert!(literal.base.is_some());
+        assert!(literal.fields.is_empty());
+
+        let (mut parser, _) = test_parser("Point { ..origin x: 10 }");
+        assert!(parser.parse_expression().is_err());
+
+        let (mut parser, _) = test_parser("Point { x: 1, x: 2 }");
+        assert_eq!(
+            parser.parse_expression().unwrap_err().msg,
+            "Duplicated field"
+        );
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let code = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+        let (mut parser, _) = test_parser(&code);
+        let err = parser.parse_expression().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::NestingTooDeep));
+
+        let code = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        let (mut parser, _) = test_parser(&code);
+        assert!(parser.parse_expression().is_ok());
+
+        let (mut parser, _) = test_parser(&code);
+        parser.options.max_depth = 50;
+        let err = parser.parse_expression().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::NestingTooDeep));
+
+        let code = format!("{}{}", "while { ".repeat(10_000), "}".repeat(10_000));
+        let (mut parser, _) = test_parser(&code);
+        let err = parser.parse_statement().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::NestingTooDeep));
+    }
+
+    #[test]
+    fn tuples() {
+        let (mut parser, _) = test_parser("(i32, bool)");
+        let annot = parser.parse_type_annotation().unwrap();
+        let syntax_ast::TypeAnnotBase::Tuple(elements) = annot.base else {
+            panic!("Expected a tuple type");
+        };
+        assert_eq!(elements.len(), 2);
+        assert!(parser.peek().is_none());
+
+        let (mut parser, _) = test_parser("(1, true)");
+        let exp = parser.parse_expression().unwrap();
+        assert_eq!(exp.span.size, 9);
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Tuple(elements)) = exp.value
+        else {
+            panic!("Expected a tuple literal");
+        };
+        assert_eq!(elements.len(), 2);
+
+        let (mut parser, _) = test_parser("(1)");
+        let exp = parser.parse_expression().unwrap();
+        assert!(matches!(
+            exp.value,
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(1, _))
+        ));
+
+        let (mut parser, _) = test_parser("(i32)");
+        assert!(parser.parse_type_annotation().is_err());
+        let (mut parser, _) = test_parser("(1,)");
+        assert!(parser.parse_expression().is_err());
+    }
+
     #[test]
     fn basic() {
         let code = r#"module test_add;
@@ -215,4 +698,1017 @@ pub fn is_true(cond: bool) -> bool {
             insta::assert_yaml_snapshot!(ast);
         });
     }
+
+    #[test]
+    fn back_before_first_token() {
+        let (mut parser, _) = test_parser("{1}");
+        let err = parser.back().unwrap_err();
+        assert_eq!(err.msg, "No preceding token");
+        assert_eq!(err.span.column, 1);
+        parser.advance();
+        assert_eq!(
+            parser.back().unwrap().value,
+            TokenValue::Keyword(TokenType::OpenBracket)
+        );
+    }
+
+    #[test]
+    fn consumes_every_token() {
+        let (mut parser, mut pool) = test_parser("module test;\nprv fn f() -> i32 { return 1; }");
+        let test = pool.insert_symbol("test".to_string());
+        assert_eq!(parser.position(), 0);
+        assert_eq!(parser.remaining(), 15);
+        assert!(!parser.is_at_end());
+        parser.parse(test, test, &mut pool).unwrap();
+        assert_eq!(parser.position(), 15);
+        assert_eq!(parser.remaining(), 0);
+        assert!(parser.is_at_end());
+    }
+
+    /// Parses an expression and prints its structure with explicit parentheses.
+    fn grouping(code: &str) -> String {
+        fn print(exp: &syntax_ast::Expression, pool: &mut InternPool) -> String {
+            match &exp.value {
+                syntax_ast::ExpressionValue::Identifier(name) => {
+                    pool.symbol_reverse_lookup(name[0]).unwrap()
+                }
+                syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(uint, _)) => {
+                    uint.to_string()
+                }
+                syntax_ast::ExpressionValue::Binary(binary) => format!(
+                    "({} {:?} {})",
+                    print(&binary.left, pool),
+                    binary.op,
+                    print(&binary.right, pool)
+                ),
+                syntax_ast::ExpressionValue::Unary(unary) => {
+                    format!("({:?} {})", unary.op, print(&unary.operand, pool))
+                }
+                syntax_ast::ExpressionValue::Cast(cast) => {
+                    let syntax_ast::TypeAnnotBase::Normal(name) = &cast.typ.base else {
+                        panic!("unexpected type");
+                    };
+                    let typ = pool.symbol_reverse_lookup(name[0]).unwrap();
+                    format!("({} As {typ})", print(&cast.value, pool))
+                }
+                _ => panic!("unexpected expression"),
+            }
+        }
+        let (mut parser, mut pool) = test_parser(code);
+        let exp = parser.parse_expression().unwrap();
+        print(&exp, &mut pool)
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(grouping("a - b - c"), "((a Minus b) Minus c)");
+        assert_eq!(grouping("a.b.c"), "((a FieldAccess b) FieldAccess c)");
+        assert_eq!(grouping("a * b + c / d"), "((a Mul b) Plus (c Div d))");
+        assert_eq!(grouping("-a.b * c"), "((Negate (a FieldAccess b)) Mul c)");
+        assert_eq!(grouping("a < b"), "(a Lt b)");
+        assert_eq!(
+            grouping("a < b and b + 1 < c"),
+            "((a Lt b) LogicalAnd ((b Plus 1) Lt c))"
+        );
+    }
+
+    #[test]
+    fn cast_precedence() {
+        assert_eq!(grouping("a + b as i64"), "(a Plus (b As i64))");
+        assert_eq!(grouping("-x as u32"), "((Negate x) As u32)");
+        assert_eq!(grouping("a * b as u8 as i16"), "(a Mul ((b As u8) As i16))");
+        assert_eq!(
+            grouping("a.b as i32 < c"),
+            "(((a FieldAccess b) As i32) Lt c)"
+        );
+    }
+
+    #[test]
+    fn chained_comparisons() {
+        let (mut parser, _) = test_parser("a < b < c");
+        let err = parser.parse_expression().unwrap_err();
+        assert_eq!(err.msg, "Comparison operators can't be chained");
+        assert_eq!(err.span.column, 7);
+        let (mut parser, _) = test_parser("a == b != c");
+        assert!(parser.parse_expression().is_err());
+        let (mut parser, _) = test_parser("(a < b) == c");
+        assert!(parser.parse_expression().is_ok());
+    }
+
+    #[test]
+    fn field_access_at_eof() {
+        let (mut parser, _) = test_parser("x.");
+        let err = parser.parse_expression().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Expression));
+        assert_eq!(err.msg, "No expression found");
+    }
+
+    #[test]
+    fn for_multiple_init() {
+        let code = r#"module test;
+
+prv fn f(n: i32) {
+    for (var i: i32 = 0, j: i32 = n; i < j; i += 1, j -= 1) {}
+}"#;
+        let ast = test_code(code, "test", "test");
+        let mut settings = insta::Settings::clone_current();
+        settings.set_sort_maps(true);
+        settings.bind(|| {
+            insta::assert_yaml_snapshot!(ast);
+        });
+    }
+
+    #[test]
+    fn for_init_needs_semicolon() {
+        let (mut parser, _) = test_parser("for (var i: i32 = 0, j: i32 = 1) {}");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!(err.msg, "`;` expected at end of line");
+    }
+
+    #[test]
+    fn unclosed_delimiters() {
+        let expression_error = |code| {
+            let (mut parser, _) = test_parser(code);
+            let err = parser.parse_expression().unwrap_err();
+            assert!(matches!(err.typ, ErrorType::UnclosedDelimiter));
+            (err.msg, err.span.column)
+        };
+        assert_eq!(expression_error("(1 + 2"), ("Unclosed `(`", 1));
+        assert_eq!(expression_error("(1, 2"), ("Unclosed `(`", 1));
+        assert_eq!(expression_error("1 + f(1, 2"), ("Unclosed `(`", 6));
+        assert_eq!(expression_error("a[1"), ("Unclosed `[`", 2));
+        assert_eq!(expression_error("{1, 2,"), ("Unclosed `{`", 1));
+
+        let (mut parser, _) = test_parser("while { x = 1;\n  y = 2;");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!(err.msg, "Unclosed `{`");
+        assert_eq!((err.span.line, err.span.column), (1, 7));
+        let (mut parser, _) = test_parser("match (x) { 1 => {}");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!(err.msg, "Unclosed `{`");
+        assert_eq!(err.span.column, 11);
+    }
+
+    #[test]
+    fn match_wildcard() {
+        let (mut parser, _) = test_parser("match (x) { 1 => {} _ => { y = 1; } 2 => {} }");
+        let Statement::Match(m) = parser.parse_statement().unwrap() else {
+            panic!("expected a match statement");
+        };
+        assert_eq!(m.cases.len(), 2);
+        assert_eq!(m.default.unwrap().len(), 1);
+
+        let (mut parser, _) = test_parser("match (x) { _ => {} _ => {} }");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!(err.msg, "Multiple default branches");
+
+        // A name merely starting with `_` is an ordinary case.
+        let (mut parser, _) = test_parser("match (x) { _a => {} }");
+        let Statement::Match(m) = parser.parse_statement().unwrap() else {
+            panic!("expected a match statement");
+        };
+        assert_eq!(m.cases.len(), 1);
+        assert!(m.default.is_none());
+    }
+
+    #[test]
+    fn break_values() {
+        let (mut parser, _) = test_parser("loop { continue; break; break x + 1; }");
+        let Statement::Loop(l) = parser.parse_statement().unwrap() else {
+            panic!("expected a loop statement");
+        };
+        assert!(l.condition.is_none());
+        assert!(matches!(l.body[0], Statement::Continue(_)));
+        let [_, Statement::Break(first), Statement::Break(second)] = &l.body[..] else {
+            panic!("expected two breaks");
+        };
+        assert!(first.value.is_none());
+        assert!(second.value.is_some());
+        assert_eq!(second.span.size, 11);
+        assert!(parser.is_at_end());
+
+        let (mut parser, _) = test_parser("loop { break 5; }");
+        let exp = parser.parse_expression().unwrap();
+        assert!(matches!(exp.value, syntax_ast::ExpressionValue::Loop(_)));
+    }
+
+    #[test]
+    fn for_update() {
+        let (mut parser, _) = test_parser("for (var i: i32 = 0; i < 10; i += 1) {}");
+        let Statement::Loop(l) = parser.parse_statement().unwrap() else {
+            panic!("expected a loop statement");
+        };
+        let [Statement::Assignment(update)] = &l.update[..] else {
+            panic!("expected one assignment");
+        };
+        assert_eq!(update.typ, syntax_ast::AssignmentType::Plus);
+        assert!(parser.is_at_end());
+
+        let (mut parser, _) = test_parser("for (;; f(), i = 0) {}");
+        let Statement::Loop(l) = parser.parse_statement().unwrap() else {
+            panic!("expected a loop statement");
+        };
+        assert!(matches!(l.update[0], Statement::Expression(_)));
+        assert!(matches!(l.update[1], Statement::Assignment(_)));
+
+        let (mut parser, _) = test_parser("for (;; i += 1; ) {}");
+        assert_eq!(parser.parse_statement().unwrap_err().msg, "Expected `)`");
+    }
+
+    #[test]
+    fn statement_assignment() {
+        let (mut parser, _) = test_parser("x = 1; f();");
+        assert!(matches!(
+            parser.parse_statement().unwrap(),
+            Statement::Assignment(_)
+        ));
+        assert!(matches!(
+            parser.parse_statement().unwrap(),
+            Statement::Expression(_)
+        ));
+        assert!(parser.is_at_end());
+
+        let (mut parser, _) = test_parser("x = 1 }");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!(err.msg, "`;` expected at end of line");
+    }
+    #[test]
+    fn array_repeat() {
+        let (mut parser, _) = test_parser("{0; 16}");
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::ArrayRepeat { value, count }) =
+            exp.value
+        else {
+            panic!("expected a repeated array");
+        };
+        assert!(matches!(
+            value.value,
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(0, _))
+        ));
+        assert!(matches!(
+            count.value,
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(16, _))
+        ));
+        assert_eq!(exp.span.size, 7);
+
+        for (code, len) in [("{}", 0), ("{1}", 1), ("{1, 2}", 2), ("{1, 2,}", 2)] {
+            let (mut parser, _) = test_parser(code);
+            let exp = parser.parse_expression().unwrap();
+            let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Array(elements)) =
+                exp.value
+            else {
+                panic!("expected an array");
+            };
+            assert_eq!(elements.len(), len);
+        }
+        let expression_error = |code| {
+            let (mut parser, _) = test_parser(code);
+            let err = parser.parse_expression().unwrap_err();
+            (err.msg, err.span.column)
+        };
+        assert_eq!(expression_error("{0; 16"), ("Unclosed `{`", 1));
+        assert_eq!(expression_error("{0; 16, 1}"), ("Expected `}`", 7));
+    }
+    #[test]
+    fn duplicated_names() {
+        let definition_error = |code: &str| {
+            let mut pool = InternPool::new();
+            let path = pool.insert_path(PathBuf::new());
+            let name = pool.insert_symbol("test".to_string());
+            let code = format!("module test;\n{code}");
+            let err = SyntacticParser::parse_code(path, &code, name, name, &mut pool).unwrap_err();
+            let ErrorType::Duplicated { first } = err.typ else {
+                panic!("expected a duplicated name");
+            };
+            (
+                err.msg,
+                (err.span.line, err.span.column),
+                (first.line, first.column),
+            )
+        };
+        assert_eq!(
+            definition_error("prv fn f() {}\nprv fn g() {}\npub fn f() {}"),
+            ("Duplicated function name", (4, 8), (2, 8))
+        );
+        assert_eq!(
+            definition_error("prv struct S { a: i32 }\nprv enum S { A }"),
+            ("Duplicated type name", (3, 10), (2, 12))
+        );
+        assert_eq!(
+            definition_error("prv let x: i32 = 1;\n\nprv var x: i32 = 2;"),
+            ("Duplicated global name", (4, 5), (2, 5))
+        );
+    }
+    #[test]
+    fn void_return() {
+        let void = intern_pool::get_token_type_symbol_id(TokenType::Void);
+        let (mut parser, _) = test_parser("fn() -> void");
+        let annot = parser.parse_type_annotation().unwrap();
+        let syntax_ast::TypeAnnotBase::Function(sig) = annot.base else {
+            panic!("expected a function type");
+        };
+        assert_eq!(
+            sig.ret.unwrap().base,
+            syntax_ast::TypeAnnotBase::Normal(vec![void])
+        );
+
+        let (mut parser, _) = test_parser("fn f() -> void {}");
+        let function = parser.parse_function(&mut InternPool::new()).unwrap();
+        assert_eq!(
+            function.return_type.unwrap().base,
+            syntax_ast::TypeAnnotBase::Normal(vec![void])
+        );
+    }
+
+    #[test]
+    fn relative_dependency() {
+        let dir = test_dir(
+            "relative_dep",
+            &[
+                ("app/module.json", r#"["../shapes"]"#),
+                ("app/main.code", "module app;\n"),
+                ("shapes/module.json", "[]"),
+                ("shapes/circle.code", "module shapes;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let ast = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap();
+        let shapes = pool.search_symbol("shapes").unwrap();
+        assert!(ast.modules[&ast.entry].dependencies.contains(&shapes));
+        let path = pool.path_reverse_lookup(ast.modules[&shapes].path).unwrap();
+        assert_eq!(path, dir.join("shapes").canonicalize().unwrap());
+    }
+
+    #[test]
+    fn absolute_dependency() {
+        let dir = test_dir(
+            "absolute_dep",
+            &[
+                ("app/main.code", "module app;\n"),
+                ("lib/shapes/module.json", "[]"),
+                ("lib/shapes/circle.code", "module shapes;\n"),
+            ],
+        );
+        let shapes_path = dir.join("lib/shapes").canonicalize().unwrap();
+        let manifest = serde_json::to_string(&[&shapes_path]).unwrap();
+        std::fs::write(dir.join("app/module.json"), manifest).unwrap();
+        let mut pool = InternPool::new();
+        let ast = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap();
+        let shapes = pool.search_symbol("shapes").unwrap();
+        let path = pool.path_reverse_lookup(ast.modules[&shapes].path).unwrap();
+        assert_eq!(path, shapes_path);
+    }
+
+    #[test]
+    fn missing_dependency() {
+        let dir = test_dir(
+            "missing_dep",
+            &[
+                ("app/module.json", r#"["../shapes"]"#),
+                ("app/main.code", "module app;\n"),
+                ("empty/circle.code", "module empty;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        let ErrorType::ModuleNotFound {
+            dependency,
+            resolved,
+        } = err.typ
+        else {
+            panic!("expected a missing module error, got {:?}", err.typ);
+        };
+        assert_eq!(pool.symbol_reverse_lookup(dependency).unwrap(), "../shapes");
+        let resolved = pool.path_reverse_lookup(resolved).unwrap();
+        assert_eq!(resolved, dir.join("app").join("../shapes"));
+        let manifest = pool.path_reverse_lookup(err.span.path).unwrap();
+        assert_eq!(manifest, dir.join("app/module.json"));
+
+        std::fs::write(dir.join("app/module.json"), r#"["../empty"]"#).unwrap();
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        let ErrorType::ModuleNotFound { resolved, .. } = err.typ else {
+            panic!("expected a missing module error, got {:?}", err.typ);
+        };
+        let resolved = pool.path_reverse_lookup(resolved).unwrap();
+        assert_eq!(resolved, dir.join("empty").canonicalize().unwrap());
+    }
+
+    #[test]
+    fn item_imports() {
+        let (mut pool, ids) = InternPool::from_symbols(&["test", "std", "io", "print"]);
+        let path = pool.insert_path(PathBuf::new());
+        let parse = |code: &str, pool: &mut InternPool| {
+            SyntacticParser::parse_code(path, code, ids[0], ids[0], pool)
+        };
+        let code = "module test;\nimport std;\nimport std::io::print;\n";
+        let ast = parse(code, &mut pool).unwrap();
+        assert_eq!(ast.imports.len(), 1);
+        let import = &ast.item_imports[&ids[3]];
+        assert_eq!(import.path, vec![ids[1], ids[2]]);
+        assert_eq!(import.item, ids[3]);
+        assert_eq!(
+            (import.span.line, import.span.column, import.span.size),
+            (3, 8, 14)
+        );
+
+        let ast = parse("module test;\nimport std::print;\n", &mut pool).unwrap();
+        assert_eq!(ast.item_imports[&ids[3]].path, vec![ids[1]]);
+        let code = "module test;\nimport print;\nimport std::io::print;\n";
+        let err = parse(code, &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Duplicated imports");
+    }
+
+    #[test]
+    fn match_expression() {
+        let code = "let x: i32 = match (c) {
+            1 => 10,
+            2 => { let y: i32 = 2; y * 10 }
+            _ => 0,
+        };";
+        let (mut parser, _) = test_parser(code);
+        let Statement::Declaration(declaration) = parser.parse_statement().unwrap() else {
+            panic!("expected a declaration");
+        };
+        let syntax_ast::ExpressionValue::Match(m) = declaration.value.value else {
+            panic!("expected a match expression");
+        };
+        assert_eq!(m.cases.len(), 2);
+        assert!(m.cases[0].body.statements.is_empty());
+        assert_eq!(m.cases[1].body.statements.len(), 1);
+        assert!(matches!(
+            m.cases[1].body.value.value,
+            syntax_ast::ExpressionValue::Binary(_)
+        ));
+        assert!(m.default.is_some());
+        assert_eq!(declaration.value.span.line, 1);
+        assert_eq!(declaration.value.span.column, 14);
+        assert!(parser.is_at_end());
+
+        let (mut parser, _) = test_parser("if (a) {} else match (b) { _ => {} }");
+        let Statement::Conditional(conditional) = parser.parse_statement().unwrap() else {
+            panic!("expected a conditional");
+        };
+        let else_branch = conditional.else_branch.unwrap();
+        assert!(matches!(else_branch[..], [Statement::Match(_)]));
+
+        let failing = [
+            ("match (c) { 1 => 10; 2 => 20 }", "Expected `,`"),
+            ("match (c) { 1 => { f(); } }", "Expected a value"),
+        ];
+        for (code, msg) in failing {
+            let (mut parser, _) = test_parser(code);
+            assert_eq!(parser.parse_expression().unwrap_err().msg, msg);
+        }
+    }
+
+    #[test]
+    fn empty_module() {
+        let dir = test_dir(
+            "empty_module",
+            &[
+                ("app/module.json", r#"["../empty"]"#),
+                ("app/main.code", "module app;\n"),
+                ("empty/module.json", "[]"),
+                ("empty/notes.txt", ""),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        assert!(matches!(err.typ, ErrorType::Module));
+        assert_eq!(err.msg, "Module has no source files");
+        let path = pool.path_reverse_lookup(err.span.path).unwrap();
+        assert_eq!(path, dir.join("empty").canonicalize().unwrap());
+    }
+
+    #[test]
+    fn literal_spans() {
+        let spans = [
+            ("{}", (1, 1, 2)),
+            ("{ }", (1, 1, 3)),
+            ("{1}", (1, 1, 3)),
+            ("{ 1, }", (1, 1, 6)),
+            ("{0; 4}", (1, 1, 6)),
+            ("Point {}", (1, 1, 8)),
+            ("Point { x: 1 }", (1, 1, 14)),
+            ("shapes::Point { ..p }", (1, 1, 21)),
+            ("f(Point {})", (1, 3, 8)),
+        ];
+        for (code, expected) in spans {
+            let (mut parser, _) = test_parser(code);
+            let mut exp = parser.parse_expression().unwrap();
+            if let syntax_ast::ExpressionValue::Call(call) = exp.value {
+                exp = call.args.into_iter().next().unwrap();
+            }
+            let span = exp.span;
+            assert_eq!((span.line, span.column, span.size), expected, "{code}");
+            assert!(parser.is_at_end());
+        }
+    }
+
+    #[test]
+    fn struct_defaults() {
+        let (mut parser, pool) = test_parser("struct Point { x: i32 = 0, y: i32, z: i32 = 1 + 2 }");
+        let typ = parser.parse_type_definition(Vec::new()).unwrap();
+        let syntax_ast::TypeDefBody::Struct(struct_) = typ.body else {
+            panic!("Expected a struct");
+        };
+        assert_eq!(struct_.fields.len(), 3);
+        assert_eq!(struct_.defaults.len(), 2);
+        let x = pool.search_symbol("x").unwrap();
+        assert!(matches!(
+            struct_.defaults[&x].value,
+            syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::UInt(0, _))
+        ));
+        assert!(
+            !struct_
+                .defaults
+                .contains_key(&pool.search_symbol("y").unwrap())
+        );
+
+        let (mut parser, _) = test_parser("union Number { int: i32 = 0, float: f32 }");
+        let err = parser.parse_type_definition(Vec::new()).unwrap_err();
+        assert_eq!(err.msg, "Union fields can't have default values");
+    }
+
+    #[test]
+    fn tagged_enums() {
+        let (mut parser, pool) = test_parser("enum Shape { Circle(f64), Rect(f64, f64), Empty }");
+        let typ = parser.parse_type_definition(Vec::new()).unwrap();
+        let syntax_ast::TypeDefBody::TaggedEnum(tagged) = typ.body else {
+            panic!("Expected a tagged enum");
+        };
+        let payload = |name| tagged.payloads[&pool.search_symbol(name).unwrap()].len();
+        assert_eq!(
+            (payload("Circle"), payload("Rect"), payload("Empty")),
+            (1, 2, 0)
+        );
+        let value = |name| tagged.tags.variants[&pool.search_symbol(name).unwrap()];
+        assert_eq!((value("Circle"), value("Rect"), value("Empty")), (0, 1, 2));
+
+        let (mut parser, _) = test_parser("enum Color { Red, Green }");
+        let typ = parser.parse_type_definition(Vec::new()).unwrap();
+        assert!(matches!(typ.body, syntax_ast::TypeDefBody::Enum(_)));
+
+        let (mut parser, _) = test_parser("enum Shape { Circle(f64 }");
+        let err = parser.parse_type_definition(Vec::new()).unwrap_err();
+        assert_eq!(err.msg, "Expected `)`");
+    }
+
+    #[test]
+    fn conditional_compilation() {
+        let code = "module test;
+@cfg(debug) prv fn log() {}
+@cfg(release) prv fn log() {}
+@cfg(debug) @cfg(verbose) prv let LEVEL: i32 = 2;
+@cfg(debug) @packed prv struct Frame { a: u8 }
+";
+        let parse = |flags: &[&str]| {
+            let mut pool = InternPool::new();
+            let name = pool.insert_symbol("test".to_string());
+            let path = pool.insert_path(PathBuf::new());
+            let options = ParserOptions {
+                cfg: flags.iter().map(|flag| flag.to_string()).collect(),
+                ..ParserOptions::default()
+            };
+            let file = SyntacticParser::parse_code_with_options(
+                path, code, name, name, &mut pool, options,
+            )
+            .unwrap();
+            let log = pool.search_symbol("log").unwrap();
+            let line = file.functions.get(&log).map(|f| f.value.span.line);
+            (line, file.globals.len(), file.types.len())
+        };
+        assert_eq!(parse(&["debug"]), (Some(2), 0, 1));
+        assert_eq!(parse(&["release"]), (Some(3), 0, 0));
+        assert_eq!(parse(&["debug", "verbose"]), (Some(2), 1, 1));
+        assert_eq!(parse(&[]), (None, 0, 0));
+
+        let mut pool = InternPool::new();
+        let name = pool.insert_symbol("test".to_string());
+        let path = pool.insert_path(PathBuf::new());
+        let code = "module test;\n@cfg debug prv fn f() {}\n";
+        let err = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected `(`");
+    }
+
+    #[test]
+    fn bare_return() {
+        let (mut parser, _) = test_parser("return; return x;");
+        let Statement::Return(bare) = parser.parse_statement().unwrap() else {
+            panic!("expected a return");
+        };
+        assert!(bare.value.is_none());
+        assert_eq!(bare.span.size, 6);
+        let Statement::Return(valued) = parser.parse_statement().unwrap() else {
+            panic!("expected a return");
+        };
+        assert!(valued.value.is_some());
+        assert_eq!(valued.span.size, 8);
+        assert!(parser.is_at_end());
+    }
+
+    #[test]
+    fn invalid_module_names() {
+        let dir = test_dir(
+            "invalid_names",
+            &[
+                ("app/module.json", r#"["../my-module"]"#),
+                ("app/main.code", "module app;\n"),
+                ("my-module/module.json", "[]"),
+                ("my-module/lib.code", "module my_module;\n"),
+                ("files/module.json", "[]"),
+                ("files/two words.code", "module files;\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Module name isn't a valid identifier");
+        let path = pool.path_reverse_lookup(err.span.path).unwrap();
+        assert_eq!(path, dir.join("my-module").canonicalize().unwrap());
+
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("files"), &mut pool).unwrap_err();
+        assert_eq!(err.msg, "File name isn't a valid identifier");
+        let path = pool.path_reverse_lookup(err.span.path).unwrap();
+        assert_eq!(path, dir.join("files/two words.code"));
+    }
+
+    #[test]
+    fn type_aliases() {
+        let code = "module test;\nprv use Meters = i32;";
+        let mut pool = InternPool::new();
+        let name = pool.insert_symbol("test".to_string());
+        let path = pool.insert_path(PathBuf::new());
+        let file = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap();
+        let meters = &file.types[&pool.search_symbol("Meters").unwrap()].value;
+        assert!(matches!(meters.body, syntax_ast::TypeDefBody::Alias(_)));
+
+        let code = "module test;\nprv use Meters == i32;";
+        let err = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected `=`");
+    }
+
+    #[test]
+    fn asm_operands() {
+        let (mut parser, mut pool) = test_parser(
+            r#"fn out(port: u16, value: u8) asm("mov dx, {0}" "out dx, {1}" : port, value);"#,
+        );
+        let function = parser.parse_function(&mut pool).unwrap();
+        let syntax_ast::FunctionBody::Asm(asm) = function.body else {
+            panic!("expected an asm body");
+        };
+        assert_eq!(asm.code, "mov dx, {0}\nout dx, {1}");
+        let names: Vec<_> = asm
+            .operands
+            .iter()
+            .map(|operand| match &operand.value {
+                syntax_ast::ExpressionValue::Identifier(name) => name.clone(),
+                _ => panic!("expected an identifier"),
+            })
+            .collect();
+        let port = pool.search_symbol("port").unwrap();
+        let value = pool.search_symbol("value").unwrap();
+        assert_eq!(names, [vec![port], vec![value]]);
+        assert!(parser.is_at_end());
+
+        let (mut parser, mut pool) = test_parser(r#"fn f(a: u8) asm("nop" : a;"#);
+        let err = parser.parse_function(&mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected `)`");
+    }
+
+    #[test]
+    fn enum_counter_overflow() {
+        let (mut parser, _) = test_parser("enum E { A = 18446744073709551615, B }");
+        let err = parser.parse_type_definition(Vec::new()).unwrap_err();
+        assert_eq!(err.msg, "Enum discriminant overflow");
+        assert_eq!((err.span.column, err.span.size), (36, 1));
+
+        let (mut parser, pool) = test_parser("enum E { A = 18446744073709551614, B, C = 0 }");
+        let typ = parser.parse_type_definition(Vec::new()).unwrap();
+        let syntax_ast::TypeDefBody::Enum(enum_) = typ.body else {
+            panic!("Expected an enum");
+        };
+        let b = pool.search_symbol("B").unwrap();
+        assert_eq!(enum_.variants[&b], u64::MAX as i128);
+    }
+
+    #[test]
+    fn search_paths() {
+        let dir = test_dir(
+            "search_paths",
+            &[
+                ("app/module.json", r#"["std"]"#),
+                ("app/main.code", "module app;\nimport std;\n"),
+                ("lib/std/module.json", "[]"),
+                ("lib/std/io.code", "module std;\npub fn print() {}\n"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        assert!(matches!(err.typ, ErrorType::ModuleNotFound { .. }));
+
+        let mut pool = InternPool::new();
+        let options = ParserOptions {
+            search_paths: vec![dir.join("missing"), dir.join("lib")],
+            ..ParserOptions::default()
+        };
+        let ast =
+            SyntacticParser::parse_modules_with_options(&dir.join("app"), &mut pool, &options)
+                .unwrap();
+        let std = pool.search_symbol("std").unwrap();
+        assert!(ast.modules[&ast.entry].dependencies.contains(&std));
+        let path = pool.path_reverse_lookup(ast.modules[&std].path).unwrap();
+        assert_eq!(path, dir.join("lib/std").canonicalize().unwrap());
+    }
+
+    #[test]
+    fn number_serialization() {
+        let (mut parser, pool) = test_parser(
+            "{123.456, 0.1, 12345678901234567.0, 2.0, 1.5f32, 0.25f64, 0x1F, 0b101, 1_000, -7}",
+        );
+        let exp = parser.parse_expression().unwrap();
+        let syntax_ast::ExpressionValue::Literal(syntax_ast::Literal::Array(elements)) = exp.value
+        else {
+            panic!("expected an array literal");
+        };
+        let literals: Vec<_> = elements
+            .into_iter()
+            .map(|element| match element.value {
+                syntax_ast::ExpressionValue::Literal(literal) => literal,
+                _ => panic!("expected a literal"),
+            })
+            .collect();
+        intern_pool::set_symbol_context(pool);
+        insta::assert_yaml_snapshot!(literals);
+    }
+
+    #[test]
+    fn block_statement() {
+        let (mut parser, _) = test_parser("{ let x: i32 = 1; } x;");
+        let Statement::Block(block) = parser.parse_statement().unwrap() else {
+            panic!("expected a block");
+        };
+        assert!(matches!(block[..], [Statement::Declaration(_)]));
+        assert!(matches!(
+            parser.parse_statement().unwrap(),
+            Statement::Expression(_)
+        ));
+        assert!(parser.is_at_end());
+    }
+
+    #[test]
+    fn field_call() {
+        let (mut parser, _) = test_parser("a.f(3) + a.b[0]");
+        let exp = parser.parse_expression().unwrap();
+        let ExpressionValue::Binary(sum) = exp.value else {
+            panic!("expected a sum");
+        };
+        let ExpressionValue::Call(call) = sum.left.value else {
+            panic!("expected a call");
+        };
+        assert!(matches!(
+            call.function.value,
+            ExpressionValue::Binary(Binary {
+                op: BinaryOp::FieldAccess,
+                ..
+            })
+        ));
+        assert_eq!(call.args.len(), 1);
+        assert!(matches!(
+            sum.right.value,
+            ExpressionValue::Binary(Binary {
+                op: BinaryOp::Indexing,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn type_annotation_limits() {
+        let code = format!("{}u8", "*let ".repeat(64));
+        let (mut parser, _) = test_parser(&code);
+        assert_eq!(parser.parse_type_annotation().unwrap().modifiers.len(), 64);
+
+        let code = format!("{}u8", "*let ".repeat(10_000));
+        let (mut parser, _) = test_parser(&code);
+        let err = parser.parse_type_annotation().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::NestingTooDeep));
+        assert_eq!(err.msg, "Too many type modifiers");
+        assert_eq!(err.span.column, 64 * 5 + 1);
+
+        let code = format!("{}u8{}", "fn(".repeat(10_000), ")".repeat(10_000));
+        let (mut parser, _) = test_parser(&code);
+        let err = parser.parse_type_annotation().unwrap_err();
+        assert!(matches!(err.typ, ErrorType::NestingTooDeep));
+    }
+
+    #[test]
+    fn cloned_parser() {
+        let (mut parser, _) = test_parser("let x: i32 = 1; x = x + 2; return x;");
+        let first = parser.parse_statement().unwrap();
+        let mut fork = parser.clone();
+        let rest: Vec<_> = (0..2).map(|_| parser.parse_statement().unwrap()).collect();
+        let forked: Vec<_> = (0..2).map(|_| fork.parse_statement().unwrap()).collect();
+        assert_eq!(rest, forked);
+        assert!(parser.is_at_end() && fork.is_at_end());
+        assert_eq!(first.clone(), first);
+    }
+
+    #[test]
+    fn derive_attributes() {
+        let code = "module test;\n@derive(Eq, Print) @packed prv struct P { a: u8 }";
+        let mut pool = InternPool::new();
+        let name = pool.insert_symbol("test".to_string());
+        let path = pool.insert_path(PathBuf::new());
+        let file = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap();
+        let p = &file.types[&pool.search_symbol("P").unwrap()].value;
+        let [derive, packed] = &p.attributes[..] else {
+            panic!("expected two attributes");
+        };
+        assert_eq!(derive.name, pool.search_symbol("derive").unwrap());
+        let args: Vec<_> = derive
+            .args
+            .iter()
+            .map(|(arg, span)| (*arg, span.column))
+            .collect();
+        assert_eq!(
+            args,
+            [
+                (pool.search_symbol("Eq").unwrap(), 9),
+                (pool.search_symbol("Print").unwrap(), 13)
+            ]
+        );
+        assert!(packed.args.is_empty());
+
+        let code = "module test;\n@derive(Eq prv struct P { a: u8 }";
+        let err = SyntacticParser::parse_code(path, code, name, name, &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected `)`");
+    }
+
+    #[test]
+    fn single_file_entry() {
+        let dir = test_dir(
+            "single_file",
+            &[
+                (
+                    "hello.code",
+                    "module hello;\nprv fn main() -> i32 { return 0; }\n",
+                ),
+                ("wrong.code", "module hello;\n"),
+                ("module.json", "[]"),
+            ],
+        );
+        let mut pool = InternPool::new();
+        let ast = SyntacticParser::parse_modules(&dir.join("hello.code"), &mut pool).unwrap();
+        let hello = pool.search_symbol("hello").unwrap();
+        assert_eq!(ast.entry, hello);
+        assert!(ast.modules[&hello].dependencies.is_empty());
+        let (sem_ast, diagnostics) = SemanticParser::analyze(&ast, &pool);
+        assert!(diagnostics.errors.is_empty());
+        let sem_ast = sem_ast.unwrap();
+        let module = sem_ast.modules[&hello].read().unwrap();
+        let main = pool.search_symbol("main").unwrap();
+        assert!(module.files[&hello].functions.contains_key(&main));
+
+        let err = SyntacticParser::parse_modules(&dir.join("wrong.code"), &mut pool).unwrap_err();
+        assert!(matches!(err.typ, ErrorType::ModuleName { .. }));
+
+        let err = SyntacticParser::parse_modules(&dir.join("module.json"), &mut pool).unwrap_err();
+        assert_eq!(err.msg, "Expected a module directory or a `.code` file");
+    }
+
+    #[test]
+    fn type_annotation_error_spans() {
+        let error = |code: &str| {
+            let (mut parser, _) = test_parser(code);
+            let err = parser.parse_type_annotation().unwrap_err();
+            (err.msg, (err.span.column, err.span.size))
+        };
+        assert_eq!(error("*let"), ("Expected a type annotation", (1, 4)));
+        assert_eq!(
+            error("fn(i32, *var"),
+            ("Expected a type annotation", (9, 4))
+        );
+        assert_eq!(
+            error("[]"),
+            ("Type annotations must specify mutability", (1, 2))
+        );
+        assert_eq!(error("*let if u8"), ("Expected a type annotation", (6, 2)));
+        assert_eq!(error("while"), ("Expected a type annotation", (1, 5)));
+        assert_eq!(
+            error("(i32) x"),
+            ("A tuple needs at least two elements", (1, 5))
+        );
+
+        let (mut parser, _) = test_parser("let x:");
+        let err = parser.parse_statement().unwrap_err();
+        assert_eq!((err.span.column, err.span.size), (6, 1));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let dir = test_dir("invalid_utf8", &[("app/module.json", "[]")]);
+        std::fs::write(dir.join("app/main.code"), b"module app;\n// \xC3\xA9\xFF\n").unwrap();
+        let mut pool = InternPool::new();
+        let err = SyntacticParser::parse_modules(&dir.join("app"), &mut pool).unwrap_err();
+        let ErrorType::InvalidUtf8 { offset } = err.typ else {
+            panic!("expected an invalid UTF-8 error, got {:?}", err.typ);
+        };
+        assert_eq!(offset, 17);
+        assert_eq!((err.span.line, err.span.column, err.span.index), (2, 5, 16));
+        let path = pool.path_reverse_lookup(err.span.path).unwrap();
+        assert_eq!(path, dir.join("app/main.code"));
+    }
+
+    #[test]
+    fn expression_body() {
+        let (mut parser, mut pool) = test_parser("fn double(x: i32) -> i32 = x * 2;");
+        let function = parser.parse_function(&mut pool).unwrap();
+        let syntax_ast::FunctionBody::Block(body) = function.body else {
+            panic!("expected a block body");
+        };
+        let [Statement::Return(ret)] = &body[..] else {
+            panic!("expected a single return");
+        };
+        assert!(matches!(
+            ret.value.as_ref().unwrap().value,
+            ExpressionValue::Binary(Binary {
+                op: BinaryOp::Mul,
+                ..
+            })
+        ));
+        assert_eq!((ret.span.column, ret.span.size), (26, 7));
+        assert!(parser.is_at_end());
+
+        let (mut parser, mut pool) = test_parser("fn f(x: i32) = x;");
+        let err = parser.parse_function(&mut pool).unwrap_err();
+        assert_eq!(
+            err.msg,
+            "A function returning an expression needs a return type"
+        );
+        assert_eq!(err.span.column, 14);
+    }
+
+    #[test]
+    fn stray_match_case() {
+        let msg = "`=>` is only valid in match arms";
+        for (code, column) in [
+            ("let x: i32 = 1 => 2;", 16),
+            ("x = => 2;", 5),
+            ("f(1) => 2;", 6),
+            ("=> 2;", 1),
+        ] {
+            let (mut parser, _) = test_parser(code);
+            let err = parser.parse_statement().unwrap_err();
+            assert_eq!((err.msg, err.span.column, err.span.size), (msg, column, 2));
+        }
+        let (mut parser, _) = test_parser("match (x) { 1 => { } _ => { } }");
+        assert!(parser.parse_statement().is_ok());
+    }
+
+    #[test]
+    fn binding_patterns() {
+        let (mut parser, pool) =
+            test_parser("match (s) { Shape::Circle(r) => { x = r; } Shape::Rect(w, _) => {} }");
+        let Statement::Match(m) = parser.parse_statement().unwrap() else {
+            panic!("expected a match statement");
+        };
+        let symbol = |name| pool.search_symbol(name).unwrap();
+        let patterns: Vec<_> = m
+            .cases
+            .iter()
+            .map(|case| {
+                let ExpressionValue::Call(call) = &case.condition.value else {
+                    panic!("expected a constructor pattern");
+                };
+                let ExpressionValue::Identifier(name) = &call.function.value else {
+                    panic!("expected a variant name");
+                };
+                let bindings: Vec<_> = call
+                    .args
+                    .iter()
+                    .map(|arg| match &arg.value {
+                        ExpressionValue::Identifier(binding) => binding.clone(),
+                        _ => panic!("expected a binding"),
+                    })
+                    .collect();
+                (name.clone(), bindings)
+            })
+            .collect();
+        assert_eq!(
+            patterns,
+            vec![
+                (
+                    vec![symbol("Shape"), symbol("Circle")],
+                    vec![vec![symbol("r")]]
+                ),
+                (
+                    vec![symbol("Shape"), symbol("Rect")],
+                    vec![vec![symbol("w")], vec![symbol("_")]]
+                ),
+            ]
+        );
+        assert_eq!(m.cases[0].body.len(), 1);
+    }
 }
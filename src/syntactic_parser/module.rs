@@ -1,6 +1,7 @@
 use super::*;
 use std::{
     collections::{HashMap, HashSet},
+    ffi::OsStr,
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -8,14 +9,40 @@ use std::{
 use syntax_ast::{Ast, Module};
 
 impl SyntacticParser {
-    fn path_to_module_name(path: &Path, pool: &mut InternPool) -> SymbolId {
-        let name = path.file_name().unwrap().to_str().unwrap().to_string();
-        pool.insert_symbol(name)
+    fn path_to_module_name(path: &Path, pool: &mut InternPool) -> Result<SymbolId, Error> {
+        let msg = "Module name isn't a valid identifier";
+        Self::path_to_name(path, path.file_name(), msg, pool)
     }
 
-    fn path_to_filename(path: &Path, pool: &mut InternPool) -> SymbolId {
-        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
-        pool.insert_symbol(name)
+    fn path_to_filename(path: &Path, pool: &mut InternPool) -> Result<SymbolId, Error> {
+        let msg = "File name isn't a valid identifier";
+        Self::path_to_name(path, path.file_stem(), msg, pool)
+    }
+
+    /// Module and file names are referred to in source, so they have to be
+    ///     identifiers. Names like `my-module` aren't mapped to anything,
+    ///     they're rejected so the directory or file gets renamed instead.
+    fn path_to_name(
+        path: &Path,
+        name: Option<&OsStr>,
+        msg: &'static str,
+        pool: &mut InternPool,
+    ) -> Result<SymbolId, Error> {
+        if let Some(name) = name.and_then(OsStr::to_str)
+            && let Some(first) = name.chars().next()
+            && (first.is_alphabetic() || first == '_')
+            && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+        {
+            let id = pool.insert_symbol(name.to_string());
+            if !intern_pool::is_keyword(&id) {
+                return Ok(id);
+            }
+        }
+        Err(Error {
+            typ: ErrorType::Module,
+            msg,
+            span: Span::path_only(pool.insert_path(path.to_path_buf())),
+        })
     }
 
     fn read_file(path: &Path, pool: &mut InternPool) -> Result<String, Error> {
@@ -29,11 +56,44 @@ impl SyntacticParser {
         }
     }
 
+    /// Invalid UTF-8 is reported where it starts, instead of as an IO error.
+    fn read_source(path: &Path, pool: &mut InternPool) -> Result<String, Error> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                return Err(Error {
+                    typ: ErrorType::Io(Box::new(err)),
+                    msg: "Failed to read file",
+                    span: Span::path_only(pool.insert_path(path.to_path_buf())),
+                });
+            }
+        };
+        let err = match String::from_utf8(bytes) {
+            Ok(code) => return Ok(code),
+            Err(err) => err,
+        };
+        let offset = err.utf8_error().valid_up_to();
+        let valid = std::str::from_utf8(&err.as_bytes()[..offset]).unwrap();
+        let line_start = valid.rfind('\n').map_or(0, |newline| newline + 1);
+        Err(Error {
+            typ: ErrorType::InvalidUtf8 { offset },
+            msg: "File isn't valid UTF-8",
+            span: Span {
+                path: pool.insert_path(path.to_path_buf()),
+                line: valid.matches('\n').count() + 1,
+                column: valid[line_start..].chars().count() + 1,
+                index: valid.chars().count(),
+                size: 1,
+            },
+        })
+    }
+
     fn parse_module_file(
         module_path: &Path,
         queue: &mut HashSet<PathBuf>,
         modules: &HashMap<SymbolId, Module>,
         pool: &mut InternPool,
+        search_paths: &[PathBuf],
     ) -> Result<HashSet<SymbolId>, Error> {
         let module_file = module_path.join("module.json");
         let content = Self::read_file(&module_file, pool)?;
@@ -49,8 +109,9 @@ impl SyntacticParser {
         };
         let mut ret = HashSet::with_capacity(dependencies.len());
         for dep in dependencies {
-            let path = PathBuf::from_str(&dep).unwrap();
-            let name = SyntacticParser::path_to_module_name(&path, pool);
+            let path =
+                Self::resolve_dependency(module_path, &module_file, dep, pool, search_paths)?;
+            let name = SyntacticParser::path_to_module_name(&path, pool)?;
             if queue.contains(&path) || modules.contains_key(&name) {
                 continue;
             }
@@ -60,6 +121,48 @@ impl SyntacticParser {
         Ok(ret)
     }
 
+    /// Dependencies are relative to the module directory unless absolute.
+    /// Relative ones that aren't found there are looked for in the search
+    ///     paths. The error names where it was expected next to the manifest.
+    /// They're canonicalized here so a missing module is reported against
+    ///     the manifest that names it, before anything else is parsed.
+    fn resolve_dependency(
+        module_path: &Path,
+        module_file: &Path,
+        dependency: String,
+        pool: &mut InternPool,
+        search_paths: &[PathBuf],
+    ) -> Result<PathBuf, Error> {
+        let relative = PathBuf::from_str(&dependency).unwrap();
+        let path = module_path.join(&relative);
+        let resolved = match fs::canonicalize(&path) {
+            Ok(resolved) if resolved.join("module.json").is_file() => return Ok(resolved),
+            Ok(resolved) => resolved,
+            Err(_) => path,
+        };
+        if relative.is_relative() {
+            for root in search_paths {
+                if let Ok(found) = fs::canonicalize(root.join(&relative))
+                    && found.join("module.json").is_file()
+                {
+                    return Ok(found);
+                }
+            }
+        }
+        Err(Error {
+            typ: ErrorType::ModuleNotFound {
+                dependency: pool.insert_symbol(dependency),
+                resolved: pool.insert_path(resolved),
+            },
+            msg: "Dependency is not a module",
+            span: Span::path_only(pool.insert_path(module_file.to_path_buf())),
+        })
+    }
+
+    fn is_source_file(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "code")
+    }
+
     fn read_dir(dir: &Path, pool: &mut InternPool) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
         let mut files = Vec::new();
         let mut dirs = Vec::new();
@@ -79,17 +182,9 @@ impl SyntacticParser {
             }
             let path = entry.unwrap().path();
             if path.is_file() {
-                if path.extension().is_none() {
-                    continue;
-                }
-                let ext = match path.extension().unwrap().to_str() {
-                    Some(ext) => ext,
-                    None => continue,
-                };
-                if ext != "code" {
-                    continue;
+                if Self::is_source_file(&path) {
+                    files.push(path);
                 }
-                files.push(path);
             } else if path.is_dir() && path.join("module.json").exists() {
                 dirs.push(path);
             }
@@ -102,30 +197,40 @@ impl SyntacticParser {
         queue: &mut HashSet<PathBuf>,
         modules: &HashMap<SymbolId, Module>,
         pool: &mut InternPool,
+        options: &ParserOptions,
     ) -> Result<Module, Error> {
-        let dependencies = Self::parse_module_file(module_path, queue, modules, pool)?;
+        let dependencies =
+            Self::parse_module_file(module_path, queue, modules, pool, &options.search_paths)?;
         let mut files = HashMap::new();
         let (file_paths, module_paths) = Self::read_dir(module_path, pool)?;
-        let module_name = Self::path_to_module_name(module_path, pool);
+        // Nothing could be resolved in it, so it's reported here instead
+        //     of as missing names later on.
+        if file_paths.is_empty() && module_paths.is_empty() {
+            return Err(Error {
+                typ: ErrorType::Module,
+                msg: "Module has no source files",
+                span: Span::path_only(pool.insert_path(module_path.to_path_buf())),
+            });
+        }
+        let module_name = Self::path_to_module_name(module_path, pool)?;
         for path in file_paths {
-            let code = match fs::read_to_string(&path) {
-                Ok(code) => code,
-                Err(err) => {
-                    return Err(Error {
-                        typ: ErrorType::Io(Box::new(err)),
-                        msg: "Failed to read file",
-                        span: Span::path_only(pool.insert_path(path)),
-                    });
-                }
-            };
-            let filename = Self::path_to_module_name(&path, pool);
+            let code = Self::read_source(&path, pool)?;
+            let filename = Self::path_to_filename(&path, pool)?;
             let path_id = pool.insert_path(path);
-            let file = Self::parse_code(path_id, &code, filename, module_name, pool)?;
+            let options = options.clone();
+            let file = Self::parse_code_with_options(
+                path_id,
+                &code,
+                filename,
+                module_name,
+                pool,
+                options,
+            )?;
             files.insert(filename, file);
         }
         let mut submodules = HashMap::new();
         for path in module_paths {
-            let name = Self::path_to_module_name(&path, pool);
+            let name = Self::path_to_module_name(&path, pool)?;
             if files.contains_key(&name) {
                 return Err(Error {
                     typ: ErrorType::Module,
@@ -133,7 +238,7 @@ impl SyntacticParser {
                     span: Span::path_only(pool.insert_path(path)),
                 });
             }
-            let submodule = Self::parse_module(&path, queue, modules, pool)?;
+            let submodule = Self::parse_module(&path, queue, modules, pool, options)?;
             submodules.insert(name, submodule);
         }
         Ok(Module {
@@ -145,7 +250,51 @@ impl SyntacticParser {
         })
     }
 
+    /// A file given as the entry is a module of its own with no dependencies,
+    ///     for trying things out without a manifest. The module is named
+    ///     after the file, so `hello.code` starts with `module hello;`.
+    fn parse_single_file(
+        path: &Path,
+        pool: &mut InternPool,
+        options: &ParserOptions,
+    ) -> Result<Ast, Error> {
+        let code = Self::read_source(path, pool)?;
+        let name = Self::path_to_filename(path, pool)?;
+        let path_id = pool.insert_path(path.to_path_buf());
+        let file =
+            Self::parse_code_with_options(path_id, &code, name, name, pool, options.clone())?;
+        let module = Module {
+            path: path_id,
+            name,
+            files: HashMap::from([(name, file)]),
+            submodules: HashMap::new(),
+            dependencies: HashSet::new(),
+        };
+        Ok(Ast {
+            entry: name,
+            modules: HashMap::from([(name, module)]),
+        })
+    }
+
     pub(crate) fn parse_modules(module_path: &Path, pool: &mut InternPool) -> Result<Ast, Error> {
+        Self::parse_modules_with_options(module_path, pool, &ParserOptions::default())
+    }
+
+    pub(crate) fn parse_modules_with_options(
+        module_path: &Path,
+        pool: &mut InternPool,
+        options: &ParserOptions,
+    ) -> Result<Ast, Error> {
+        if module_path.is_file() {
+            if !Self::is_source_file(module_path) {
+                return Err(Error {
+                    typ: ErrorType::Module,
+                    msg: "Expected a module directory or a `.code` file",
+                    span: Span::path_only(pool.insert_path(module_path.to_path_buf())),
+                });
+            }
+            return Self::parse_single_file(module_path, pool, options);
+        }
         let entry = module_path.to_path_buf();
         let mut queue = HashSet::new();
         let mut modules = HashMap::new();
@@ -161,12 +310,12 @@ impl SyntacticParser {
                     span: Span::path_only(pool.insert_path(path)),
                 });
             }
-            let module = Self::parse_module(&path, &mut queue, &modules, pool)?;
-            modules.insert(Self::path_to_module_name(&path, pool), module);
+            let module = Self::parse_module(&path, &mut queue, &modules, pool, options)?;
+            modules.insert(Self::path_to_module_name(&path, pool)?, module);
             queue.remove(&path);
         }
         Ok(Ast {
-            entry: Self::path_to_module_name(&entry, pool),
+            entry: Self::path_to_module_name(&entry, pool)?,
             modules,
         })
     }
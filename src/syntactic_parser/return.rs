@@ -1,11 +1,18 @@
 use super::*;
+use syntax_ast::Return;
 
 impl SyntacticParser {
     pub(super) fn parse_return(&mut self) -> Result<Statement, Error> {
         std::debug_assert!(self.is_keyword(TokenType::Return));
+        let start = self.peek().unwrap().span;
         self.advance();
-        let exp = self.parse_expression()?;
+        let value = if self.is_keyword(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        let span = start.merge(self.back()?.span);
         self.end_line()?;
-        Ok(Statement::Return(exp))
+        Ok(Statement::Return(Return { value, span }))
     }
 }
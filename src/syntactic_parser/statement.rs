@@ -1,24 +1,27 @@
 use super::*;
 
 impl SyntacticParser {
-    pub(super) fn parse_statement(
-        &mut self,
-        pool: &mut InternPool,
-    ) -> Result<Statement, Error> {
+    pub(super) fn parse_statement(&mut self) -> Result<Statement, Error> {
         let token = self.expect_token(ErrorType::Statement, "Expected statement")?;
         let TokenValue::Keyword(kw) = token.value else {
             return self.parse_assignment_or_expression(true);
         };
         match kw {
-            TokenType::If => self.parse_conditional(pool),
-            TokenType::Match => self.parse_match(pool),
-            TokenType::For | TokenType::While => self.parse_loop(pool),
+            TokenType::If => self.parse_conditional(),
+            TokenType::Match => self.parse_match(),
+            TokenType::For | TokenType::While | TokenType::Loop => self.parse_loop(),
             TokenType::Let | TokenType::Var => {
                 Ok(Statement::Declaration(self.parse_declaration()?))
             }
             TokenType::Return => self.parse_return(),
-            TokenType::Continue => Ok(Statement::Continue(token.span)),
-            TokenType::Break => Ok(Statement::Break(token.span)),
+            TokenType::Continue => self.parse_continue(),
+            TokenType::Break => self.parse_break(),
+            // Assignments through a pointer, like `*p = 1;` or `(*p).x = 1;`.
+            TokenType::Mul | TokenType::OpenParen => self.parse_assignment_or_expression(true),
+            // Struct literals start with their type's name, so a `{` here is
+            //     always a block. An array literal can't be a statement anyway.
+            TokenType::OpenBracket => Ok(Statement::Block(self.parse_block()?)),
+            TokenType::MatchCase => Err(self.stray_match_case()),
             _ => Err(self.error(ErrorType::Statement, "Invalid statement")),
         }
     }
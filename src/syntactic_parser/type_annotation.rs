@@ -2,24 +2,56 @@ use super::*;
 use syntax_ast::{FunctionSig, TypeAnnot, TypeAnnotBase, TypeModifier, TypeModifierType};
 
 impl SyntacticParser {
+    /// Function and tuple types nest like expressions do, so they count
+    ///     towards the same depth limit.
+    /// Errors at the end of the input have no token to point at, so they
+    ///     cover the unfinished annotation instead, or the token before it
+    ///     if it's empty. The innermost annotation is the one reported.
     pub(super) fn parse_type_annotation(&mut self) -> Result<TypeAnnot, Error> {
+        let start = self.index;
+        self.nested(Self::parse_type_annotation_nested)
+            .map_err(|mut err| {
+                if err.span.size == 0
+                    && let Some(last) = self.tokens.last()
+                {
+                    let first = self.tokens.get(start).unwrap_or(last);
+                    err.span = first.span.merge(last.span);
+                }
+                err
+            })
+    }
+
+    fn parse_type_annotation_nested(&mut self) -> Result<TypeAnnot, Error> {
         let mut modifiers = Vec::new();
         let start = self.peek();
         loop {
             let token =
                 self.expect_token(ErrorType::TypeAnnotation, "Expected a type annotation")?;
             match token.value {
-                TokenValue::Identifier(_) => {
+                TokenValue::Keyword(kw @ (TokenType::Mul | TokenType::OpenBrace)) => {
+                    if modifiers.len() == self.options.max_type_modifiers {
+                        return Err(
+                            self.error(ErrorType::NestingTooDeep, "Too many type modifiers")
+                        );
+                    }
+                    self.advance();
+                    modifiers.push(self.parse_type_modifier(kw)?);
+                }
+                // Other keywords can't start a type, so they're reported as
+                //     they are instead of being skipped.
+                TokenValue::Keyword(kw)
+                    if kw != TokenType::Fn && kw != TokenType::OpenParen && !kw.is_primitive() =>
+                {
+                    return Err(self.error(ErrorType::TypeAnnotation, "Expected a type annotation"));
+                }
+                TokenValue::Identifier(_) | TokenValue::Keyword(_) => {
+                    let base = self.parse_base()?;
                     return Ok(TypeAnnot {
-                        base: self.parse_base()?,
+                        base,
                         modifiers,
-                        span: token.span - start.unwrap().span,
+                        span: start.unwrap().span.merge(self.back()?.span),
                     });
                 }
-                TokenValue::Keyword(kw) => {
-                    self.advance();
-                    modifiers.push(self.parse_type_modifier(kw)?);
-                }
                 _ => {
                     return Err(self.error(ErrorType::TypeAnnotation, "Expected a type annotation"));
                 }
@@ -28,19 +60,54 @@ impl SyntacticParser {
     }
 
     fn parse_base(&mut self) -> Result<TypeAnnotBase, Error> {
+        let start = self.peek().unwrap().span;
+        if let Some(Token {
+            value: TokenValue::Keyword(kw),
+            ..
+        }) = self.peek()
+            && kw.is_primitive()
+        {
+            self.advance();
+            return Ok(TypeAnnotBase::Normal(vec![
+                intern_pool::get_token_type_symbol_id(kw),
+            ]));
+        }
+        if self.is_keyword(TokenType::OpenParen) {
+            let elements = self.parse_type_list("Expected tuple element types")?;
+            if elements.len() < 2 {
+                return Err(Error {
+                    typ: ErrorType::TypeAnnotation,
+                    msg: "A tuple needs at least two elements",
+                    span: start.merge(self.back()?.span),
+                });
+            }
+            return Ok(TypeAnnotBase::Tuple(elements));
+        }
         if !self.is_keyword(TokenType::Fn) {
             return Ok(TypeAnnotBase::Normal(self.parse_name()?));
         }
         self.advance();
-        self.expect_keyword(
-            TokenType::OpenParen,
-            ErrorType::TypeAnnotation,
-            "Expected function argument types",
-        )?;
+        let args = self.parse_type_list("Expected function argument types")?;
+        if !self.is_keyword(TokenType::ReturnType) {
+            return Ok(TypeAnnotBase::Function(FunctionSig { args, ret: None }));
+        }
+        self.advance();
+        Ok(TypeAnnotBase::Function(FunctionSig {
+            args,
+            ret: Some(Box::new(self.parse_type_annotation()?)),
+        }))
+    }
+
+    /// Parses a parenthesized, comma-separated list of type annotations.
+    pub(super) fn parse_type_list(
+        &mut self,
+        message: &'static str,
+    ) -> Result<Vec<TypeAnnot>, Error> {
+        self.expect_keyword(TokenType::OpenParen, ErrorType::TypeAnnotation, message)?;
         self.advance();
-        let mut args = Vec::new();
+        let mut types = Vec::new();
         while !self.is_keyword(TokenType::CloseParen) {
-            args.push(self.parse_type_annotation()?);
+            types.push(self.parse_type_annotation()?);
             if !self.is_keyword(TokenType::Comma) {
                 break;
             }
@@ -52,24 +119,15 @@ impl SyntacticParser {
             "Expected `)`",
         )?;
         self.advance();
-        if !self.is_keyword(TokenType::ReturnType) {
-            return Ok(TypeAnnotBase::Function(FunctionSig { args, ret: None }));
-        }
-        self.advance();
-        Ok(TypeAnnotBase::Function(FunctionSig {
-            args,
-            ret: Some(Box::new(self.parse_type_annotation()?)),
-        }))
+        Ok(types)
     }
 
     fn parse_type_modifier(&mut self, keyword: TokenType) -> Result<TypeModifier, Error> {
-        Ok(match keyword {
-            TokenType::Mul => self.parse_pointer()?,
-            TokenType::OpenBrace => self.parse_array_or_slice()?,
-            _ => {
-                return Err(self.error(ErrorType::TypeAnnotation, "Expected a type annotation"));
-            }
-        })
+        match keyword {
+            TokenType::Mul => self.parse_pointer(),
+            TokenType::OpenBrace => self.parse_array_or_slice(),
+            _ => unreachable!("only `*` and `[` start type modifiers"),
+        }
     }
 
     fn parse_pointer(&mut self) -> Result<TypeModifier, Error> {
@@ -81,12 +139,12 @@ impl SyntacticParser {
         })
     }
 
+    /// The size of an array is a constant expression, folded in the semantic pass.
     fn parse_array_or_slice(&mut self) -> Result<TypeModifier, Error> {
-        let (is_array, array_size) = if let Some(uint) = self.is_uint() {
-            self.advance();
-            (true, uint)
+        let array_size = if self.is_keyword(TokenType::CloseBrace) {
+            None
         } else {
-            (false, 0)
+            Some(self.parse_expression()?)
         };
         if !self.is_keyword(TokenType::CloseBrace) {
             return Err(self.error(ErrorType::TypeAnnotation, "Expected `]`"));
@@ -96,10 +154,9 @@ impl SyntacticParser {
         self.advance();
         Ok(TypeModifier {
             mutable,
-            typ: if is_array {
-                TypeModifierType::Array(array_size)
-            } else {
-                TypeModifierType::Slice
+            typ: match array_size {
+                Some(size) => TypeModifierType::Array(Box::new(size)),
+                None => TypeModifierType::Slice,
             },
         })
     }
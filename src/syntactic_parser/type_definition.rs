@@ -5,10 +5,10 @@ This crate parses all kinds of type definitions.
 - Enum
 - Alias
 
-Structs are of the following format:
+Structs are of the following format, where the default values are optional:
 ```
 struct Point {
-    x: u64,
+    x: u64 = 0,
     y: u64,
 }
 ```
@@ -21,14 +21,23 @@ union Person {
 }
 ```
 
-Enums are of the following format:
+Enums are of the following format, where the backing type is optional:
 ```
-enum PersonType {
-    Teacher,
+enum PersonType : i8 {
+    Teacher = -1,
     Student = 10,
 }
 ```
 
+Variants may carry payloads, which makes the enum a tagged union:
+```
+enum Shape {
+    Circle(f64),
+    Rect(f64, f64),
+    Empty,
+}
+```
+
 Aliases are of the following format:
 ```
 use Names = []var []var u8;
@@ -37,21 +46,28 @@ use Names = []var []var u8;
 
 use super::*;
 use std::collections::{HashMap, HashSet};
-use syntax_ast::{TypeAnnot, TypeDef, TypeDefBody};
+use syntax_ast::{
+    Attribute, Enum, Expression, Struct, TaggedEnum, TypeAnnot, TypeDef, TypeDefBody,
+};
 
 impl SyntacticParser {
-    pub(super) fn parse_type_definition(&mut self) -> Result<TypeDef, Error> {
+    pub(super) fn parse_type_definition(
+        &mut self,
+        attributes: Vec<Attribute>,
+    ) -> Result<TypeDef, Error> {
         let token = self.peek().unwrap();
         let TokenValue::Keyword(kw) = token.value else {
             panic!("Type definition starts with a keyword");
         };
-        match kw {
+        let mut typ = match kw {
             TokenType::Struct => self.parse_struct(),
             TokenType::Enum => self.parse_enum(),
             TokenType::Union => self.parse_union(),
             TokenType::Use => self.parse_alias(),
             _ => panic!("Invalid keyword for type definition"),
-        }
+        }?;
+        typ.attributes = attributes;
+        Ok(typ)
     }
 
     fn parse_struct(&mut self) -> Result<TypeDef, Error> {
@@ -62,15 +78,22 @@ impl SyntacticParser {
             .ok_or(self.error(ErrorType::TypeDefinition, "Expected an identifier"))?;
         let span = self.peek().unwrap().span;
         self.advance();
-        let fields = self.parse_struct_body()?;
+        let mut defaults = HashMap::new();
+        let fields = self.parse_struct_body(Some(&mut defaults))?;
         Ok(TypeDef {
             name,
-            body: TypeDefBody::Struct(fields),
+            body: TypeDefBody::Struct(Struct { fields, defaults }),
             span,
+            attributes: Vec::new(),
         })
     }
 
-    fn parse_struct_body(&mut self) -> Result<HashMap<SymbolId, TypeAnnot>, Error> {
+    /// Union fields can't have default values, so they're only parsed
+    ///     if there's somewhere to put them.
+    fn parse_struct_body(
+        &mut self,
+        mut defaults: Option<&mut HashMap<SymbolId, Expression>>,
+    ) -> Result<HashMap<SymbolId, TypeAnnot>, Error> {
         if !self.is_keyword(TokenType::OpenBracket) {
             return Err(self.error(ErrorType::TypeDefinition, "Expected `{`"));
         }
@@ -82,6 +105,16 @@ impl SyntacticParser {
                 return Err(self.error(ErrorType::TypeDefinition, "Duplicated struct field "));
             }
             fields.insert(name, field_type);
+            if self.is_keyword(TokenType::Assign) {
+                let Some(defaults) = defaults.as_deref_mut() else {
+                    return Err(self.error(
+                        ErrorType::TypeDefinition,
+                        "Union fields can't have default values",
+                    ));
+                };
+                self.advance();
+                defaults.insert(name, self.parse_expression()?);
+            }
             if !self.is_keyword(TokenType::Comma) {
                 break;
             }
@@ -115,24 +148,42 @@ impl SyntacticParser {
             .ok_or(self.error(ErrorType::TypeDefinition, "Expected an identifier"))?;
         let span = self.peek().unwrap().span;
         self.advance();
-        let fields = self.parse_enum_body()?;
+        let backing = if self.is_keyword(TokenType::Colon) {
+            self.advance();
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+        let mut payloads = HashMap::new();
+        let variants = self.parse_enum_body(&mut payloads)?;
+        let tags = Enum { backing, variants };
+        let body = if payloads.values().all(Vec::is_empty) {
+            TypeDefBody::Enum(tags)
+        } else {
+            TypeDefBody::TaggedEnum(TaggedEnum { tags, payloads })
+        };
         Ok(TypeDef {
             name,
-            body: TypeDefBody::Enum(fields),
+            body,
             span,
+            attributes: Vec::new(),
         })
     }
 
-    fn parse_enum_body(&mut self) -> Result<HashMap<SymbolId, u64>, Error> {
+    /// Returns the values of the variants. Their payload types are put in `payloads`.
+    fn parse_enum_body(
+        &mut self,
+        payloads: &mut HashMap<SymbolId, Vec<TypeAnnot>>,
+    ) -> Result<HashMap<SymbolId, i128>, Error> {
         if !self.is_keyword(TokenType::OpenBracket) {
             return Err(self.error(ErrorType::TypeDefinition, "Expected `{`"));
         }
         self.advance();
         let mut fields = HashMap::new();
         let mut values = HashSet::new();
-        let mut counter: u64 = 0;
+        let mut counter: i128 = 0;
         while !self.is_keyword(TokenType::CloseBracket) {
-            let (name, value) = self.parse_enum_field(counter)?;
+            let (name, payload, value) = self.parse_enum_field(counter)?;
             if fields.contains_key(&name) {
                 return Err(self.error(ErrorType::TypeDefinition, "Duplicated enum field"));
             }
@@ -140,6 +191,7 @@ impl SyntacticParser {
                 return Err(self.error(ErrorType::TypeDefinition, "Duplicated enum value"));
             }
             fields.insert(name, value);
+            payloads.insert(name, payload);
             values.insert(value);
             counter = value + 1;
             if !self.is_keyword(TokenType::Comma) {
@@ -155,21 +207,41 @@ impl SyntacticParser {
         Ok(fields)
     }
 
-    fn parse_enum_field(&mut self, counter: u64) -> Result<(SymbolId, u64), Error> {
+    /// A variant without a value gets the one after the previous variant's.
+    /// No backing type holds more than u64::MAX, so counting past it is
+    ///     reported here. Smaller backing types are checked semantically.
+    fn parse_enum_field(
+        &mut self,
+        counter: i128,
+    ) -> Result<(SymbolId, Vec<TypeAnnot>, i128), Error> {
         let id = self
             .is_identifier()
             .ok_or(self.error(ErrorType::TypeDefinition, "Expected an identifier"))?;
+        let span = self.peek().unwrap().span;
         self.advance();
+        let payload = if self.is_keyword(TokenType::OpenParen) {
+            self.parse_type_list("Expected payload types")?
+        } else {
+            Vec::new()
+        };
         if !self.is_keyword(TokenType::Assign) {
-            return Ok((id, counter));
+            if counter > u64::MAX as i128 {
+                return Err(Error {
+                    typ: ErrorType::TypeDefinition,
+                    msg: "Enum discriminant overflow",
+                    span,
+                });
+            }
+            return Ok((id, payload, counter));
         }
         self.advance();
-        let value = self.is_uint().ok_or(self.error(
-            ErrorType::TypeDefinition,
-            "Expected a positive integer value",
-        ))?;
+        let value = match self.peek().map(|token| token.value) {
+            Some(TokenValue::Literal(token::Literal::UInt(uint, _))) => uint as i128,
+            Some(TokenValue::Literal(token::Literal::Int(int))) => int as i128,
+            _ => return Err(self.error(ErrorType::TypeDefinition, "Expected an integer value")),
+        };
         self.advance();
-        Ok((id, value))
+        Ok((id, payload, value))
     }
 
     fn parse_union(&mut self) -> Result<TypeDef, Error> {
@@ -183,11 +255,12 @@ impl SyntacticParser {
         };
         let span = self.peek().unwrap().span;
         self.advance();
-        let fields = self.parse_struct_body()?;
+        let fields = self.parse_struct_body(None)?;
         Ok(TypeDef {
             name,
             body: TypeDefBody::Union(fields),
             span,
+            attributes: Vec::new(),
         })
     }
 
@@ -202,7 +275,7 @@ impl SyntacticParser {
         };
         let span = self.peek().unwrap().span;
         self.advance();
-        if !self.is_keyword(TokenType::Eq) {
+        if !self.is_keyword(TokenType::Assign) {
             return Err(self.error(ErrorType::TypeDefinition, "Expected `=`"));
         }
         self.advance();
@@ -212,6 +285,7 @@ impl SyntacticParser {
             name,
             body: TypeDefBody::Alias(typ),
             span,
+            attributes: Vec::new(),
         })
     }
 }
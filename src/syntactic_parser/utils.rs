@@ -5,8 +5,33 @@ impl SyntacticParser {
         self.tokens.get(self.index).cloned()
     }
 
-    pub(super) fn back(&self) -> Token {
-        self.tokens[self.index - 1].clone()
+    /// Looks n tokens ahead of the current one. `peek_n(0)` is the same as `peek()`.
+    pub(super) fn peek_n(&self, n: usize) -> Option<Token> {
+        self.tokens.get(self.index + n).cloned()
+    }
+
+    pub(super) fn peek2(&self) -> Option<Token> {
+        self.peek_n(1)
+    }
+
+    /// Checks whether the token n positions ahead is the given keyword.
+    pub(super) fn is_keyword_n(&self, n: usize, keyword: TokenType) -> bool {
+        matches!(
+            self.peek_n(n),
+            Some(Token {
+                value: TokenValue::Keyword(kwd),
+                ..
+            }) if kwd == keyword
+        )
+    }
+
+    /// The token before the current one, which is usually where a span ends.
+    /// Errors instead of panicking if nothing has been consumed yet.
+    pub(super) fn back(&self) -> Result<Token, Error> {
+        match self.index.checked_sub(1) {
+            Some(index) => Ok(self.tokens[index].clone()),
+            None => Err(self.error(ErrorType::Expression, "No preceding token")),
+        }
     }
 
     pub(super) fn error(&self, typ: ErrorType, msg: &'static str) -> Error {
@@ -17,6 +42,12 @@ impl SyntacticParser {
         Error { typ, msg, span }
     }
 
+    /// An error for a `=>` at the next token, which otherwise would be reported
+    ///     as whatever the surrounding code happened to expect there.
+    pub(super) fn stray_match_case(&self) -> Error {
+        self.error(ErrorType::Expression, "`=>` is only valid in match arms")
+    }
+
     pub(super) fn expect_token(
         &self,
         error_type: ErrorType,
@@ -71,7 +102,9 @@ impl SyntacticParser {
     }
 
     pub(super) fn end_line(&mut self) -> Result<(), Error> {
-        if !self.is_keyword(TokenType::Semicolon) {
+        if self.is_keyword(TokenType::MatchCase) {
+            Err(self.stray_match_case())
+        } else if !self.is_keyword(TokenType::Semicolon) {
             Err(self.error(ErrorType::LineEnd, "`;` expected at end of line"))
         } else {
             self.advance();
@@ -100,25 +133,54 @@ impl SyntacticParser {
         }
     }
 
-    pub(super) fn is_uint(&self) -> Option<u64> {
-        let token = self.peek()?;
-        match token.value {
-            TokenValue::Literal(token::Literal::UInt(uint)) => Some(uint),
-            _ => None,
+    /// Runs f one nesting level deeper, failing if that's deeper than allowed.
+    pub(super) fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error(ErrorType::NestingTooDeep, "Nesting too deep"));
         }
+        self.depth += 1;
+        let ret = f(self);
+        self.depth -= 1;
+        ret
     }
 
-    pub(super) fn parse_block(&mut self, pool: &mut InternPool) -> Result<Vec<Statement>, Error> {
+    /// Errors at the opening delimiter if the input ends before it's closed,
+    ///     since an error at the end of the input wouldn't say which one it is.
+    pub(super) fn check_unclosed(&self, close: TokenType, opener: Span) -> Result<(), Error> {
+        if self.peek().is_some() {
+            return Ok(());
+        }
+        let msg = match close {
+            TokenType::CloseParen => "Unclosed `(`",
+            TokenType::CloseBrace => "Unclosed `[`",
+            TokenType::CloseBracket => "Unclosed `{`",
+            _ => panic!("Not a closing delimiter"),
+        };
+        Err(Error {
+            typ: ErrorType::UnclosedDelimiter,
+            msg,
+            span: opener,
+        })
+    }
+
+    pub(super) fn parse_block(&mut self) -> Result<Vec<Statement>, Error> {
         if !self.is_keyword(TokenType::OpenBracket) {
             return Err(self.error(ErrorType::Conditional, "Expected contional body"));
         }
+        let opener = self.peek().unwrap().span;
         self.advance();
-        let mut statements = Vec::new();
-        while !self.is_keyword(TokenType::CloseBracket) {
-            statements.push(self.parse_statement(pool)?);
-        }
-        self.advance();
-        Ok(statements)
+        self.nested(|parser| {
+            let mut statements = Vec::new();
+            while !parser.is_keyword(TokenType::CloseBracket) {
+                parser.check_unclosed(TokenType::CloseBracket, opener)?;
+                statements.push(parser.parse_statement()?);
+            }
+            parser.advance();
+            Ok(statements)
+        })
     }
 
     pub(super) fn parse_name(&mut self) -> Result<Name, Error> {
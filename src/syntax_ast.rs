@@ -1,15 +1,16 @@
-use crate::intern_pool::{PathId, SymbolId};
+use crate::intern_pool::{PathId, StringId, SymbolId};
 use crate::span::Span;
+use crate::token::{FloatSuffix, NumberBase};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Ast {
     pub(crate) entry: SymbolId,
     pub(crate) modules: HashMap<SymbolId, Module>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Module {
     pub(crate) name: SymbolId,
     pub(crate) files: HashMap<SymbolId, File>,
@@ -18,11 +19,14 @@ pub(crate) struct Module {
     pub(crate) path: PathId,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct File {
     pub(crate) name: SymbolId,
     pub(crate) module: SymbolId,
     pub(crate) imports: HashMap<SymbolId, Span>,
+    /// `import module::item;` or `import module::file::item;`,
+    ///     keyed by the name of the item.
+    pub(crate) item_imports: HashMap<SymbolId, ItemImport>,
     pub(crate) globals: HashMap<SymbolId, Scope<Declaration>>,
     pub(crate) functions: HashMap<SymbolId, Scope<Function>>,
     pub(crate) types: HashMap<SymbolId, Scope<TypeDef>>,
@@ -30,96 +34,164 @@ pub(crate) struct File {
 
 pub(crate) type Name = Vec<SymbolId>;
 
+/// A single item imported into the file. The path is `module::...::file`,
+///     so the item is qualified the same way as in expressions, or just
+///     `module`, for an item defined in one of the module's files.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct ItemImport {
+    pub(crate) path: Name,
+    pub(crate) item: SymbolId,
+    pub(crate) span: Span,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub(crate) enum Visibility {
+    /// `pub`: visible everywhere.
     Public,
+    /// `prv`: only visible in the file.
     Private,
+    /// `mod` or `pub(module)`: visible in the files of the module.
     Module,
+    /// `pub(super)`: also visible in the parent module.
+    Super,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Scope<T> {
     pub(crate) visibility: Visibility,
     pub(crate) value: T,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct TypeDef {
     pub(crate) name: SymbolId,
     pub(crate) body: TypeDefBody,
     pub(crate) span: Span,
+    pub(crate) attributes: Vec<Attribute>,
+}
+
+/// An attribute like `@packed` written before a definition.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Attribute {
+    pub(crate) name: SymbolId,
+    pub(crate) span: Span,
+    /// The names in parentheses after it, as in `@derive(Eq, Print)`.
+    pub(crate) args: Vec<(SymbolId, Span)>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum TypeDefBody {
-    Struct(HashMap<SymbolId, TypeAnnot>),
-    Enum(HashMap<SymbolId, u64>),
+    Struct(Struct),
+    Enum(Enum),
+    TaggedEnum(TaggedEnum),
     Union(HashMap<SymbolId, TypeAnnot>),
     Alias(TypeAnnot),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Struct {
+    pub(crate) fields: HashMap<SymbolId, TypeAnnot>,
+    /// The `= value` written after a field's type. Struct literals may
+    ///     leave out these fields. Checked to be constant in the semantic pass.
+    pub(crate) defaults: HashMap<SymbolId, Expression>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Enum {
+    /// The type written after `:`, if any.
+    pub(crate) backing: Option<TypeAnnot>,
+    /// Values are checked against the backing type in the semantic pass.
+    pub(crate) variants: HashMap<SymbolId, i128>,
+}
+
+/// An enum with a payload after at least one of its variants, like
+///     `Circle(f64)`. The variants are numbered like a plain enum's,
+///     and each is stored with its payload types, empty if it has none.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct TaggedEnum {
+    pub(crate) tags: Enum,
+    pub(crate) payloads: HashMap<SymbolId, Vec<TypeAnnot>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum Statement {
     Declaration(Declaration),
     Assignment(Assignment),
     Expression(Expression),
     Loop(Loop),
     Continue(Span),
-    Break(Span),
+    Break(Break),
     Conditional(Conditional),
     Match(Match),
-    Return(Expression),
+    Return(Return),
+    /// A bare block, which introduces a new scope.
+    Block(Vec<Statement>),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct FunctionSig {
     pub(crate) args: Vec<TypeAnnot>,
     pub(crate) ret: Option<Box<TypeAnnot>>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum TypeAnnotBase {
     Normal(Name),
     Function(FunctionSig),
+    Tuple(Vec<TypeAnnot>),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct TypeAnnot {
     pub(crate) base: TypeAnnotBase,
     pub(crate) modifiers: Vec<TypeModifier>,
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct TypeModifier {
     pub(crate) mutable: bool,
     pub(crate) typ: TypeModifierType,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum TypeModifierType {
     Pointer,
     Slice,
-    Array(u64),
+    Array(Box<Expression>),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Function {
     pub(crate) name: SymbolId,
     pub(crate) arguments: Vec<FunctionArg>,
     pub(crate) return_type: Option<TypeAnnot>,
-    pub(crate) body: Vec<Statement>,
+    pub(crate) body: FunctionBody,
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) enum FunctionBody {
+    Block(Vec<Statement>),
+    Asm(Asm),
+}
+
+/// Adjacent string literals in an `asm(...)` body, joined as lines,
+///     and the operands after the `:` they refer to as `{0}`, `{1}` and so on.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Asm {
+    pub(crate) code: String,
+    pub(crate) operands: Vec<Expression>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct FunctionArg {
     pub(crate) name: SymbolId,
     pub(crate) typ: TypeAnnot,
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Declaration {
     pub(crate) name: SymbolId,
     pub(crate) mutable: bool,
@@ -128,29 +200,41 @@ pub(crate) struct Declaration {
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Expression {
     pub(crate) value: ExpressionValue,
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum ExpressionValue {
     Binary(Binary),
     Unary(Unary),
     Call(Call),
     Literal(Literal),
     Identifier(Name),
+    Cast(Cast),
+    /// `loop { ... }`, valued by the `break`s in it.
+    Loop(Vec<Statement>),
+    /// `match (value) { case => value, ... }`, valued by the arm taken.
+    Match(Box<MatchExpression>),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+/// `value as typ`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Cast {
+    pub(crate) value: Box<Expression>,
+    pub(crate) typ: TypeAnnot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Binary {
     pub(crate) left: Box<Expression>,
     pub(crate) right: Box<Expression>,
     pub(crate) op: BinaryOp,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub(crate) enum BinaryOp {
     Plus,
     Minus,
@@ -174,13 +258,27 @@ pub(crate) enum BinaryOp {
     FieldAccess,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+impl BinaryOp {
+    pub(crate) fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Gt
+                | BinaryOp::Ge
+                | BinaryOp::Lt
+                | BinaryOp::Le
+                | BinaryOp::Eq
+                | BinaryOp::NotEq
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Unary {
     pub(crate) operand: Box<Expression>,
     pub(crate) op: UnaryOp,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub(crate) enum UnaryOp {
     LogicalNot,
     BitNot,
@@ -189,24 +287,71 @@ pub(crate) enum UnaryOp {
     Negate,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Call {
     pub(crate) function: Box<Expression>,
     pub(crate) args: Vec<Expression>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+/// Numbers are serialized as they'd be written, so dumps of the AST are
+///     stable and readable: integers in their base and floats as the
+///     shortest text that parses back to the same value, with their suffix.
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) enum Literal {
-    String(String),
-    UInt(u64),
+    String(StringId),
+    #[serde(serialize_with = "serialize_uint")]
+    UInt(u64, NumberBase),
     Int(i64),
-    Float(f64),
+    #[serde(serialize_with = "serialize_float")]
+    Float(f64, Option<FloatSuffix>),
     Bool(bool),
     Array(Vec<Expression>),
-    Struct(HashMap<SymbolId, Expression>),
+    /// `{value; count}`, where the count is a constant expression.
+    ArrayRepeat {
+        value: Box<Expression>,
+        count: Box<Expression>,
+    },
+    Struct(StructLiteral),
+    Tuple(Vec<Expression>),
+}
+
+fn serialize_uint<S: serde::Serializer>(
+    value: &u64,
+    base: &NumberBase,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&match base {
+        NumberBase::Binary => format!("0b{value:b}"),
+        NumberBase::Decimal => value.to_string(),
+        NumberBase::Hexadecimal => format!("0x{value:X}"),
+    })
+}
+
+/// Debug formatting is the shortest round-trip representation, and
+///     unlike Display it keeps the `.0` of whole numbers.
+fn serialize_float<S: serde::Serializer>(
+    value: &f64,
+    suffix: &Option<FloatSuffix>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let suffix = match suffix {
+        Some(FloatSuffix::F32) => "f32",
+        Some(FloatSuffix::F64) => "f64",
+        None => "",
+    };
+    serializer.collect_str(&format_args!("{value:?}{suffix}"))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct StructLiteral {
+    /// The struct type written before `{`. Boxed so it doesn't grow every expression.
+    pub(crate) typ: Box<TypeAnnot>,
+    pub(crate) fields: HashMap<SymbolId, Expression>,
+    /// The `..base` expression the fields that aren't given are copied from.
+    pub(crate) base: Option<Box<Expression>>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Assignment {
     pub(crate) left: Expression,
     pub(crate) right: Expression,
@@ -214,7 +359,7 @@ pub(crate) struct Assignment {
     pub(crate) span: Span,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub(crate) enum AssignmentType {
     Assign,
     Plus,
@@ -229,29 +374,64 @@ pub(crate) enum AssignmentType {
     BitXor,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct ConditionalBranch {
     pub(crate) condition: Expression,
     pub(crate) body: Vec<Statement>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Conditional {
     pub(crate) if_branch: ConditionalBranch,
     pub(crate) elif_branches: Vec<ConditionalBranch>,
     pub(crate) else_branch: Option<Vec<Statement>>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Match {
     pub(crate) value: Expression,
     pub(crate) cases: Vec<ConditionalBranch>,
     pub(crate) default: Option<Vec<Statement>>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct MatchExpression {
+    pub(crate) value: Expression,
+    pub(crate) cases: Vec<MatchArm>,
+    pub(crate) default: Option<ValueBlock>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct MatchArm {
+    pub(crate) condition: Expression,
+    pub(crate) body: ValueBlock,
+}
+
+/// The body of a match expression arm. Either a single expression, or
+///     `{ statements; value }` where the last expression has no `;`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct ValueBlock {
+    pub(crate) statements: Vec<Statement>,
+    pub(crate) value: Expression,
+}
+
+/// `break;` or `break value;`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Break {
+    pub(crate) value: Option<Expression>,
+    pub(crate) span: Span,
+}
+
+/// `return;` or `return value;`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Return {
+    pub(crate) value: Option<Expression>,
+    pub(crate) span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub(crate) struct Loop {
-    pub(crate) init: Option<Declaration>,
+    pub(crate) init: Vec<Declaration>,
     pub(crate) condition: Option<Expression>,
     pub(crate) update: Vec<Statement>,
     pub(crate) body: Vec<Statement>,
@@ -1,6 +1,7 @@
 /// This file defines Token.
-use crate::intern_pool::SymbolId;
+use crate::intern_pool::{StringId, SymbolId};
 use crate::span::Span;
+use serde::Serialize;
 
 /// A list of builtin keywords or punctuators.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -11,6 +12,7 @@ pub(crate) enum TokenType {
     Colon,
     DoubleColon,
     Dot,
+    DotDot,
     OpenParen,
     CloseParen,
     OpenBrace,
@@ -57,6 +59,7 @@ pub(crate) enum TokenType {
     Match,
     While,
     For,
+    Loop,
     Break,
     Continue,
     Return,
@@ -72,6 +75,8 @@ pub(crate) enum TokenType {
     Module,
     Import,
     Use,
+    Asm,
+    As,
 
     // Literals
     True,
@@ -91,17 +96,62 @@ pub(crate) enum TokenType {
     F32,
     F64,
     Bool,
+    Void,
+}
+
+impl TokenType {
+    /// How the keyword or punctuator is written.
+    pub(crate) fn as_str(self) -> &'static str {
+        TOKEN_TYPES_STR[TOKEN_TYPES_ENUM.iter().position(|&x| x == self).unwrap()]
+    }
+
+    /// Primitive type names are keywords, but they are used as type names.
+    pub(crate) fn is_primitive(self) -> bool {
+        matches!(
+            self,
+            TokenType::U8
+                | TokenType::U16
+                | TokenType::U32
+                | TokenType::U64
+                | TokenType::Usize
+                | TokenType::I8
+                | TokenType::I16
+                | TokenType::I32
+                | TokenType::I64
+                | TokenType::Isize
+                | TokenType::F32
+                | TokenType::F64
+                | TokenType::Bool
+                | TokenType::Void
+        )
+    }
+}
+
+/// The base an integer literal is written in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub(crate) enum NumberBase {
+    Binary,
+    Decimal,
+    Hexadecimal,
+}
+
+/// The type a float literal is given by its suffix, as in `1.5f32`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub(crate) enum FloatSuffix {
+    F32,
+    F64,
 }
 
 /// Literal values.
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum Literal {
     /// All non-negative integer literals are treated as UInt.
-    UInt(u64),
+    /// The base is how the literal was written.
+    UInt(u64, NumberBase),
     /// Only negative integer literals are treated as Int.
     Int(i64),
-    Float(f64),
-    String(String),
+    Float(f64, Option<FloatSuffix>),
+    String(StringId),
 }
 
 /// Possible token values.
@@ -114,6 +164,8 @@ pub(crate) enum TokenValue {
     Literal(Literal),
     /// A keyword or a punctuator. They are treated the same at this stage.
     Keyword(TokenType),
+    /// An attribute such as `@packed`. The `@` isn't part of the name.
+    Attribute(SymbolId),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -124,28 +176,30 @@ pub(crate) struct Token {
 
 /// This maps each TokenType with its string representation. It's also used to construct
 ///     the InternPool.
-pub(crate) const TOKEN_TYPES_STR: [&str; 79] = [
+pub(crate) const TOKEN_TYPES_STR: [&str; 84] = [
     // Punctuators
-    ",", ";", ":", "::", ".", "(", ")", "[", "]", "{", "}", "+", "+=", "-", "-=", "*", "*=", "/",
-    "/=", "%", "%=", "<<", "<<=", ">>", ">>=", "&", "&=", "|", "|=", "^", "^=", "~", "and", "or",
-    "!", "==", "!=", ">", ">=", "<", "<=", "=", "->", "=>", // Keywords
-    "if", "else", "match", "while", "for", "break", "continue", "return", "fn", "let", "var",
-    "struct", "enum", "union", "pub", "prv", "mod", "module", "import", "use",
+    ",", ";", ":", "::", ".", "..", "(", ")", "[", "]", "{", "}", "+", "+=", "-", "-=", "*", "*=",
+    "/", "/=", "%", "%=", "<<", "<<=", ">>", ">>=", "&", "&=", "|", "|=", "^", "^=", "~", "and",
+    "or", "!", "==", "!=", ">", ">=", "<", "<=", "=", "->", "=>", // Keywords
+    "if", "else", "match", "while", "for", "loop", "break", "continue", "return", "fn", "let",
+    "var", "struct", "enum", "union", "pub", "prv", "mod", "module", "import", "use", "asm", "as",
     // Literals
     "true", "false", // Primitives
     "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize", "f32", "f64", "bool",
+    "void",
 ];
 
 /// Rust doesn't trust programmers to convert an integer back to an enum.
 /// Therefore, all of the enum values here are listed in the order they
 ///     appear in TOKEN_TYPES_STR to perform 2-way conversions.
-pub(crate) const TOKEN_TYPES_ENUM: [TokenType; 79] = [
+pub(crate) const TOKEN_TYPES_ENUM: [TokenType; 84] = [
     // Punctuators
     TokenType::Comma,
     TokenType::Semicolon,
     TokenType::Colon,
     TokenType::DoubleColon,
     TokenType::Dot,
+    TokenType::DotDot,
     TokenType::OpenParen,
     TokenType::CloseParen,
     TokenType::OpenBrace,
@@ -191,6 +245,7 @@ pub(crate) const TOKEN_TYPES_ENUM: [TokenType; 79] = [
     TokenType::Match,
     TokenType::While,
     TokenType::For,
+    TokenType::Loop,
     TokenType::Break,
     TokenType::Continue,
     TokenType::Return,
@@ -206,6 +261,8 @@ pub(crate) const TOKEN_TYPES_ENUM: [TokenType; 79] = [
     TokenType::Module,
     TokenType::Import,
     TokenType::Use,
+    TokenType::Asm,
+    TokenType::As,
     // Literals
     TokenType::True,
     TokenType::False,
@@ -223,6 +280,7 @@ pub(crate) const TOKEN_TYPES_ENUM: [TokenType; 79] = [
     TokenType::F32,
     TokenType::F64,
     TokenType::Bool,
+    TokenType::Void,
 ];
 
 /// A sanity check. They must have the same length.
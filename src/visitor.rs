@@ -0,0 +1,179 @@
+//! Traversal of the semantic AST, so passes don't recurse by hand.
+//! A pass implements `Visitor` and only overrides the nodes it cares about.
+//!     The default methods call the matching `walk_*` function, which visits
+//!     the children, so an override calls it too to keep descending.
+//! Nodes are visited mutably, so passes can rewrite them in place.
+//! Identifiers aren't descended into, since what they refer to is visited
+//!     where it's defined. Neither are custom types, which may contain themselves.
+
+use crate::rw_arc::RwArc;
+use crate::semantic_ast::{
+    ConditionalBranch, Declaration, Expression, ExpressionValue, Literal, MatchArm, Statement,
+    Type, ValueBlock,
+};
+
+pub(crate) trait Visitor {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, exp: &mut Expression) {
+        walk_expression(self, exp);
+    }
+
+    fn visit_type(&mut self, typ: &mut Type) {
+        walk_type(self, typ);
+    }
+}
+
+pub(crate) fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &mut [Statement]) {
+    for statement in block.iter_mut() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub(crate) fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Declaration(declaration) => walk_declaration(visitor, declaration),
+        Statement::Assignment(assignment) => {
+            visitor.visit_expression(&mut assignment.left);
+            visitor.visit_expression(&mut assignment.right);
+        }
+        Statement::Expression(exp) | Statement::Return(Some(exp)) => visitor.visit_expression(exp),
+        Statement::Return(None) => {}
+        Statement::Loop(loop_) => {
+            for declaration in loop_.init.iter() {
+                walk_declaration(visitor, declaration);
+            }
+            if let Some(condition) = &mut loop_.condition {
+                visitor.visit_expression(condition);
+            }
+            walk_block(visitor, &mut loop_.update);
+            walk_block(visitor, &mut loop_.body);
+        }
+        Statement::Continue(_) => {}
+        Statement::Block(block) => walk_block(visitor, block),
+        Statement::Break(break_) => {
+            if let Some(value) = &mut break_.value {
+                visitor.visit_expression(value);
+            }
+        }
+        Statement::Conditional(conditional) => {
+            walk_branch(visitor, &mut conditional.if_branch);
+            for branch in conditional.elif_branches.iter_mut() {
+                walk_branch(visitor, branch);
+            }
+            if let Some(block) = &mut conditional.else_branch {
+                walk_block(visitor, block);
+            }
+        }
+        Statement::Match(match_) => {
+            visitor.visit_expression(&mut match_.value);
+            for case in match_.cases.iter_mut() {
+                walk_branch(visitor, case);
+            }
+            if let Some(block) = &mut match_.default {
+                walk_block(visitor, block);
+            }
+        }
+    }
+}
+
+/// The declaration is write-locked while its type and value are visited.
+fn walk_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &RwArc<Declaration>) {
+    let mut declaration = declaration.write().unwrap();
+    visitor.visit_type(&mut declaration.typ);
+    visitor.visit_expression(&mut declaration.value);
+}
+
+fn walk_branch<V: Visitor + ?Sized>(visitor: &mut V, branch: &mut ConditionalBranch) {
+    visitor.visit_expression(&mut branch.condition);
+    walk_block(visitor, &mut branch.body);
+}
+
+/// Visits the operands before the type of the expression itself.
+pub(crate) fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, exp: &mut Expression) {
+    match &mut exp.value {
+        ExpressionValue::Binary(binary) => {
+            visitor.visit_expression(&mut binary.left);
+            visitor.visit_expression(&mut binary.right);
+        }
+        ExpressionValue::Unary(unary) => visitor.visit_expression(&mut unary.operand),
+        ExpressionValue::Call(call) => {
+            visitor.visit_expression(&mut call.function);
+            for arg in call.args.iter_mut() {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionValue::Literal(literal) => walk_literal(visitor, literal),
+        ExpressionValue::Identifier(_) => {}
+        ExpressionValue::Cast(inner) => visitor.visit_expression(inner),
+        ExpressionValue::Loop(body) => walk_block(visitor, body),
+        ExpressionValue::Match(match_) => {
+            visitor.visit_expression(&mut match_.value);
+            for MatchArm { condition, body } in match_.cases.iter_mut() {
+                visitor.visit_expression(condition);
+                walk_value_block(visitor, body);
+            }
+            if let Some(body) = &mut match_.default {
+                walk_value_block(visitor, body);
+            }
+        }
+    }
+    visitor.visit_type(&mut exp.typ);
+}
+
+fn walk_literal<V: Visitor + ?Sized>(visitor: &mut V, literal: &mut Literal) {
+    match literal {
+        Literal::String(_)
+        | Literal::UInt(_)
+        | Literal::Int(_)
+        | Literal::Float(_)
+        | Literal::Bool(_) => {}
+        Literal::Array(elements)
+        | Literal::Tuple(elements)
+        | Literal::Variant {
+            payload: elements, ..
+        } => {
+            for element in elements.iter_mut() {
+                visitor.visit_expression(element);
+            }
+        }
+        Literal::ArrayRepeat { value, .. } => visitor.visit_expression(value),
+        Literal::Struct(literal) => {
+            for field in literal.fields.values_mut() {
+                visitor.visit_expression(field);
+            }
+            if let Some(base) = &mut literal.base {
+                visitor.visit_expression(base);
+            }
+        }
+    }
+}
+
+fn walk_value_block<V: Visitor + ?Sized>(visitor: &mut V, block: &mut ValueBlock) {
+    walk_block(visitor, &mut block.statements);
+    visitor.visit_expression(&mut block.value);
+}
+
+pub(crate) fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, typ: &mut Type) {
+    match typ {
+        Type::Function(function) => {
+            for arg in function.args.iter_mut() {
+                visitor.visit_type(arg);
+            }
+            if let Some(ret) = &mut function.ret {
+                visitor.visit_type(ret);
+            }
+        }
+        Type::Tuple(elements) => {
+            for element in elements.iter_mut() {
+                visitor.visit_type(element);
+            }
+        }
+        Type::Pointer { inner, .. } | Type::Slice { inner, .. } | Type::Array { inner, .. } => {
+            visitor.visit_type(inner);
+        }
+        _ => {}
+    }
+}